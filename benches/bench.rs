@@ -83,6 +83,38 @@ fn cmp(c: &mut Criterion) {
         .measurement_time(MEASUREMENT_TIME),
     );
 
+    // Arrays and objects of `Value` are pre-allocated from the size hints of the
+    // SIMD tape, the non-SIMD deserializer has none to give
+    c.bench(
+        "de/json/value",
+        ParameterizedBenchmark::new(
+            "toctoc",
+            |b, data| {
+                b.iter_batched(
+                    || data.clone(),
+                    |mut value| {
+                        black_box(
+                            toctoc::json::from_str::<toctoc::json::Value>(&mut value, &mut ())
+                                .unwrap(),
+                        )
+                    },
+                    BatchSize::NumIterations(LEN as u64),
+                )
+            },
+            vec![input_json()],
+        )
+        .with_function("serde_json", |b, data| {
+            b.iter_batched(
+                || data.clone(),
+                |value| black_box(serde_json::from_str::<serde_json::Value>(&value).unwrap()),
+                BatchSize::NumIterations(LEN as u64),
+            )
+        })
+        .throughput(|d| Throughput::Bytes(d.as_bytes().len() as u64))
+        .warm_up_time(WARM_UP_TIME)
+        .measurement_time(MEASUREMENT_TIME),
+    );
+
     c.bench(
         "ser/bson",
         ParameterizedBenchmark::new(
@@ -118,6 +150,27 @@ fn cmp(c: &mut Criterion) {
         .warm_up_time(WARM_UP_TIME)
         .measurement_time(MEASUREMENT_TIME),
     );
+
+    c.bench(
+        "de/bson/u32",
+        ParameterizedBenchmark::new(
+            "toctoc",
+            |b, data| {
+                b.iter_batched(
+                    || data.clone(),
+                    |value| black_box(toctoc::bson::from_bin::<Vec<u32>>(&value, &mut ()).unwrap()),
+                    BatchSize::NumIterations(LEN as u64),
+                )
+            },
+            vec![toctoc::bson::to_bin(
+                &(0..10_000u32).collect::<Vec<_>>(),
+                &mut (),
+            )],
+        )
+        .throughput(|d| Throughput::Bytes(d.len() as u64))
+        .warm_up_time(WARM_UP_TIME)
+        .measurement_time(MEASUREMENT_TIME),
+    );
 }
 
 criterion_group!(benches, cmp);
@@ -1,10 +1,23 @@
+use std::collections::HashSet;
+
+use darling::{FromField, FromVariant};
 use proc_macro2::{Span, TokenStream};
 use syn::punctuated::Punctuated;
 use syn::{
-    parse_quote, GenericParam, Generics, Lifetime, LifetimeDef, TypeParamBound, WhereClause,
-    WherePredicate,
+    parse_quote, Data, GenericArgument, GenericParam, Generics, Ident, Lifetime, LifetimeDef, Path,
+    PathArguments, ReturnType, Type, TypeParamBound, WhereClause, WherePredicate,
 };
 
+use crate::common::{ToctocFieldOptions, ToctocVariantOptions};
+use crate::DeriveResult;
+
+/// Trait the derived impl is for
+#[derive(Clone, Copy, PartialEq)]
+pub enum Trait {
+    Serialize,
+    Deserialize,
+}
+
 pub fn with_lifetime_bound(generics: &Generics, lifetime: &str) -> Generics {
     let bound = Lifetime::new(lifetime, Span::call_site());
     let def = LifetimeDef {
@@ -69,11 +82,66 @@ pub fn within_lifetime_bound(generics: &Generics, lifetime: &str) -> Generics {
     }
 }
 
-pub fn where_clause_with_bound(generics: &Generics, bound: TokenStream) -> WhereClause {
-    let new_predicates = generics.type_params().map::<WherePredicate, _>(|param| {
-        let param = &param.ident;
-        parse_quote!(#param : #bound)
-    });
+/// Types of the fields (de)serialized through their own `Serialize` or `Deserialize`
+/// impls, skipped fields and fields with custom functions are left out
+pub fn field_types(data: &Data, tr: Trait) -> DeriveResult<Vec<&Type>> {
+    let mut fields = vec![];
+    match data {
+        Data::Struct(data) => fields.extend(data.fields.iter()),
+        Data::Enum(data) => {
+            for v in &data.variants {
+                let opt =
+                    ToctocVariantOptions::from_variant(v).map_err(|err| err.write_errors())?;
+                let skipped = match tr {
+                    Trait::Serialize => opt.no_ser,
+                    Trait::Deserialize => opt.no_de,
+                };
+                if !opt.skip && !skipped {
+                    fields.extend(v.fields.iter());
+                }
+            }
+        }
+        Data::Union(_) => {}
+    }
+
+    let mut types = vec![];
+    for f in fields {
+        let opt = ToctocFieldOptions::from_field(f).map_err(|err| err.write_errors())?;
+        let skipped = match tr {
+            Trait::Serialize => {
+                opt.skip_serializing || opt.serialize_with.is_some() || opt.base64.is_some()
+            }
+            Trait::Deserialize => {
+                opt.skip_deserializing || opt.deserialize_with.is_some() || opt.base64.is_some()
+            }
+        };
+        if !opt.skip && !skipped {
+            types.push(&f.ty);
+        }
+    }
+    Ok(types)
+}
+
+/// Adds `bound` to the type parameters mentioned by `types`, the ones that
+/// aren't (like the ones only used inside `PhantomData`) are left unbounded
+pub fn where_clause_with_bound(
+    generics: &Generics,
+    types: &[&Type],
+    bound: TokenStream,
+) -> WhereClause {
+    let params: Vec<_> = generics.type_params().map(|param| &param.ident).collect();
+    let mut used = HashSet::new();
+    for ty in types {
+        type_params(ty, &params, &mut used);
+    }
+
+    let new_predicates = generics
+        .type_params()
+        .filter(|param| used.contains(&param.ident))
+        .map::<WherePredicate, _>(|param| {
+            let param = &param.ident;
+            parse_quote!(#param : #bound)
+        });
 
     let mut generics = generics.clone();
     generics
@@ -82,3 +150,96 @@ pub fn where_clause_with_bound(generics: &Generics, bound: TokenStream) -> Where
         .extend(new_predicates);
     generics.where_clause.unwrap()
 }
+
+/// Collects into `used` every type parameter in `params` mentioned by `ty`
+fn type_params(ty: &Type, params: &[&Ident], used: &mut HashSet<Ident>) {
+    match ty {
+        Type::Path(ty) => {
+            if let Some(qself) = &ty.qself {
+                type_params(&qself.ty, params, used);
+            }
+            path_params(&ty.path, params, used);
+        }
+        Type::Reference(ty) => type_params(&ty.elem, params, used),
+        Type::Ptr(ty) => type_params(&ty.elem, params, used),
+        Type::Slice(ty) => type_params(&ty.elem, params, used),
+        Type::Array(ty) => type_params(&ty.elem, params, used),
+        Type::Paren(ty) => type_params(&ty.elem, params, used),
+        Type::Group(ty) => type_params(&ty.elem, params, used),
+        Type::Tuple(ty) => {
+            for elem in &ty.elems {
+                type_params(elem, params, used);
+            }
+        }
+        Type::TraitObject(ty) => bound_params(&ty.bounds, params, used),
+        Type::ImplTrait(ty) => bound_params(&ty.bounds, params, used),
+        Type::BareFn(ty) => {
+            for arg in &ty.inputs {
+                type_params(&arg.ty, params, used);
+            }
+            return_params(&ty.output, params, used);
+        }
+        Type::Never(_) | Type::Infer(_) => {}
+        // Can't see inside macros, so assume all of them are used
+        _ => used.extend(params.iter().map(|param| (*param).clone())),
+    }
+}
+
+fn path_params(path: &Path, params: &[&Ident], used: &mut HashSet<Ident>) {
+    match path.segments.last() {
+        // Doesn't hold any value of its type parameters
+        Some(last) if last.ident == "PhantomData" => return,
+        Some(_) => {}
+        None => return,
+    }
+
+    // `T` or an associated type like `T::Item`
+    if path.leading_colon.is_none() {
+        let first = &path.segments[0].ident;
+        if let Some(param) = params.iter().find(|param| **param == first) {
+            used.insert((*param).clone());
+        }
+    }
+
+    for segment in &path.segments {
+        match &segment.arguments {
+            PathArguments::None => {}
+            PathArguments::AngleBracketed(args) => {
+                for arg in &args.args {
+                    match arg {
+                        GenericArgument::Type(ty) => type_params(ty, params, used),
+                        GenericArgument::Binding(binding) => type_params(&binding.ty, params, used),
+                        GenericArgument::Constraint(constraint) => {
+                            bound_params(&constraint.bounds, params, used)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            PathArguments::Parenthesized(args) => {
+                for ty in &args.inputs {
+                    type_params(ty, params, used);
+                }
+                return_params(&args.output, params, used);
+            }
+        }
+    }
+}
+
+fn bound_params<P>(
+    bounds: &Punctuated<TypeParamBound, P>,
+    params: &[&Ident],
+    used: &mut HashSet<Ident>,
+) {
+    for bound in bounds {
+        if let TypeParamBound::Trait(bound) = bound {
+            path_params(&bound.path, params, used);
+        }
+    }
+}
+
+fn return_params(output: &ReturnType, params: &[&Ident], used: &mut HashSet<Ident>) {
+    if let ReturnType::Type(_, ty) = output {
+        type_params(ty, params, used);
+    }
+}
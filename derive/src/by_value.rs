@@ -0,0 +1,86 @@
+use darling::FromDeriveInput;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_quote, Data, DataStruct, DeriveInput, Error, Meta, NestedMeta, Type};
+
+use crate::common::*;
+use crate::DeriveResult;
+
+pub fn derive(input: DeriveInput) -> DeriveResult<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(DataStruct { fields, .. }) => fields,
+        _ => {
+            let msg = "`ByValue` can only be derived for structs";
+            return Err(Error::new(Span::call_site(), msg).to_compile_error());
+        }
+    };
+
+    let derive_opt = ToctocOptions::from_derive_input(&input).map_err(|err| err.write_errors())?;
+    let crate_path = derive_opt.crate_path_or_default();
+
+    if !is_repr_c(&input) {
+        let msg =
+            "`ByValue` requires `#[repr(C)]` to keep the data layout consistent across builds";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for f in fields {
+        match &f.ty {
+            Type::Reference(_) | Type::Ptr(_) => {
+                let msg = "`ByValue` fields can't be references or pointers";
+                return Err(Error::new_spanned(&f.ty, msg).to_compile_error());
+            }
+            ty => where_clause
+                .predicates
+                .push(parse_quote!(#ty: __crate::bytes::ByValue)),
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = &generics.where_clause;
+
+    // Generic structs can only be checked once `PADDING_CHECK` is used with their parameters
+    let ty = fields.iter().map(|f| &f.ty);
+    let check = if input.generics.params.is_empty() {
+        Some(quote! { const _: () = <#ident as __crate::bytes::ByValue>::PADDING_CHECK; })
+    } else {
+        None
+    };
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals, unused_attributes, unused_qualifications)]
+        const _: () = {
+            use #crate_path as __crate;
+
+            unsafe impl #impl_generics __crate::bytes::ByValue for #ident #ty_generics #where_clause {
+                const PADDING_CHECK: () = assert!(
+                    ::core::mem::size_of::<Self>() == 0 #(+ ::core::mem::size_of::<#ty>())*,
+                    "`ByValue` structs can't have padding bytes, add explicit padding fields instead"
+                );
+            }
+
+            #check
+        };
+    })
+}
+
+/// Looks for `C` in any of the `#[repr(...)]` attributes
+fn is_repr_c(input: &DeriveInput) -> bool {
+    input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("repr"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| match nested {
+            NestedMeta::Meta(Meta::Path(path)) => path.is_ident("C"),
+            _ => false,
+        })
+}
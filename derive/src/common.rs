@@ -1,13 +1,78 @@
 //! Common stuff shared across all the derive implementation
 
 use darling::util::Override;
-use darling::{FromDeriveInput, FromField, FromVariant};
-use proc_macro2::Span;
+use darling::{FromDeriveInput, FromField, FromMeta, FromVariant};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{DataEnum, DeriveInput, Error, Fields, Token, WherePredicate};
+
+use crate::bound::Trait;
+use crate::DeriveResult;
 
 fn default_path() -> syn::Path {
     syn::parse_str("Default::default").unwrap()
 }
 
+/// Types that can't implement `Default`, anything else (references included)
+/// is left for the compiler to check
+fn never_default(ty: &syn::Type) -> bool {
+    use syn::Type::*;
+    match ty {
+        Ptr(_) | BareFn(_) | Never(_) | TraitObject(_) | ImplTrait(_) => true,
+        Paren(p) => never_default(&p.elem),
+        Group(g) => never_default(&g.elem),
+        Array(a) => never_default(&a.elem),
+        Tuple(t) => t.elems.iter().any(never_default),
+        _ => false,
+    }
+}
+
+/// The `other` variant must be a newtype of `String`, `Cow<str>` or `&str`,
+/// all of them can be made `From` the variant name
+pub fn check_other_variant(v: &syn::Variant) -> DeriveResult<()> {
+    match &v.fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            if is_other_type(&fields.unnamed[0].ty) {
+                return Ok(());
+            }
+        }
+        _ => {}
+    }
+    let msg = "`other` must be used on a newtype variant of `String`, `Cow<str>` or `&str`";
+    Err(Error::new_spanned(v, msg).to_compile_error())
+}
+
+fn is_other_type(ty: &syn::Type) -> bool {
+    use syn::Type::*;
+    match ty {
+        Reference(r) => is_str(&r.elem),
+        Path(p) => match p.path.segments.last() {
+            Some(s) if s.ident == "String" => s.arguments.is_empty(),
+            Some(s) if s.ident == "Cow" => match &s.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => is_str(ty),
+                    _ => false,
+                }),
+                _ => false,
+            },
+            _ => false,
+        },
+        Paren(p) => is_other_type(&p.elem),
+        Group(g) => is_other_type(&g.elem),
+        _ => false,
+    }
+}
+
+fn is_str(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) => p.qself.is_none() && p.path.is_ident("str"),
+        _ => false,
+    }
+}
+
 pub fn make_ident<D: std::fmt::Display>(postfix: D) -> syn::Ident {
     let field = format!("_{}", postfix);
     syn::Ident::new(&field, proc_macro2::Span::call_site())
@@ -18,24 +83,284 @@ pub fn make_literal_int(i: usize) -> syn::LitInt {
     syn::LitInt::new(&i, proc_macro2::Span::call_site())
 }
 
+/// Case convention used by `#[toctoc(rename_all = "...")]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenameRule {
+    /// `camelCase`
+    CamelCase,
+    /// `snake_case`
+    SnakeCase,
+    /// `PascalCase`
+    PascalCase,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnakeCase,
+    /// `kebab-case`
+    KebabCase,
+}
+
+impl RenameRule {
+    /// Applies the rule to a field name, assumed to be in `snake_case`
+    pub fn apply_to_field(self, field: &str) -> String {
+        use RenameRule::*;
+        match self {
+            SnakeCase => field.to_owned(),
+            CamelCase => {
+                let pascal = PascalCase.apply_to_field(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => pascal,
+                }
+            }
+            PascalCase => {
+                let mut pascal = String::with_capacity(field.len());
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.extend(c.to_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            ScreamingSnakeCase => field.to_uppercase(),
+            KebabCase => field.replace('_', "-"),
+        }
+    }
+
+    /// Applies the rule to a variant name, assumed to be in `PascalCase`
+    pub fn apply_to_variant(self, variant: &str) -> String {
+        use RenameRule::*;
+        match self {
+            PascalCase => variant.to_owned(),
+            CamelCase => {
+                let mut chars = variant.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+            SnakeCase | ScreamingSnakeCase | KebabCase => {
+                let mut snake = String::with_capacity(variant.len() + 4);
+                for (i, c) in variant.char_indices() {
+                    if i > 0 && c.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.extend(c.to_lowercase());
+                }
+                self.apply_to_field(&snake)
+            }
+        }
+    }
+}
+
+impl FromMeta for RenameRule {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        use RenameRule::*;
+        match value {
+            "camelCase" => Ok(CamelCase),
+            "snake_case" => Ok(SnakeCase),
+            "PascalCase" => Ok(PascalCase),
+            "SCREAMING_SNAKE_CASE" => Ok(ScreamingSnakeCase),
+            "kebab-case" => Ok(KebabCase),
+            _ => Err(darling::Error::unknown_value(value)),
+        }
+    }
+}
+
+/// Variant used by the `#[toctoc(base64 = "...")]` fields
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Base64 {
+    /// `standard`, the default
+    Standard,
+    /// `standard_no_pad`
+    StandardNoPad,
+    /// `url_safe`
+    UrlSafe,
+    /// `url_safe_no_pad`
+    UrlSafeNoPad,
+}
+
+impl Base64 {
+    /// Name of the matching `base64::Config`
+    fn config(self) -> TokenStream {
+        use Base64::*;
+        match self {
+            Standard => quote! { STANDARD },
+            StandardNoPad => quote! { STANDARD_NO_PAD },
+            UrlSafe => quote! { URL_SAFE },
+            UrlSafeNoPad => quote! { URL_SAFE_NO_PAD },
+        }
+    }
+}
+
+impl FromMeta for Base64 {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        use Base64::*;
+        match value {
+            "standard" => Ok(Standard),
+            "standard_no_pad" => Ok(StandardNoPad),
+            "url_safe" => Ok(UrlSafe),
+            "url_safe_no_pad" => Ok(UrlSafeNoPad),
+            _ => Err(darling::Error::unknown_value(value)),
+        }
+    }
+}
+
+/// The only (de)serialized field of a `#[toctoc(transparent)]` struct
+pub struct TransparentField<'a> {
+    pub member: syn::Member,
+    pub field: &'a syn::Field,
+    /// Skipped fields along with their default values
+    pub skipped: Vec<(syn::Member, TokenStream)>,
+}
+
+impl<'a> TransparentField<'a> {
+    pub fn from_fields(input: &DeriveInput, fields: &'a Fields) -> DeriveResult<Self> {
+        let mut found = None;
+        let mut skipped = vec![];
+
+        for (i, f) in fields.iter().enumerate() {
+            let member = match &f.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(syn::Index::from(i)),
+            };
+
+            let opt = ToctocFieldOptions::from_field(f).map_err(|err| err.write_errors())?;
+            if opt.skip {
+                skipped.push((member, opt.skipped_default(&f.ty)?));
+            } else if found.is_none() {
+                found = Some((member, f));
+            } else {
+                let msg = "`transparent` requires exactly one non skipped field";
+                return Err(Error::new_spanned(f, msg).to_compile_error());
+            }
+        }
+
+        match found {
+            Some((member, field)) => Ok(Self {
+                member,
+                field,
+                skipped,
+            }),
+            None => {
+                let msg = "`transparent` requires exactly one non skipped field";
+                Err(Error::new_spanned(&input.ident, msg).to_compile_error())
+            }
+        }
+    }
+}
+
 #[derive(Debug, FromDeriveInput)]
 #[darling(attributes(toctoc))]
 pub struct ToctocOptions {
     pub ident: syn::Ident,
-    // /// Use the default to fill out any missing field from this `struct`.
-    // ///
-    // /// It's also possible to specify a function to create the default value like so:
-    // /// `#[toctoc(default = "path::to::default_function")`
-    // ///
-    // /// **Note** Beware `enums` aren't suported because they can just swap variants while loading
-    // /// thus invalidating all the defaults
-    // #[darling(default)]
-    // pub default: Option<Override<syn::Path>>,
+    /// Use the default to fill out any missing field from this `struct`.
+    ///
+    /// It's also possible to specify a function to create the default value like so:
+    /// `#[toctoc(default = "path::to::default_function")`
+    ///
+    /// **Note** Beware `enums` aren't suported because they can just swap variants while loading
+    /// thus invalidating all the defaults
+    #[darling(default)]
+    pub default: Option<Override<syn::Path>>,
     /// Specify a path to the toctoc crate instance to use when referring to toctoc APIs
     /// from generated code. This is normally only applicable when invoking re-exported
     /// toctoc derives from a public macro in a different crate.
     #[darling(default, rename = "crate")]
     pub crate_path: Option<syn::Path>,
+    /// Rename all the fields (for structs) or variants (for enums) according to
+    /// the given case convention, like `#[toctoc(rename_all = "camelCase")]`
+    #[darling(default)]
+    pub rename_all: Option<RenameRule>,
+    /// Same as `rename_all` but only for the variant names of enums, takes precedence
+    /// over `rename_all`
+    #[darling(default)]
+    pub rename_all_variants: Option<RenameRule>,
+    /// Rename the fields of structs and of every struct variant of enums, a per
+    /// variant `rename_all` still wins
+    #[darling(default)]
+    pub rename_all_fields: Option<RenameRule>,
+    /// Fail to deserialize when a field isn't declared by the struct
+    /// (or any of the struct variants for enums)
+    #[darling(default)]
+    pub deny_unknown_fields: bool,
+    /// Internally tagged enum, the variant name is written in the `tag` field
+    /// of the same map that holds the variant fields, like `{"type":"A","a":1}`
+    #[darling(default)]
+    pub tag: Option<String>,
+    /// Adjacently tagged enum when used along with `tag`, the variant fields are
+    /// written in the `content` field next to the tag, like `{"t":"A","c":{"a":1}}`
+    #[darling(default)]
+    pub content: Option<String>,
+    /// (De)serialize a struct with a single field as if it was just that field
+    #[darling(default)]
+    pub transparent: bool,
+    /// Enum written without any variant name, when deserializing each variant
+    /// is tried in order and the first one that succeeds is picked
+    #[darling(default)]
+    pub untagged: bool,
+    /// Enum of unit variants written as its integer discriminant, the
+    /// enum must also have a `#[repr(u8)]` (or any other integer type)
+    #[darling(default)]
+    pub repr: bool,
+    /// Where clause predicates appended to the inferred ones, for when a type
+    /// parameter needs more than the `T: Serialize` or `T: Deserialize<'de>` bound,
+    /// like `#[toctoc(bound = "T: MyTrait, U: AnotherTrait")]`
+    #[darling(default)]
+    pub bound: Option<String>,
+    /// Same as `bound` but only for `Serialize`, takes precedence over `bound`
+    #[darling(default)]
+    pub ser_bound: Option<String>,
+    /// Same as `bound` but only for `Deserialize`, takes precedence over `bound`,
+    /// the deserializer lifetime can be used like `T::Item: Deserialize<'de>`
+    #[darling(default)]
+    pub de_bound: Option<String>,
+}
+
+/// Integer type of a `#[toctoc(repr)]` enum, taken from its `#[repr(...)]` attribute
+pub fn enum_repr(
+    input: &DeriveInput,
+    enumeration: &DataEnum,
+    opt: &ToctocOptions,
+) -> DeriveResult<syn::Ident> {
+    if opt.untagged || opt.tag.is_some() {
+        let msg = "`repr` can't be used along with `tag` or `untagged`";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    for v in &enumeration.variants {
+        if !v.fields.is_empty() {
+            let msg = "`repr` enums only support unit variants";
+            return Err(Error::new_spanned(v, msg).to_compile_error());
+        }
+    }
+
+    const INTEGERS: &[&str] = &[
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ];
+
+    for attr in input.attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+            for nested in &list.nested {
+                if let syn::NestedMeta::Meta(syn::Meta::Path(path)) = nested {
+                    match path.get_ident() {
+                        Some(ident) if INTEGERS.contains(&&ident.to_string()[..]) => {
+                            return Ok(ident.clone())
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    let msg = "`repr` requires the enum to have an integer `#[repr(...)]`, like `#[repr(u8)]`";
+    Err(Error::new_spanned(&input.ident, msg).to_compile_error())
 }
 
 impl ToctocOptions {
@@ -45,15 +370,51 @@ impl ToctocOptions {
             .map_or_else(|| syn::parse_str("toctoc").unwrap(), |p| p.clone())
     }
 
-    // /// Returns the default behavior
-    // pub fn default_behaviour(&self) -> Option<syn::Path> {
-    //     use Override::*;
-    //     match &self.default {
-    //         Some(Explicit(path)) => Some(path.clone()),
-    //         Some(Inherit) => Some(default_path()),
-    //         None => None,
-    //     }
-    // }
+    /// Rule for the fields of a struct
+    pub fn field_rule(&self) -> Option<RenameRule> {
+        self.rename_all_fields.or(self.rename_all)
+    }
+
+    /// Rule for the variant names of an enum
+    pub fn variant_rule(&self) -> Option<RenameRule> {
+        self.rename_all_variants.or(self.rename_all)
+    }
+
+    /// Rule for the fields of a struct `variant`
+    pub fn variant_field_rule(&self, variant: &ToctocVariantOptions) -> Option<RenameRule> {
+        variant.rename_all.or(self.rename_all_fields)
+    }
+
+    /// Returns the default behavior
+    pub fn default_behavior(&self) -> Option<syn::Path> {
+        use Override::*;
+        match &self.default {
+            Some(Explicit(path)) => Some(path.clone()),
+            Some(Inherit) => Some(default_path()),
+            None => None,
+        }
+    }
+
+    /// Predicates given by the `bound` attribute that applies to `tr`
+    pub fn custom_bound(&self, tr: Trait) -> DeriveResult<Vec<WherePredicate>> {
+        let (name, bound) = match tr {
+            Trait::Serialize if self.ser_bound.is_some() => ("ser_bound", &self.ser_bound),
+            Trait::Deserialize if self.de_bound.is_some() => ("de_bound", &self.de_bound),
+            _ => ("bound", &self.bound),
+        };
+        let bound = match bound {
+            Some(bound) => bound,
+            None => return Ok(vec![]),
+        };
+
+        match Punctuated::<WherePredicate, Token![,]>::parse_terminated.parse_str(bound) {
+            Ok(predicates) => Ok(predicates.into_iter().collect()),
+            Err(err) => {
+                let msg = format!("invalid `{}`: {}", name, err);
+                Err(Error::new_spanned(&self.ident, msg).to_compile_error())
+            }
+        }
+    }
 }
 
 #[derive(Default, FromField)]
@@ -62,6 +423,9 @@ pub struct ToctocFieldOptions {
     pub ident: Option<syn::Ident>,
     /// Rename field ident
     pub rename: Option<syn::Ident>,
+    /// Alternative names accepted during deserialization, can be repeated
+    #[darling(multiple)]
+    pub alias: Vec<String>,
     /// Skips field (de)serialization. The field must implement `Default::default()`
     /// or specify any default function with `#[toctoc(default = "path::to::default_function")`
     pub skip: bool,
@@ -69,32 +433,45 @@ pub struct ToctocFieldOptions {
     pub skip_deserializing: bool,
     /// Skip serialization
     pub skip_serializing: bool,
+    /// Skip serialization when the predicate returns `true`, it receives a reference
+    /// to the field, like `#[toctoc(skip_serializing_if = "Option::is_none")]`
+    pub skip_serializing_if: Option<syn::Path>,
+    /// Inline the fields of this field into the parent map, only for struct fields
+    pub flatten: bool,
     /// Use the default implementation when this field is missing.
     ///
     /// It's also possible to specify a function to create the default value like so:
     /// `#[toctoc(default = "path::to::default_function")`
     pub default: Option<Override<syn::Path>>,
+    /// Serialize the field with a custom function instead of its `Serialize` impl,
+    /// the same signature of `Serialize::begin`:
+    /// `fn ser<T>(value: &T, v: ser::Visitor, c: &mut dyn ser::Context) -> ser::Done`
+    pub serialize_with: Option<syn::Path>,
+    /// Deserialize the field with a custom function instead of its `Deserialize` impl,
+    /// the same signature of `Deserialize::begin`:
+    /// `fn de<'de, T>(out: &mut Option<T>) -> &mut dyn de::Visitor<'de>`
+    pub deserialize_with: Option<syn::Path>,
+    /// Write a `Bytes` field as a base64 string (requires the `base64` feature), the
+    /// standard alphabet with padding is used unless another variant is given, like
+    /// `#[toctoc(base64 = "url_safe_no_pad")]`; any of them is accepted when deserializing.
+    /// Binary formats keep their native bytes
+    pub base64: Option<Override<Base64>>,
     // TODO: `bytes` allow (de)serialization using aligned bytes
-    // TODO: allow custom save functions, but their signatures must be reviewed first
-    // /// Especial `load` function to be used on this field.
-    // ///
-    // /// Uses this signature:
-    // /// `fn load<L: Loader + ?Sized>(field: &mut FIELD_TYPE, loader: L) -> Result<(), L::Err>`
-    // pub de_with: Option<syn::Path>,
-    // /// Especial `save` function to be used on this field.
-    // ///
-    // /// Uses this signature:
-    // /// `fn save<S: Saver + ?Sized>(field: &FIELD_TYPE, saver: S) -> Result<S::Ok, S::Err>`
-    // pub ser_with: Option<syn::Path>,
 }
 
 impl ToctocFieldOptions {
-    /// Field name
-    pub fn name(&self) -> Option<&syn::Ident> {
-        if self.rename.is_some() {
-            self.rename.as_ref()
+    /// Field name, a per field `rename` always wins over the container `rename_all`
+    ///
+    /// **NOTE** Only valid for named fields
+    pub fn name(&self, rename_all: Option<RenameRule>) -> String {
+        if let Some(name) = self.rename.as_ref() {
+            name.to_string()
         } else {
-            self.ident.as_ref()
+            let ident = self.ident.as_ref().unwrap().to_string();
+            match rename_all {
+                Some(rule) => rule.apply_to_field(&ident),
+                None => ident,
+            }
         }
     }
 
@@ -108,13 +485,72 @@ impl ToctocFieldOptions {
         }
     }
 
-    /// Returns the default behavior forced on, used when the field is skipped
-    pub fn default_behavior_forced(&self) -> syn::Path {
+    /// Default value of a skipped field of type `ty`, without a default function
+    /// the type must implement `Default`. Types that never do are reported here,
+    /// for any other the compiler error will point to the field type
+    pub fn skipped_default(&self, ty: &syn::Type) -> DeriveResult<TokenStream> {
+        if let Some(Override::Explicit(path)) = &self.default {
+            return Ok(quote! { #path() });
+        }
+
+        if never_default(ty) {
+            let field = match &self.ident {
+                Some(ident) => format!("field `{}`", ident),
+                None => "field".to_owned(),
+            };
+            let msg = format!(
+                "{} is skipped but its type does not implement `Default`; add #[toctoc(default = \"...\")]",
+                field
+            );
+            return Err(Error::new_spanned(ty, msg).to_compile_error());
+        }
+
+        Ok(quote_spanned! { ty.span() => <#ty as __crate::export::Default>::default() })
+    }
+
+    /// Config of a `base64` field
+    fn base64_config(&self) -> DeriveResult<Option<TokenStream>> {
         use Override::*;
-        match &self.default {
-            Some(Explicit(path)) => path.clone(),
-            Some(Inherit) | None => default_path(),
+        let base64 = match &self.base64 {
+            Some(Explicit(base64)) => *base64,
+            Some(Inherit) => Base64::Standard,
+            None => return Ok(None),
+        };
+
+        if self.flatten || self.serialize_with.is_some() || self.deserialize_with.is_some() {
+            let msg = "`base64` can't be used along with `flatten`, `serialize_with` or `deserialize_with`";
+            return Err(Error::new_spanned(&self.ident, msg).to_compile_error());
         }
+        Ok(Some(base64.config()))
+    }
+
+    /// Expression of the value to serialize, `value` is a reference to the field
+    pub fn serialize_value(&self, value: TokenStream) -> DeriveResult<TokenStream> {
+        Ok(match (self.base64_config()?, &self.serialize_with) {
+            (Some(config), _) => {
+                quote! { __crate::__base64_field!(serialize, #value, #config) }
+            }
+            (None, Some(with)) => {
+                quote! { &__crate::ser::SerializeWith { value: #value, with: #with } }
+            }
+            (None, None) => value,
+        })
+    }
+
+    /// Initial value of the field place and the function that begins its visitor,
+    /// fields deserialized with a custom function may not implement `Deserialize`
+    pub fn deserialize_begin(&self) -> DeriveResult<(TokenStream, TokenStream)> {
+        Ok(match (self.base64_config()?, &self.deserialize_with) {
+            (Some(_), _) => (
+                quote! { __crate::export::None },
+                quote! { (__crate::__base64_field!(deserialize)) },
+            ),
+            (None, Some(with)) => (quote! { __crate::export::None }, quote! { #with }),
+            (None, None) => (
+                quote! { __crate::Deserialize::default() },
+                quote! { __crate::Deserialize::begin },
+            ),
+        })
     }
 }
 
@@ -131,6 +567,13 @@ pub struct ToctocVariantOptions {
     pub no_de: bool,
     /// Skip serialization
     pub no_ser: bool,
+    /// Rename all the fields of a struct variant
+    pub rename_all: Option<RenameRule>,
+    /// Fail to deserialize when a field isn't declared by the struct variant
+    pub deny_unknown_fields: bool,
+    /// Catch all the unknown variant names, the variant must be a newtype of
+    /// `String`, `Cow<str>` or `&str`
+    pub other: bool,
 }
 
 impl Default for ToctocVariantOptions {
@@ -141,16 +584,24 @@ impl Default for ToctocVariantOptions {
             skip: false,
             no_de: false,
             no_ser: false,
+            rename_all: None,
+            deny_unknown_fields: false,
+            other: false,
         }
     }
 }
 
 impl ToctocVariantOptions {
-    pub fn name(&self) -> &syn::Ident {
+    /// Variant name, a per variant `rename` always wins over the container `rename_all`
+    pub fn name(&self, rename_all: Option<RenameRule>) -> String {
         if let Some(name) = self.rename.as_ref() {
-            name
+            name.to_string()
         } else {
-            &self.ident
+            let ident = self.ident.to_string();
+            match rename_all {
+                Some(rule) => rule.apply_to_variant(&ident),
+                None => ident,
+            }
         }
     }
 }
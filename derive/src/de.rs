@@ -1,6 +1,6 @@
 use darling::{FromDeriveInput, FromField, FromVariant};
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{parse_quote, Data, DataEnum, DataStruct, DeriveInput, Error, Fields};
 
 use crate::bound;
@@ -15,60 +15,293 @@ pub fn derive(input: DeriveInput) -> DeriveResult<TokenStream> {
     }
 }
 
+/// Fallback arm used when matching the map keys
+fn unknown_field_arm(deny_unknown_fields: bool) -> TokenStream {
+    if deny_unknown_fields {
+        quote! { __k => __crate::export::Err(__crate::Error::unknown_field(__k))?, }
+    } else {
+        quote! { _ => __m.visit(__crate::de::Visitor::ignore(), __c)?, }
+    }
+}
+
+/// Replays the buffered `content` field of an adjacently tagged enum into the visitor
+/// returned by `begin`
+fn visit_content(content: &str, begin: TokenStream) -> TokenStream {
+    quote! {
+        match &__content {
+            Some(__content) => __content.visit(#begin, __c)?,
+            None => __crate::export::Err(__crate::Error::missing_field(#content))?,
+        }
+    }
+}
+
+/// Visitor methods of a `#[toctoc(transparent)]` struct, all of them are forwarded
+/// to the inner field visitor
+fn derive_transparent(input: &DeriveInput, fields: &Fields) -> DeriveResult<TokenStream> {
+    let ident = &input.ident;
+    let TransparentField {
+        member,
+        field,
+        skipped,
+    } = TransparentField::from_fields(input, fields)?;
+    let ty = &field.ty;
+    let (skipped, skipped_default): (Vec<_>, Vec<_>) = skipped.into_iter().unzip();
+
+    // Visit the inner field then wrap it
+    let wrap = quote! {
+        self.__out = __inner.map(|__inner| #ident {
+            #member: __inner,
+            #(#skipped: #skipped_default,)*
+        });
+        __crate::export::Ok(())
+    };
+
+    Ok(forward_visitor(ty, |_| wrap.clone()))
+}
+
+/// Implements every visitor method by forwarding it to the visitor of `ty`,
+/// then `wrap` is called with the context expression to build the value
+/// from the `__inner: Option<#ty>` result
+fn forward_visitor<T: ToTokens>(ty: &T, wrap: impl Fn(TokenStream) -> TokenStream) -> TokenStream {
+    let inner = quote! {
+        let mut __inner: __crate::export::Option<#ty> = __crate::export::None;
+        let __v = <#ty as __crate::Deserialize>::begin(&mut __inner);
+    };
+    let wrap_c = wrap(quote!(__c));
+    let wrap = wrap(quote!(&mut ()));
+
+    quote! {
+        fn null(&mut self, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.null(__c)?; #wrap_c
+        }
+
+        fn boolean(&mut self, __b: bool) -> __crate::Result<()> {
+            #inner __v.boolean(__b)?; #wrap
+        }
+
+        fn string(&mut self, __s: &'de __crate::export::str, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.string(__s, __c)?; #wrap_c
+        }
+
+        fn negative(&mut self, __n: i64, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.negative(__n, __c)?; #wrap_c
+        }
+
+        fn nonnegative(&mut self, __n: u64, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.nonnegative(__n, __c)?; #wrap_c
+        }
+
+        fn negative_i32(&mut self, __n: i32, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.negative_i32(__n, __c)?; #wrap_c
+        }
+
+        fn nonnegative_u32(&mut self, __n: u32, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.nonnegative_u32(__n, __c)?; #wrap_c
+        }
+
+        fn short_int(&mut self, __n: i16, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.short_int(__n, __c)?; #wrap_c
+        }
+
+        fn int128(&mut self, __n: i128, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.int128(__n, __c)?; #wrap_c
+        }
+
+        fn uint128(&mut self, __n: u128, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.uint128(__n, __c)?; #wrap_c
+        }
+
+        fn double(&mut self, __n: f64) -> __crate::Result<()> {
+            #inner __v.double(__n)?; #wrap
+        }
+
+        fn single(&mut self, __n: f32) -> __crate::Result<()> {
+            #inner __v.single(__n)?; #wrap
+        }
+
+        fn seq(&mut self, __s: &mut dyn __crate::de::Seq<'de>, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.seq(__s, __c)?; #wrap_c
+        }
+
+        fn map(&mut self, __m: &mut dyn __crate::de::Map<'de>, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.map(__m, __c)?; #wrap_c
+        }
+
+        fn bytes(&mut self, __b: &'de [u8], __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+            #inner __v.bytes(__b, __c)?; #wrap_c
+        }
+    }
+}
+
 fn derive_struct(input: &DeriveInput, fields: &Fields) -> DeriveResult<TokenStream> {
+    let derive_opt = ToctocOptions::from_derive_input(input).map_err(|err| err.write_errors())?;
+    let crate_path = derive_opt.crate_path_or_default();
+
+    if derive_opt.tag.is_some() {
+        let msg = "`tag` is only supported on enums";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.untagged {
+        let msg = "`untagged` is only supported on enums";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.content.is_some() {
+        let msg = "`content` is only supported on enums";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
     let ident = &input.ident;
+    // Helper structs only get the bounds declared by the type, the ones with `'de` go on the impls
+    let (impl_generics, ty_generics, type_where_clause) = input.generics.split_for_impl();
+
+    // Missing fields are taken from this instance
+    let base = derive_opt.default_behavior();
+    if base.is_some() && (derive_opt.transparent || !matches!(fields, Fields::Named(_))) {
+        let msg = "`default` is only supported on structs with named fields";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
 
     let body = match fields {
+        _ if derive_opt.transparent => derive_transparent(input, fields)?,
         Fields::Named(fields) => {
             let mut field = vec![];
             let mut field_ty = vec![];
             let mut field_name = vec![];
+            let mut unwrapped = vec![];
             let mut field_unwrap = vec![];
+            let mut base_field = vec![];
+            let mut base_skipped = false;
+            let mut field_init = vec![];
+            let mut field_begin = vec![];
             let mut skipped = vec![];
             let mut skipped_default = vec![];
+            let mut flat = vec![];
+            let mut flat_ty = vec![];
+            let mut flat_begin = vec![];
+            let mut flat_name = vec![];
 
             for f in &fields.named {
                 let opt = ToctocFieldOptions::from_field(f).map_err(|err| err.write_errors())?;
 
                 if opt.skip || opt.skip_deserializing {
-                    let ident = &opt.ident;
-                    skipped.push(ident.clone());
-                    skipped_default.push(opt.default_behavior_forced());
+                    // Kept as they are in the container default
+                    if base.is_some() && opt.default.is_none() {
+                        base_skipped = true;
+                        continue;
+                    }
+                    skipped.push(opt.ident.clone());
+                    skipped_default.push(opt.skipped_default(&f.ty)?);
+                    continue;
+                }
+
+                if opt.flatten {
+                    if derive_opt.deny_unknown_fields {
+                        let msg = "`flatten` can't be used along with `deny_unknown_fields`";
+                        return Err(Error::new_spanned(f, msg).to_compile_error());
+                    }
+
+                    flat_begin.push(opt.deserialize_begin()?.1);
+                    let ident = opt.ident.unwrap();
+                    flat_name.push(ident.to_string());
+                    flat.push(ident);
+                    flat_ty.push(f.ty.clone());
                     continue;
                 }
 
-                let ident = opt.name().unwrap();
-                let name = ident.to_string();
+                let name = opt.name(derive_opt.field_rule());
 
                 // Create the default function if any otherwise result in error
-                match opt.default_behavior() {
-                    Some(default) => field_unwrap.push(quote! { unwrap_or_else(#default)? }),
-                    None => {
+                let ident = opt.ident.as_ref().unwrap();
+                match (opt.default_behavior(), &base) {
+                    (Some(default), _) => field_unwrap.push(quote! { unwrap_or_else(#default) }),
+                    (None, Some(_)) => base_field.push(ident.clone()),
+                    (None, None) => {
                         field_unwrap.push(quote! { ok_or(__crate::Error::missing_field(#name))? })
                     }
                 }
+                if !base_field.contains(ident) {
+                    unwrapped.push(ident.clone());
+                }
+
+                let (init, begin) = opt.deserialize_begin()?;
+                field_init.push(init);
+                field_begin.push(begin);
 
-                field.push(ident.clone());
+                let alias = &opt.alias;
+                field_name.push(quote! { #name #(| #alias)* });
+                field.push(opt.ident.unwrap());
                 field_ty.push(f.ty.clone());
-                field_name.push(name);
             }
 
+            // Unknown keys are buffered to be later visited by every flattened field
+            let (flatten, unknown) = if flat.is_empty() {
+                (None, unknown_field_arm(derive_opt.deny_unknown_fields))
+            } else {
+                (
+                    Some(quote! { let mut __flatten = __crate::de::Flatten::new(); }),
+                    quote! { __k => __flatten.insert(__k, __m, __c)?, },
+                )
+            };
+
+            let literal = quote! {
+                #ident {
+                    #(#base_field,)*
+                    #(#unwrapped,)*
+                    #(#flat,)*
+                    #(#skipped: #skipped_default,)* // Fill out skipped fields with their default values
+                }
+            };
+            // The container default is only built when some field is missing, the
+            // fields found are assigned to it instead of moved out of it as it
+            // may implement `Drop`
+            let out = match base {
+                Some(base) => {
+                    let from_base = quote! {{
+                        let mut __base: #ident #ty_generics = #base();
+                        #(if let __crate::export::Some(__v) = #base_field { __base.#base_field = __v; })*
+                        #(__base.#unwrapped = #unwrapped;)*
+                        #(__base.#flat = #flat;)*
+                        #(__base.#skipped = #skipped_default;)*
+                        __base
+                    }};
+                    if base_skipped {
+                        from_base
+                    } else if base_field.is_empty() {
+                        literal
+                    } else {
+                        quote! {
+                            match (#(#base_field,)*) {
+                                (#(__crate::export::Some(#base_field),)*) => #literal,
+                                (#(#base_field,)*) => #from_base,
+                            }
+                        }
+                    }
+                }
+                None => literal,
+            };
+
             quote! {
                 fn map(&mut self, __m: &mut dyn __crate::de::Map<'de>, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
-                    #(let mut #field: __crate::export::Option<#field_ty> = __crate::Deserialize::default();)*
+                    #(let mut #field: __crate::export::Option<#field_ty> = #field_init;)*
+                    #flatten
                     while let Some(__k) = __m.next()? {
                         match __k {
-                            #(#field_name => __m.visit(__crate::Deserialize::begin(&mut #field), __c)?,)*
-                            _ => __m.visit(__crate::de::Visitor::ignore(), __c)?,
+                            #(#field_name => __m.visit(#field_begin(&mut #field), __c)?,)*
+                            #unknown
                         }
                     }
                     // Unwrap all
-                    #(let #field = #field.take() . #field_unwrap;)*
+                    #(let #unwrapped = #unwrapped.take() . #field_unwrap;)*
+                    // Flattened fields
+                    #(
+                        let mut #flat: __crate::export::Option<#flat_ty> = __crate::export::None;
+                        __flatten.visit(#flat_begin(&mut #flat), __c)?;
+                        let #flat = #flat.ok_or(__crate::Error::missing_field(#flat_name))?;
+                    )*
                     // Build struct
-                    self.__out = __crate::export::Some(#ident {
-                        #(#field,)*
-                        #(#skipped: #skipped_default(),)* // Fill out skipped fields with their default values
-                    });
+                    self.__out = __crate::export::Some(#out);
                     Ok(())
                 }
             }
@@ -90,7 +323,7 @@ fn derive_struct(input: &DeriveInput, fields: &Fields) -> DeriveResult<TokenStre
                             value.ok_or(Error::missing_element(#field))?
                         },)*
                     ));
-                    while __s.visit(Visitor::ignore(), __c)? {}
+                    __s.ignore_remaining(__c)?;
                     Ok(())
                 }
             }
@@ -103,28 +336,24 @@ fn derive_struct(input: &DeriveInput, fields: &Fields) -> DeriveResult<TokenStre
         },
     };
 
-    let derive_opt = ToctocOptions::from_derive_input(input).map_err(|err| err.write_errors())?;
-    let crate_path = derive_opt.crate_path_or_default();
+    let bound = parse_quote!(__crate::Deserialize<'de>);
+    let types = bound::field_types(&input.data, bound::Trait::Deserialize)?;
+    let mut where_clause = bound::where_clause_with_bound(&input.generics, &types, bound);
+    where_clause
+        .predicates
+        .extend(derive_opt.custom_bound(bound::Trait::Deserialize)?);
 
-    // TODO: Custom bounds
-
-    let bound = parse_quote!(__crate::Deserialize);
-    let where_clause = bound::where_clause_with_bound(&input.generics, bound);
-
-    let ident = &input.ident;
     let input_generics = bound::within_lifetime_bound(&input.generics, "'de"); // Add deserialzier lifetime
     let (impl_de_generics, _, _) = input_generics.split_for_impl();
 
-    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
-
     Ok(quote! {
         #[doc(hidden)]
-        #[allow(non_upper_case_globals, unused_attributes, unused_qualifications)]
+        #[allow(non_upper_case_globals, unused_attributes, unused_qualifications, unused_mut)]
         const _: () = {
             use #crate_path as __crate;
 
             #[repr(C)]
-            struct __Visitor #impl_generics #where_clause {
+            struct __Visitor #impl_generics #type_where_clause {
                 __out: __crate::export::Option<#ident #ty_generics>,
             }
 
@@ -151,20 +380,44 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
     let derive_opt = ToctocOptions::from_derive_input(input).map_err(|err| err.write_errors())?;
     let crate_path = derive_opt.crate_path_or_default();
 
-    // TODO: Custom bounds
+    if derive_opt.untagged && derive_opt.tag.is_some() {
+        let msg = "`untagged` can't be used along with `tag`";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.content.is_some() && derive_opt.tag.is_none() {
+        let msg = "`content` must be used along with `tag`";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.default.is_some() {
+        let msg = "`default` is only supported on structs";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.repr {
+        return derive_repr(input, enumeration, &derive_opt);
+    }
 
-    let bound = parse_quote!(__crate::Deserialize);
-    let where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let bound = parse_quote!(__crate::Deserialize<'de>);
+    let types = bound::field_types(&input.data, bound::Trait::Deserialize)?;
+    let mut where_clause = bound::where_clause_with_bound(&input.generics, &types, bound);
+    where_clause
+        .predicates
+        .extend(derive_opt.custom_bound(bound::Trait::Deserialize)?);
 
     let ident = &input.ident;
     let input_generics = bound::within_lifetime_bound(&input.generics, "'de"); // Add deserialzier lifetime
     let (impl_de_generics, _, _) = input_generics.split_for_impl();
 
-    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    // Helper structs only get the bounds declared by the type, the ones with `'de` go on the impls
+    let (impl_generics, ty_generics, type_where_clause) = input.generics.split_for_impl();
 
     let mut arm = vec![];
+    let mut attempt = vec![];
     let mut unit_variant = vec![];
     let mut unit_variant_name = vec![];
+    let mut other = None;
 
     for v in &enumeration.variants {
         let opt = ToctocVariantOptions::from_variant(v).map_err(|err| err.write_errors())?;
@@ -174,7 +427,11 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
         }
 
         let variant = &opt.ident;
-        let name = opt.name().to_string();
+        let name = opt.name(derive_opt.variant_rule());
+
+        if opt.other {
+            check_other_variant(v)?;
+        }
 
         match &v.fields {
             Fields::Named(fields) => {
@@ -182,39 +439,53 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
                 let mut field_ty = vec![];
                 let mut field_name = vec![];
                 let mut field_unwrap = vec![];
+                let mut field_init = vec![];
+                let mut field_begin = vec![];
                 let mut skipped = vec![];
                 let mut skipped_default = vec![];
 
                 for f in &fields.named {
-                    let opt =
+                    let field_opt =
                         ToctocFieldOptions::from_field(f).map_err(|err| err.write_errors())?;
 
-                    if opt.skip || opt.skip_deserializing {
+                    if field_opt.flatten {
+                        let msg = "`flatten` is only supported on struct fields";
+                        return Err(Error::new_spanned(f, msg).to_compile_error());
+                    }
+
+                    if field_opt.skip || field_opt.skip_deserializing {
                         // Field default value
-                        let ident = &opt.ident;
+                        let ident = &field_opt.ident;
                         skipped.push(ident.clone());
-                        skipped_default.push(opt.default_behavior_forced());
+                        skipped_default.push(field_opt.skipped_default(&f.ty)?);
                         continue;
                     }
 
-                    let ident = &opt.ident;
-                    let name = opt.name().unwrap().to_string();
+                    let ident = &field_opt.ident;
+                    let name = field_opt.name(derive_opt.variant_field_rule(&opt));
 
                     // Create the default function if any otherwise result in error
-                    match opt.default_behavior() {
-                        Some(default) => field_unwrap.push(quote! { unwrap_or_else(#default)? }),
+                    match field_opt.default_behavior() {
+                        Some(default) => field_unwrap.push(quote! { unwrap_or_else(#default) }),
                         None => field_unwrap
                             .push(quote! { ok_or(__crate::Error::missing_field(#name))? }),
                     }
 
+                    let (init, begin) = field_opt.deserialize_begin()?;
+                    field_init.push(init);
+                    field_begin.push(begin);
+
+                    let alias = &field_opt.alias;
+                    field_name.push(quote! { #name #(| #alias)* });
                     field.push(ident.clone());
                     field_ty.push(f.ty.clone());
-                    field_name.push(name);
                 }
 
-                arm.push(quote! {
-                    Some(#name) => {
-                        struct __Inner #impl_generics #where_clause {
+                let unknown =
+                    unknown_field_arm(derive_opt.deny_unknown_fields || opt.deny_unknown_fields);
+
+                let inner = quote! {
+                        struct __Inner #impl_generics #type_where_clause {
                             #( #field: #field_ty, )*
                         }
 
@@ -231,17 +502,17 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
                         }
 
                         #[repr(C)]
-                        struct __InnerVisitor #impl_generics #where_clause {
+                        struct __InnerVisitor #impl_generics #type_where_clause {
                             __out: __crate::export::Option<__Inner #ty_generics>,
                         }
 
                         impl #impl_de_generics __crate::de::Visitor<'de> for __InnerVisitor #ty_generics #where_clause {
                             fn map(&mut self, __m: &mut dyn __crate::de::Map<'de>, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
-                                #(let mut #field: __crate::export::Option<#field_ty> = __crate::Deserialize::default();)*
+                                #(let mut #field: __crate::export::Option<#field_ty> = #field_init;)*
                                 while let Some(__k) = __m.next()? {
                                     match __k {
-                                        #(#field_name => __m.visit(__crate::Deserialize::begin(&mut #field), __c)?,)*
-                                        _ => __m.visit(__crate::de::Visitor::ignore(), __c)?,
+                                        #(#field_name => __m.visit(#field_begin(&mut #field), __c)?,)*
+                                        #unknown
                                     }
                                 }
                                 // Unwrap all
@@ -252,16 +523,71 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
                             }
                         }
 
+                };
+
+                let build = quote! {
+                    self.__out = __crate::export::Some(#ident::#variant {
+                        #(#field: __value.#field,)*
+                        #(#skipped: #skipped_default,)* // Fill out skipped fields with their default values
+                    })
+                };
+
+                if derive_opt.untagged {
+                    attempt.push(quote! {
+                        {
+                            #inner
+                            let mut __value = None;
+                            if __content.visit(__Inner::begin(&mut __value), __c).is_ok() {
+                                if let Some(__value) = __value {
+                                    #build;
+                                    return __crate::export::Ok(());
+                                }
+                            }
+                        }
+                    });
+                    continue;
+                }
+
+                // Tagged variants were buffered while looking for the tag
+                let visit_inner = match (&derive_opt.tag, &derive_opt.content) {
+                    (Some(_), Some(content)) => {
+                        visit_content(content, quote! { __Inner::begin(&mut __value) })
+                    }
+                    (Some(_), None) => {
+                        quote! { __flatten.visit(__Inner::begin(&mut __value), __c)?; }
+                    }
+                    (None, _) => quote! { __m.visit(__Inner::begin(&mut __value), __c)?; },
+                };
+
+                arm.push(quote! {
+                    Some(#name) => {
+                        #inner
                         let mut __value = None;
-                        __m.visit(__Inner::begin(&mut __value), __c)?;
+                        #visit_inner
                         let __value = __value.unwrap();
-                        self.__out = __crate::export::Some(#ident::#variant {
-                            #(#field: __value.#field,)*
-                            #(#skipped: #skipped_default(),)* // Fill out skipped fields with their default values
-                        })
+                        #build
                     }
                 })
             }
+            Fields::Unnamed(_) if derive_opt.tag.is_some() && derive_opt.content.is_none() => {
+                let msg = "internally tagged enums only support unit and struct variants";
+                return Err(Error::new_spanned(v, msg).to_compile_error());
+            }
+            Fields::Unnamed(_) if opt.other => {
+                if derive_opt.untagged {
+                    let msg = "`other` can't be used on untagged enums";
+                    return Err(Error::new_spanned(v, msg).to_compile_error());
+                }
+                if derive_opt.content.is_some() {
+                    let msg = "`other` can't be used on adjacently tagged enums";
+                    return Err(Error::new_spanned(v, msg).to_compile_error());
+                }
+                if other.is_some() {
+                    let msg = "only one variant can be marked as `other`";
+                    return Err(Error::new_spanned(v, msg).to_compile_error());
+                }
+                other = Some(variant.clone());
+            }
             Fields::Unnamed(fields) => {
                 let (ty, index): (Vec<_>, Vec<_>) = fields
                     .unnamed
@@ -270,15 +596,54 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
                     .map(|(i, f)| (f.ty.clone(), make_literal_int(i)))
                     .unzip();
 
+                if derive_opt.untagged {
+                    // Newtype variants are read as the inner value
+                    let (value_ty, value) = match &ty[..] {
+                        [ty] => (quote! { #ty }, quote! { __value }),
+                        _ => (quote! { ( #(#ty,)* ) }, quote! { #(__value.#index,)* }),
+                    };
+                    attempt.push(quote! {
+                        {
+                            let mut __value: __crate::export::Option<#value_ty> = __crate::export::None;
+                            if __content.visit(__crate::Deserialize::begin(&mut __value), __c).is_ok() {
+                                if let Some(__value) = __value {
+                                    self.__out = __crate::export::Some(#ident::#variant(#value));
+                                    return __crate::export::Ok(());
+                                }
+                            }
+                        }
+                    });
+                    continue;
+                }
+
+                let begin = quote! { __crate::de::Deserialize::begin(&mut __value) };
+                let visit_value = match &derive_opt.content {
+                    Some(content) => visit_content(content, begin),
+                    None => quote! { __m.visit(#begin, __c)?; },
+                };
+
                 arm.push(quote! {
                     Some(#name) => {
                         let mut __value: Option<( #(#ty,)* )> = None;
-                        __m.visit(__crate::de::Deserialize::begin(&mut __value), __c)?;
+                        #visit_value
                         let __value = __value.unwrap();
                         self.__out = Some(#ident::#variant( #(__value.#index,)*));
                     }
                 });
             }
+            Fields::Unit if derive_opt.untagged => {
+                attempt.push(quote! {
+                    if let __crate::de::Buffered::Null = __content {
+                        self.__out = __crate::export::Some(#ident::#variant);
+                        return __crate::export::Ok(());
+                    }
+                });
+            }
+            Fields::Unit if derive_opt.tag.is_some() => {
+                arm.push(quote! {
+                    Some(#name) => self.__out = __crate::export::Some(#ident::#variant)
+                });
+            }
             Fields::Unit => {
                 unit_variant.push(variant.clone());
                 unit_variant_name.push(name);
@@ -287,7 +652,54 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
     }
 
     // Only create a map visitor if the enum hahs struct and tuple variants
-    let map = if arm.len() > 0 {
+    let map = if let (Some(tag), Some(content)) = (&derive_opt.tag, &derive_opt.content) {
+        // The content may come before the tag, so it's always buffered
+        let unknown = unknown_field_arm(derive_opt.deny_unknown_fields);
+        Some(quote! {
+            fn map(&mut self, __m: &mut dyn __crate::de::Map<'de>, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+                let mut __tag: __crate::export::Option<&'de __crate::export::str> = __crate::export::None;
+                let mut __content: __crate::export::Option<__crate::de::Buffered<'de>> = __crate::export::None;
+                while let Some(__k) = __m.next()? {
+                    match __k {
+                        #tag if __tag.is_none() => __m.visit(__crate::Deserialize::begin(&mut __tag), __c)?,
+                        #content if __content.is_none() => __m.visit(__crate::Deserialize::begin(&mut __content), __c)?,
+                        #unknown
+                    }
+                }
+
+                match __tag {
+                    #( #arm, )*
+                    Some(__variant) => { __crate::export::Err(__crate::Error::unknown_variant(__variant))? },
+                    None => { __crate::export::Err(__crate::Error::missing_field(#tag))? },
+                }
+
+                Ok(())
+            }
+        })
+    } else if let Some(tag) = &derive_opt.tag {
+        // The tag may be anywhere in the map, so the other fields are buffered
+        Some(quote! {
+            fn map(&mut self, __m: &mut dyn __crate::de::Map<'de>, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+                let mut __tag: __crate::export::Option<&'de __crate::export::str> = __crate::export::None;
+                let mut __flatten = __crate::de::Flatten::new();
+                while let Some(__k) = __m.next()? {
+                    if __k == #tag && __tag.is_none() {
+                        __m.visit(__crate::Deserialize::begin(&mut __tag), __c)?;
+                    } else {
+                        __flatten.insert(__k, __m, __c)?;
+                    }
+                }
+
+                match __tag {
+                    #( #arm, )*
+                    Some(__variant) => { __crate::export::Err(__crate::Error::unknown_variant(__variant))? },
+                    None => { __crate::export::Err(__crate::Error::missing_field(#tag))? },
+                }
+
+                Ok(())
+            }
+        })
+    } else if arm.len() > 0 {
         Some(quote! {
             fn map(&mut self, __m: &mut dyn __crate::de::Map<'de>, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
                 match __m.next()? {
@@ -297,9 +709,7 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
                     None => { __crate::export::Err(__crate::Error::expecting("variant"))? },
                 }
 
-                while let Some(s) = __m.next()? {
-                    __m.visit(__crate::de::Visitor::ignore(), __c)?;
-                }
+                __m.ignore_remaining(__c)?;
 
                 Ok(())
             }
@@ -308,13 +718,23 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
         None
     };
 
+    // Unknown names are either captured by the `other` variant or an error
+    let unknown = match &other {
+        Some(other) => {
+            quote! { __variant => #ident::#other(__crate::export::From::from(__variant)), }
+        }
+        None => quote! {
+            __variant => { __crate::export::Err(__crate::Error::unknown_variant(__variant))? },
+        },
+    };
+
     // Only create a string visitor if the enum has unit variants
-    let string = if unit_variant.len() > 0 {
+    let string = if unit_variant.len() > 0 || other.is_some() {
         Some(quote! {
             fn string(&mut self, s: &'de __crate::export::str, _: &mut dyn __crate::de::Context) -> __crate::Result<()> {
                 let value = match s {
                     #( #unit_variant_name => #ident::#unit_variant, )*
-                    __variant => { __crate::export::Err(__crate::Error::unknown_variant(__variant))? },
+                    #unknown
                 };
                 self.__out = __crate::export::Some(value);
                 __crate::export::Ok(())
@@ -324,6 +744,84 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
         None
     };
 
+    let (body, untagged) = if derive_opt.untagged {
+        // Buffer the input so every variant can be tried against it
+        let body = forward_visitor(&quote!(__crate::de::Buffered<'de>), |c| {
+            quote! {
+                match &__inner {
+                    Some(__content) => self.__untagged(__content, #c),
+                    None => __crate::export::Ok(()),
+                }
+            }
+        });
+        let name = ident.to_string();
+        let untagged = quote! {
+            impl #impl_de_generics __Visitor #ty_generics #where_clause {
+                fn __untagged(&mut self, __content: &__crate::de::Buffered<'de>, __c: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+                    #( #attempt )*
+                    __crate::export::Err(__crate::Error::no_matching_variant(#name))
+                }
+            }
+        };
+        (body, Some(untagged))
+    } else {
+        (quote! { #map #string }, None)
+    };
+
+    Ok(quote! {
+        #[allow(non_upper_case_globals)]
+        const _: () = {
+            use #crate_path as __crate;
+
+            #[repr(C)]
+            struct __Visitor #impl_generics #type_where_clause {
+                __out: __crate::export::Option<#ident #ty_generics>,
+            }
+
+            impl #impl_de_generics __crate::de::Visitor<'de> for __Visitor #ty_generics #where_clause {
+                #body
+            }
+
+            #untagged
+
+            impl #impl_de_generics __crate::Deserialize<'de> for #ident #ty_generics #where_clause {
+                fn begin(__out: &mut __crate::export::Option<Self>) -> &mut dyn __crate::de::Visitor<'de> {
+                    unsafe {
+                        &mut *{
+                            __out
+                            as *mut __crate::export::Option<Self>
+                            as *mut __Visitor
+                        }
+                    }
+                }
+            }
+        };
+    })
+}
+
+/// Enum read from its integer discriminant
+fn derive_repr(
+    input: &DeriveInput,
+    enumeration: &DataEnum,
+    derive_opt: &ToctocOptions,
+) -> DeriveResult<TokenStream> {
+    let crate_path = derive_opt.crate_path_or_default();
+    let repr = enum_repr(input, enumeration, derive_opt)?;
+
+    let ident = &input.ident;
+    let input_generics = bound::within_lifetime_bound(&input.generics, "'de");
+    let (impl_de_generics, _, _) = input_generics.split_for_impl();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut variant = vec![];
+    for v in &enumeration.variants {
+        let opt = ToctocVariantOptions::from_variant(v).map_err(|err| err.write_errors())?;
+        if opt.skip || opt.no_de {
+            continue;
+        }
+        variant.push(opt.ident);
+    }
+
     Ok(quote! {
         #[allow(non_upper_case_globals)]
         const _: () = {
@@ -334,9 +832,45 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<
                 __out: __crate::export::Option<#ident #ty_generics>,
             }
 
+            impl #impl_generics __Visitor #ty_generics #where_clause {
+                fn __discriminant(&mut self, n: #repr) -> __crate::Result<()> {
+                    #(
+                        if n == #ident::#variant as #repr {
+                            self.__out = __crate::export::Some(#ident::#variant);
+                            return __crate::export::Ok(());
+                        }
+                    )*
+                    __crate::export::Err(__crate::Error::unknown_variant(&n.to_string()))
+                }
+
+                // Compared in the `repr` type, numbers out of its range can't be any discriminant
+                fn __convert<N: __crate::export::Copy + __crate::export::ToString>(&mut self, n: N) -> __crate::Result<()>
+                where
+                    #repr: __crate::export::TryFrom<N>,
+                {
+                    match <#repr as __crate::export::TryFrom<N>>::try_from(n) {
+                        __crate::export::Ok(d) => self.__discriminant(d),
+                        __crate::export::Err(_) => __crate::export::Err(__crate::Error::unknown_variant(&n.to_string())),
+                    }
+                }
+            }
+
             impl #impl_de_generics __crate::de::Visitor<'de> for __Visitor #ty_generics #where_clause {
-                #map
-                #string
+                fn negative(&mut self, n: i64, _: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+                    self.__convert(n)
+                }
+
+                fn nonnegative(&mut self, n: u64, _: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+                    self.__convert(n)
+                }
+
+                fn int128(&mut self, n: i128, _: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+                    self.__convert(n)
+                }
+
+                fn uint128(&mut self, n: u128, _: &mut dyn __crate::de::Context) -> __crate::Result<()> {
+                    self.__convert(n)
+                }
             }
 
             impl #impl_de_generics __crate::Deserialize<'de> for #ident #ty_generics #where_clause {
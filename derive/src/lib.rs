@@ -3,6 +3,7 @@
 extern crate proc_macro;
 
 mod bound;
+mod by_value;
 mod common;
 mod de;
 mod ser;
@@ -10,7 +11,8 @@ mod ser;
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
-type DeriveResult<T> = std::result::Result<T, T>;
+/// Errors are already turned into `compile_error!` invocations
+type DeriveResult<T> = std::result::Result<T, proc_macro2::TokenStream>;
 
 #[proc_macro_derive(Serialize, attributes(toctoc))]
 pub fn derive_serialize(input: TokenStream) -> TokenStream {
@@ -25,3 +27,10 @@ pub fn derive_deserialize(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|err| err)
         .into()
 }
+
+#[proc_macro_derive(ByValue, attributes(toctoc))]
+pub fn derive_by_value(input: TokenStream) -> TokenStream {
+    by_value::derive(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(|err| err)
+        .into()
+}
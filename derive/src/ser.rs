@@ -16,9 +16,33 @@ pub fn derive(input: DeriveInput) -> DeriveResult<TokenStream> {
 }
 
 fn derive_struct(input: &DeriveInput, fields: &Fields) -> DeriveResult<TokenStream> {
+    let derive_opt = ToctocOptions::from_derive_input(input).map_err(|err| err.write_errors())?;
+    let crate_path = derive_opt.crate_path_or_default();
+
+    if derive_opt.tag.is_some() {
+        let msg = "`tag` is only supported on enums";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.untagged {
+        let msg = "`untagged` is only supported on enums";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.content.is_some() {
+        let msg = "`content` is only supported on enums";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    // Types of the flattened fields
+    let mut flat_ty = vec![];
+
     let body = match fields {
+        _ if derive_opt.transparent => {
+            let member = TransparentField::from_fields(input, fields)?.member;
+            quote! { __crate::ser::Serialize::begin(&self.#member, v, c) }
+        }
         Fields::Named(fields) => {
-            let mut field_name = vec![];
             let mut field = vec![];
 
             for f in &fields.named {
@@ -28,15 +52,42 @@ fn derive_struct(input: &DeriveInput, fields: &Fields) -> DeriveResult<TokenStre
                     continue;
                 }
 
-                let ident = opt.name().unwrap();
-                field.push(ident.clone());
-                field_name.push(ident.to_string());
+                if opt.flatten && opt.serialize_with.is_some() {
+                    let msg = "`flatten` can't be used along with `serialize_with`";
+                    return Err(Error::new_spanned(f, msg).to_compile_error());
+                }
+
+                let name = opt.name(derive_opt.field_rule());
+                let ident = opt.ident.unwrap();
+                let value = opt.serialize_value(quote! { &self.#ident })?;
+                let write = if opt.flatten {
+                    flat_ty.push(&f.ty);
+                    quote! { __map.flatten(#value, c) }
+                } else {
+                    quote! { __map.field(#name, #value, c) }
+                };
+                field.push(match &opt.skip_serializing_if {
+                    Some(skip) => {
+                        quote! { let __map = if #skip(&self.#ident) { __map } else { #write }; }
+                    }
+                    None => quote! { let __map = #write; },
+                });
             }
 
+            // The keys of flattened fields may overlap with the other ones
+            let map = if !flat_ty.is_empty() {
+                quote! {
+                    let mut __keys = v.map().unique_keys();
+                    let __map = __keys.map();
+                }
+            } else {
+                quote! { let __map = v.map(); }
+            };
+
             quote! {
-                v.map()
-                #(.field(#field_name, &self.#field, c))*
-                .done()
+                #map
+                #(#field)*
+                __map.done()
             }
         }
         Fields::Unnamed(fields) => {
@@ -56,16 +107,28 @@ fn derive_struct(input: &DeriveInput, fields: &Fields) -> DeriveResult<TokenStre
         Fields::Unit => quote! { v.null() },
     };
 
-    let derive_opt = ToctocOptions::from_derive_input(input).map_err(|err| err.write_errors())?;
-    let crate_path = derive_opt.crate_path_or_default();
-
     let ident = &input.ident;
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
 
-    // TODO: Custom bounds
-
     let bound = parse_quote!(__crate::ser::Serialize);
-    let where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let types = bound::field_types(&input.data, bound::Trait::Serialize)?;
+    let mut where_clause = bound::where_clause_with_bound(&input.generics, &types, bound);
+    where_clause
+        .predicates
+        .extend(derive_opt.custom_bound(bound::Trait::Serialize)?);
+    where_clause.predicates.extend(
+        flat_ty
+            .iter()
+            .map::<syn::WherePredicate, _>(|ty| parse_quote!(#ty: __crate::ser::SerializeMap)),
+    );
+
+    // Always serialized as a map, so it can be flattened
+    let map_impl = match fields {
+        Fields::Named(_) if !derive_opt.transparent => Some(quote! {
+            impl #impl_generics __crate::ser::SerializeMap for #ident #ty_generics #where_clause {}
+        }),
+        _ => None,
+    };
 
     Ok(quote! {
         #[doc(hidden)]
@@ -78,6 +141,8 @@ fn derive_struct(input: &DeriveInput, fields: &Fields) -> DeriveResult<TokenStre
                     #body
                 }
             }
+
+            #map_impl
         };
     })
 }
@@ -86,17 +151,32 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<Toke
     let derive_opt = ToctocOptions::from_derive_input(input).map_err(|err| err.write_errors())?;
     let crate_path = derive_opt.crate_path_or_default();
 
+    if derive_opt.untagged && derive_opt.tag.is_some() {
+        let msg = "`untagged` can't be used along with `tag`";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.content.is_some() && derive_opt.tag.is_none() {
+        let msg = "`content` must be used along with `tag`";
+        return Err(Error::new_spanned(&input.ident, msg).to_compile_error());
+    }
+
+    if derive_opt.repr {
+        return derive_repr(input, enumeration, &derive_opt);
+    }
+
     let ident = &input.ident;
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
 
-    // TODO: Custom bounds
-
     let bound = parse_quote!(__crate::ser::Serialize);
-    let where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let types = bound::field_types(&input.data, bound::Trait::Serialize)?;
+    let mut where_clause = bound::where_clause_with_bound(&input.generics, &types, bound);
+    where_clause
+        .predicates
+        .extend(derive_opt.custom_bound(bound::Trait::Serialize)?);
 
     let inner_generics = bound::with_lifetime_bound(&input.generics, "'a");
-    let (inner_impl_generics, inner_ty_generics, inner_where_clause) =
-        inner_generics.split_for_impl();
+    let (inner_impl_generics, inner_ty_generics, _) = inner_generics.split_for_impl();
 
     let mut arm = vec![];
     for v in &enumeration.variants {
@@ -105,23 +185,31 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<Toke
         if opt.skip || opt.no_ser {
             continue;
         }
+        if opt.other {
+            check_other_variant(v)?;
+        }
 
         let mut dot2 = None;
         let variant = &opt.ident;
-        let name = opt.name().to_string();
+        let name = opt.name(derive_opt.variant_rule());
 
         match &v.fields {
             Fields::Named(fields) => {
-                let mut field_name = vec![];
+                let mut field_write = vec![];
                 let mut field = vec![];
                 let mut field_ty = vec![];
                 let mut field_deref = vec![];
 
                 for f in &fields.named {
-                    let opt =
+                    let field_opt =
                         ToctocFieldOptions::from_field(f).map_err(|err| err.write_errors())?;
 
-                    if opt.skip || opt.skip_serializing {
+                    if field_opt.flatten {
+                        let msg = "`flatten` is only supported on struct fields";
+                        return Err(Error::new_spanned(f, msg).to_compile_error());
+                    }
+
+                    if field_opt.skip || field_opt.skip_serializing {
                         // Some fields are skipped so add the `..` at the end of the match arm
                         if dot2.is_none() {
                             dot2 = Some(syn::token::Dot2::default());
@@ -130,8 +218,16 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<Toke
                         continue;
                     }
 
-                    field.push(opt.ident.clone());
-                    field_name.push(opt.name().unwrap().to_string());
+                    let name = field_opt.name(derive_opt.variant_field_rule(&opt));
+                    let ident = &field_opt.ident;
+                    let value = field_opt.serialize_value(quote! { &*self.#ident })?;
+                    field_write.push(match &field_opt.skip_serializing_if {
+                        Some(skip) => quote! {
+                            let __map = if #skip(&*self.#ident) { __map } else { __map.field(#name, #value, c) };
+                        },
+                        None => quote! { let __map = __map.field(#name, #value, c); },
+                    });
+                    field.push(field_opt.ident);
 
                     match &f.ty {
                         Type::Reference(r) => {
@@ -145,26 +241,58 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<Toke
                     }
                 }
 
+                let inner = quote! { &__Inner { #( #field: #field_deref #field, )* } };
+                let write = match (&derive_opt.tag, &derive_opt.content) {
+                    _ if derive_opt.untagged => quote! {
+                        __crate::ser::Serialize::begin(#inner, v, c)
+                    },
+                    (Some(tag), Some(content)) => quote! {
+                        v.map()
+                        .field(#tag, &#name, c)
+                        .field(#content, #inner, c)
+                        .done()
+                    },
+                    (Some(tag), None) => quote! {
+                        v.map()
+                        .field(#tag, &#name, c)
+                        .flatten(#inner, c)
+                        .done()
+                    },
+                    (None, _) => quote! {
+                        v.map()
+                        .field(#name, #inner, c)
+                        .done()
+                    },
+                };
+
                 arm.push(quote! {
                     #ident::#variant { #(#field,)* #dot2 } => {
                         struct __Inner #inner_impl_generics {
                             #( #field: &'a #field_ty, )*
                         }
 
-                        impl #inner_impl_generics __crate::ser::Serialize for __Inner #inner_ty_generics #inner_where_clause {
+                        impl #inner_impl_generics __crate::ser::Serialize for __Inner #inner_ty_generics #where_clause {
                             fn begin(&self, v: __crate::ser::Visitor, c: &mut dyn __crate::ser::Context) -> __crate::ser::Done {
-                                v.map()
-                                #(.field(#field_name, &*self.#field, c))*
-                                .done()
+                                let __map = v.map();
+                                #(#field_write)*
+                                __map.done()
                             }
                         }
 
-                        v.map()
-                        .field(#name, &__Inner { #( #field: #field_deref #field, )* }, c)
-                        .done()
+                        impl #inner_impl_generics __crate::ser::SerializeMap for __Inner #inner_ty_generics #where_clause {}
+
+                        #write
                     }
                 })
             }
+            Fields::Unnamed(_) if derive_opt.tag.is_some() && derive_opt.content.is_none() => {
+                let msg = "internally tagged enums only support unit and struct variants";
+                return Err(Error::new_spanned(v, msg).to_compile_error());
+            }
+            Fields::Unnamed(_) if opt.other && derive_opt.content.is_some() => {
+                let msg = "`other` can't be used on adjacently tagged enums";
+                return Err(Error::new_spanned(v, msg).to_compile_error());
+            }
             Fields::Unnamed(fields) => {
                 // ? NOTE: Depends on the implementation for tuples
 
@@ -175,17 +303,50 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<Toke
                     .map(|(i, _)| make_ident(i))
                     .collect();
 
+                let write = match &field[..] {
+                    // Catch all variants are written back as the variant name
+                    [field] if opt.other => quote! {
+                        v.string(#field)
+                    },
+                    // Newtype variants are written as the inner value
+                    [field] if derive_opt.untagged => quote! {
+                        __crate::ser::Serialize::begin(#field, v, c)
+                    },
+                    _ if derive_opt.untagged => quote! {
+                        __crate::ser::Serialize::begin(&(#(#field,)*), v, c)
+                    },
+                    _ => match (&derive_opt.tag, &derive_opt.content) {
+                        (Some(tag), Some(content)) => quote! {
+                            v.map()
+                            .field(#tag, &#name, c)
+                            .field(#content, &(#(#field,)*), c)
+                            .done()
+                        },
+                        _ => quote! {
+                            v.map()
+                            .field(#name, &(#(#field,)*), c)
+                            .done()
+                        },
+                    },
+                };
+
                 arm.push(quote! {
                     #ident::#variant (#(#field,)*) => {
-                        v.map()
-                        .field(#name, &(#(#field,)*), c)
-                        .done()
+                        #write
                     }
                 })
             }
-            Fields::Unit => arm.push(quote! {
-                #ident::#variant => v.string(#name)
+            Fields::Unit if derive_opt.untagged => arm.push(quote! {
+                #ident::#variant => v.null()
             }),
+            Fields::Unit => match &derive_opt.tag {
+                Some(tag) => arm.push(quote! {
+                    #ident::#variant => v.map().field(#tag, &#name, c).done()
+                }),
+                None => arm.push(quote! {
+                    #ident::#variant => v.string(#name)
+                }),
+            },
         }
     }
 
@@ -206,3 +367,59 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> DeriveResult<Toke
         };
     })
 }
+
+/// Enum written as its integer discriminant
+fn derive_repr(
+    input: &DeriveInput,
+    enumeration: &DataEnum,
+    derive_opt: &ToctocOptions,
+) -> DeriveResult<TokenStream> {
+    let crate_path = derive_opt.crate_path_or_default();
+    let repr = enum_repr(input, enumeration, derive_opt)?;
+
+    // Pointer sized integers are written as 64 bits ones
+    let (method, ty) = match &repr.to_string()[..] {
+        "u8" => ("byte", "u8"),
+        "u16" => ("ushort", "u16"),
+        "u32" => ("uint", "u32"),
+        "u64" | "usize" => ("ulong", "u64"),
+        "u128" => ("uint128", "u128"),
+        "i8" => ("sbyte", "i8"),
+        "i16" => ("short", "i16"),
+        "i32" => ("int", "i32"),
+        "i64" | "isize" => ("long", "i64"),
+        _ => ("int128", "i128"),
+    };
+    let method = syn::Ident::new(method, Span::call_site());
+    let ty = syn::Ident::new(ty, Span::call_site());
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut variant = vec![];
+    for v in &enumeration.variants {
+        let opt = ToctocVariantOptions::from_variant(v).map_err(|err| err.write_errors())?;
+        if opt.skip || opt.no_ser {
+            continue;
+        }
+        variant.push(opt.ident);
+    }
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals, unused_attributes, unused_qualifications)]
+        const _: () = {
+            use #crate_path as __crate;
+
+            impl #impl_generics __crate::ser::Serialize for #ident #ty_generics #where_clause {
+                fn begin(&self, v: __crate::ser::Visitor, _: &mut dyn __crate::ser::Context) -> __crate::ser::Done {
+                    match self {
+                        #(#ident::#variant => v.#method(#ident::#variant as #ty),)*
+                        #[allow(unreachable_patterns)]
+                        _ => v.null(),
+                    }
+                }
+            }
+        };
+    })
+}
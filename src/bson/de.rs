@@ -3,6 +3,7 @@ use std::io::Read;
 use std::mem::MaybeUninit;
 use std::str;
 
+use crate::bson::BsonDeOptions;
 use crate::bytes::guess_align_of;
 use crate::de::{Context, Deserialize, DeserializerTrait, Map, Seq, Visitor};
 use crate::error::{Error, Result};
@@ -37,6 +38,42 @@ pub fn from_bin<'de, T: Deserialize<'de>>(b: &'de [u8], ctx: &mut dyn Context) -
     out.ok_or_else(Error::unknown)
 }
 
+/// Same as `from_bin` but fails when documents are nested deeper than `limit`
+pub fn from_bin_with_depth_limit<'de, T: Deserialize<'de>>(
+    b: &'de [u8],
+    ctx: &mut dyn Context,
+    limit: usize,
+) -> Result<T> {
+    from_bin_with_options(b, ctx, BsonDeOptions { max_depth: limit })
+}
+
+/// Deserialize a BSON byte slice with custom `options`.
+///
+/// ```rust
+/// use toctoc::bson::{self, BsonDeOptions};
+///
+/// let options = BsonDeOptions { max_depth: 2 };
+///
+/// let b = bson::to_bin(&vec![vec![1u32]], &mut ());
+/// let out: toctoc::Result<Vec<Vec<u32>>> = bson::from_bin_with_options(&b, &mut (), options);
+/// assert!(out.is_ok());
+///
+/// let b = bson::to_bin(&vec![vec![vec![1u32]]], &mut ());
+/// let out: toctoc::Result<Vec<Vec<Vec<u32>>>> = bson::from_bin_with_options(&b, &mut (), options);
+/// assert!(out.is_err());
+/// ```
+pub fn from_bin_with_options<'de, T: Deserialize<'de>>(
+    b: &'de [u8],
+    ctx: &mut dyn Context,
+    options: BsonDeOptions,
+) -> Result<T> {
+    let mut out = None;
+    let mut de = BsonDe::new(b);
+    de.max_depth = options.max_depth;
+    de.deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
 pub struct BsonDe<'de> {
     /// Remaining buffer slice
     buffer: &'de [u8],
@@ -46,6 +83,10 @@ pub struct BsonDe<'de> {
     index: usize,
     ty: u8,
     key: &'de str,
+    /// Maximum number of nested documents allowed
+    max_depth: usize,
+    /// Current number of nested documents
+    depth: usize,
 }
 
 macro_rules! read_byte_impl {
@@ -74,13 +115,22 @@ impl<'de> BsonDe<'de> {
             align: guess_align_of(buffer.as_ptr()),
             ty: 0,
             key: "",
+            max_depth: BsonDeOptions::default().max_depth,
+            depth: 0,
         }
     }
 
     /// Begin deserialization
     fn begin(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
-        // Root document size
-        self.read_u32()? as usize;
+        // Root document size, must match the entire buffer
+        let size = self.read_u32()? as usize;
+        if size != self.buffer.len() + 4 {
+            Err(err!(
+                "document size mismatch: declared {} actual {}",
+                size,
+                self.buffer.len() + 4
+            ))?
+        }
 
         self.next()?;
 
@@ -100,7 +150,7 @@ impl<'de> BsonDe<'de> {
 
         // Document done and all input was consumed
         if self.read_u8()? != 0 {
-            Err(Error("root document not ended".to_string()))
+            Err(err!("root document not ended"))
         } else if self.buffer.len() != 0 {
             Err(err!("buffer has {} bytes left", self.buffer.len()))
         } else {
@@ -163,6 +213,9 @@ impl<'de> BsonDe<'de> {
             0x02 => {
                 // Utf8 String
                 let size = self.read_u32()?;
+                if size == 0 {
+                    Err(err!("string size must include its '\\0'"))?
+                }
                 let bytes = self.read_bytes((size - 1) as usize)?;
                 // TODO: Maybe implement the `lookup4` algorithm
                 if !faster_utf8_validator::validate(bytes) {
@@ -182,30 +235,44 @@ impl<'de> BsonDe<'de> {
             }
             0x83 => {
                 let n = self.read_u32()?;
-                v.nonnegative(n as u64, c)?;
+                v.nonnegative_u32(n, c)?;
             }
             0x10 => {
                 let n = self.read_i32()?;
-                v.negative(n as i64, c)?;
+                v.negative_i32(n, c)?;
+            }
+            0x86 => {
+                let n = self.read_i128()?;
+                v.int128(n, c)?;
+            }
+            0x87 => {
+                let n = self.read_u128()?;
+                v.uint128(n, c)?;
             }
             0x85 => {
                 let n = self.read_f32()?;
                 v.single(n)?;
             }
             0x04 => {
-                let size = self.read_i32()?;
+                let size = self.read_doc_size()?;
                 // Subtract 4 bytes of the size it self and 1 of '\0' (end document)
-                let e = size as usize + self.index - 5;
-                let mut stack = Stack { e, de: self };
+                let e = size + self.index - 5;
+                self.enter_doc()?;
+                let mut stack = Stack::new(e, self);
                 v.seq(&mut stack, c)?;
+                self.depth -= 1;
+                self.check_doc_end(e, size)?;
                 self.read_u8()?; // '\0' (end document)
                 self.skip(e + 1) // No matter what skip the entire document
             }
             0x03 => {
-                let size = self.read_i32()?;
-                let e = size as usize + self.index - 5;
-                let mut stack = Stack { e, de: self };
+                let size = self.read_doc_size()?;
+                let e = size + self.index - 5;
+                self.enter_doc()?;
+                let mut stack = Stack::new(e, self);
                 v.map(&mut stack, c)?;
+                self.depth -= 1;
+                self.check_doc_end(e, size)?;
                 self.read_u8()?; // '\0' (end document)
                 self.skip(e + 1) // No matter what skip the entire document
             }
@@ -214,6 +281,42 @@ impl<'de> BsonDe<'de> {
         Ok(())
     }
 
+    /// Reads the size of an embedded document, which must fit inside the remaining buffer
+    fn read_doc_size(&mut self) -> Result<usize> {
+        let size = self.read_i32()?;
+        // At least the 4 bytes of the size it self and 1 of '\0' (end document)
+        if size < 5 || size as usize - 4 > self.buffer.len() {
+            Err(err!(
+                "document size mismatch: declared {} available {}",
+                size,
+                self.buffer.len() + 4
+            ))?
+        }
+        Ok(size as usize)
+    }
+
+    /// Keeps track of the nesting depth to avoid overflowing the stack
+    #[inline(always)]
+    fn enter_doc(&mut self) -> Result<()> {
+        if self.depth >= self.max_depth {
+            Err(Error::depth_exceeded(self.max_depth))?
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Makes sure the elements of an embedded document didn't go past its end `e`
+    #[inline(always)]
+    fn check_doc_end(&self, e: usize, size: usize) -> Result<()> {
+        if self.index > e {
+            Err(err!(
+                "document elements overflow its declared size of {}",
+                size
+            ))?
+        }
+        Ok(())
+    }
+
     #[inline(always)]
     fn skip(&mut self, i: usize) {
         if self.index < i {
@@ -223,7 +326,7 @@ impl<'de> BsonDe<'de> {
         }
     }
 
-    read_byte_impl!(u8, i8, u32, i32, u64, i64, f32, f64);
+    read_byte_impl!(u8, i8, u32, i32, u64, i64, u128, i128, f32, f64);
 
     fn read_bytes(&mut self, length: usize) -> Result<&'de [u8]> {
         if length < self.buffer.len() {
@@ -242,61 +345,89 @@ impl<'de> BsonDe<'de> {
 
     /// Reads a sequence of bytes until find a '\0' then return it as str
     fn read_cstring(&mut self) -> Result<&'de str> {
-        for (mut i, byte) in self.buffer.iter().copied().enumerate() {
-            if byte != 0 {
-                continue;
-            }
-            if !byte.is_ascii() {
-                Err(err!("non ascii char ({}) in field name", byte))?
-            }
+        let i = match memchr::memchr(0, self.buffer) {
+            Some(i) => i,
+            None => Err(err!("field name not terminated"))?,
+        };
 
-            unsafe {
-                let ptr = self.buffer.as_ptr();
-                let buf = std::slice::from_raw_parts(ptr, i);
+        let (buf, rem) = self.buffer.split_at(i);
+        // Plus 1 because we don't need the '\0' string terminator
+        self.buffer = &rem[1..];
+        self.index += i + 1;
 
-                // Plus 1 because we don't need the '\0' string terminator
-                i += 1;
-                self.buffer = std::slice::from_raw_parts(ptr.add(i), self.buffer.len() - i);
-                self.index += i;
-
-                // Not an utf8 string, but an ascii sequence
-                return Ok(str::from_utf8_unchecked(buf));
-            }
+        if !faster_utf8_validator::validate(buf) {
+            Err(Error::invalid_utf8())?
         }
-
-        unreachable!()
+        Ok(unsafe { str::from_utf8_unchecked(buf) })
     }
 }
 
 struct Stack<'a, 'de: 'de> {
     e: usize,
     de: &'a mut BsonDe<'de>,
+    /// Last key or element index visited, used to annotate errors
+    key: &'de str,
+    count: usize,
+}
+
+impl<'a, 'de: 'de> Stack<'a, 'de> {
+    fn new(e: usize, de: &'a mut BsonDe<'de>) -> Self {
+        Self {
+            e,
+            de,
+            key: "",
+            count: 0,
+        }
+    }
 }
 
 impl<'a, 'de: 'de> Seq<'de> for Stack<'a, 'de> {
     fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<bool> {
         if self.de.index < self.e {
+            let index = self.count;
+            self.count += 1;
             self.de.next()?;
-            self.de.visit(v, c)?;
+            self.de
+                .visit(v, c)
+                .map_err(|e| e.append_path_index(index))?;
             Ok(true)
         } else {
             Ok(false)
         }
     }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        // Jump to the document end
+        self.de.skip(self.e);
+        Ok(())
+    }
 }
 
 impl<'a, 'de: 'de> Map<'de> for Stack<'a, 'de> {
     fn next(&mut self) -> Result<Option<&'de str>> {
         if self.de.index < self.e {
             self.de.next()?;
-            Ok(Some(self.de.key))
+            self.key = self.de.key;
+            Ok(Some(self.key))
         } else {
             Ok(None)
         }
     }
 
     fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
-        self.de.visit(v, c)
+        let key = self.key;
+        self.de.visit(v, c).map_err(|e| e.append_path_segment(key))
+    }
+
+    fn fields_remaining(&self) -> Option<usize> {
+        // Rough minimum element size, type byte, a single char key and its '\0'
+        Some(self.e.saturating_sub(self.de.index) / 3)
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        // Jump to the document end
+        self.de.skip(self.e);
+        Ok(())
     }
 }
 
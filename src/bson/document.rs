@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+use std::mem::{self, ManuallyDrop};
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+use crate::bson::{drop, Value};
+
+/// A `BTreeMap<String, Value>` with a non-recursive drop impl.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Document<'de> {
+    inner: BTreeMap<String, Value<'de>>,
+}
+
+impl<'de> Drop for Document<'de> {
+    fn drop(&mut self) {
+        for (_, child) in mem::replace(&mut self.inner, BTreeMap::new()) {
+            drop::safely(child);
+        }
+    }
+}
+
+/// Moves the inner map out, `ManuallyDrop` prevents the `Document` drop impl
+/// from running afterward and emptying it
+fn take_inner(document: Document) -> BTreeMap<String, Value> {
+    let document = ManuallyDrop::new(document);
+    unsafe { ptr::read(&document.inner) }
+}
+
+impl<'de> Document<'de> {
+    pub fn new() -> Self {
+        Document {
+            inner: BTreeMap::new(),
+        }
+    }
+}
+
+impl<'de> Deref for Document<'de> {
+    type Target = BTreeMap<String, Value<'de>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'de> DerefMut for Document<'de> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<'de> IntoIterator for Document<'de> {
+    type Item = (String, Value<'de>);
+    type IntoIter = <BTreeMap<String, Value<'de>> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        take_inner(self).into_iter()
+    }
+}
+
+impl<'a, 'de: 'a> IntoIterator for &'a Document<'de> {
+    type Item = (&'a String, &'a Value<'de>);
+    type IntoIter = <&'a BTreeMap<String, Value<'de>> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, 'de: 'a> IntoIterator for &'a mut Document<'de> {
+    type Item = (&'a String, &'a mut Value<'de>);
+    type IntoIter = <&'a mut BTreeMap<String, Value<'de>> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'de> FromIterator<(String, Value<'de>)> for Document<'de> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (String, Value<'de>)>,
+    {
+        Document {
+            inner: BTreeMap::from_iter(iter),
+        }
+    }
+}
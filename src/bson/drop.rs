@@ -0,0 +1,26 @@
+use crate::bson::Value;
+
+pub fn safely(value: Value) {
+    match value {
+        Value::Array(_) | Value::Document(_) => {}
+        _ => return,
+    }
+
+    let mut stack = Vec::new();
+    stack.push(value);
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::Array(vec) => {
+                for child in vec {
+                    stack.push(child);
+                }
+            }
+            Value::Document(map) => {
+                for (_, child) in map {
+                    stack.push(child);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -15,12 +15,37 @@
 //!
 
 mod ser;
-pub use self::ser::to_bin;
-pub use self::ser::BsonSer;
+pub use self::ser::{to_bin, to_bin_into_vec, to_writer};
+pub use self::ser::{BsonBinaryWriter, BsonSer};
 
 mod de;
 pub use self::de::from_bin;
+pub use self::de::from_bin_with_depth_limit;
+pub use self::de::from_bin_with_options;
 pub use self::de::BsonDe;
 
+mod options;
+pub use self::options::BsonDeOptions;
+
+mod reader;
+pub use self::reader::from_reader;
+
+mod validate;
+pub use self::validate::validate;
+
+mod value;
+pub use self::value::Value;
+
+mod array;
+pub use self::array::Array;
+
+mod document;
+pub use self::document::Document;
+
+mod drop;
+
+mod stream;
+pub use self::stream::stream_to_json;
+
 mod owned;
-pub use self::owned::{from_bin_owned, Owned};
+pub use self::owned::{from_bin_iter, from_bin_owned, Owned, OwnedIter};
@@ -0,0 +1,13 @@
+/// Options for `from_bin_with_options`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BsonDeOptions {
+    /// Maximum number of nested documents, deeper inputs fail
+    /// with `Error::depth_exceeded` instead of overflowing the stack
+    pub max_depth: usize,
+}
+
+impl Default for BsonDeOptions {
+    fn default() -> Self {
+        Self { max_depth: 128 }
+    }
+}
@@ -1,11 +1,26 @@
 use crate::de::{Context, Deserialize};
 use crate::error::Result;
-use crate::owned::OwnedRaw;
+use crate::owned::{OwnedIterRaw, OwnedRaw};
 use std::mem::transmute;
 use std::pin::Pin;
 
 pub type Owned<T> = OwnedRaw<Vec<u8>, T>;
 
+pub type OwnedIter<T> = OwnedIterRaw<Vec<u8>, std::vec::IntoIter<T>>;
+
+/// Same as `from_bin` but takes ownership of the `data`, so `T` can borrow
+/// from it and still be moved around.
+///
+/// ```rust
+/// use toctoc::bson;
+///
+/// let b = bson::to_bin(&vec!["a", "b"], &mut ());
+/// let mut v = bson::from_bin_owned::<Vec<&str>>(b, &mut ()).unwrap();
+/// unsafe {
+///     v.as_mut().push("c");
+///     assert_eq!(*v.as_ref(), vec!["a", "b", "c"]);
+/// }
+/// ```
 pub fn from_bin_owned<'de, T: Deserialize<'de>>(
     mut data: Vec<u8>,
     ctx: &mut dyn Context,
@@ -18,3 +33,22 @@ pub fn from_bin_owned<'de, T: Deserialize<'de>>(
         })
     }
 }
+
+/// Deserializes a BSON array and iterates over its elements, that
+/// may borrow from `data`
+///
+/// # Safety
+///
+/// The items borrow from `data` with a forged lifetime, so the caller must
+/// guarantee that none of them (or any borrow taken out of them) outlives
+/// the `OwnedIter` that yields them.
+pub unsafe fn from_bin_iter<'de, T: Deserialize<'de>>(
+    mut data: Vec<u8>,
+    ctx: &mut dyn Context,
+) -> Result<OwnedIter<T>> {
+    let inner: Vec<T> = super::from_bin(transmute(data.as_mut_slice()), ctx)?;
+    Ok(OwnedIter {
+        data: Pin::new(data),
+        iter: Some(inner.into_iter()),
+    })
+}
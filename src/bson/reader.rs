@@ -0,0 +1,57 @@
+use std::io::Read;
+
+use crate::de::{Context, Deserialize};
+use crate::error::{Error, Result};
+
+/// Reads a single BSON document from `reader` then deserializes it.
+///
+/// Only the bytes declared by the document size prefix are read, so
+/// documents written one after another can be read by calling this
+/// function repeatedly with the same `reader`. Like
+/// [`json::from_reader`](crate::json::from_reader) the document is kept in
+/// a buffer dropped before returning, so `T` can't borrow from it.
+///
+/// ```rust
+/// use toctoc::bson;
+/// use std::io::Cursor;
+///
+/// let mut bin = bson::to_bin(&vec![1u32, 2], &mut ());
+/// bin.extend(bson::to_bin(&true, &mut ()));
+///
+/// let mut reader = Cursor::new(bin);
+/// let first: Vec<u32> = bson::from_reader(&mut reader, &mut ()).unwrap();
+/// let second: bool = bson::from_reader(&mut reader, &mut ()).unwrap();
+/// assert_eq!(first, vec![1, 2]);
+/// assert!(second);
+/// ```
+pub fn from_reader<T, R>(mut reader: R, ctx: &mut dyn Context) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+    R: Read,
+{
+    let mut prefix = [0; 4];
+    reader
+        .read_exact(&mut prefix)
+        .map_err(|err| Error::generic(err.to_string()))?;
+    let size = u32::from_le_bytes(prefix) as usize;
+    if size < 5 {
+        Err(err!("invalid document size {}", size))?
+    }
+
+    // The buffer grows while reading, a corrupted size won't allocate
+    // more memory than the actual input
+    let mut bin = prefix.to_vec();
+    reader
+        .take(size as u64 - 4)
+        .read_to_end(&mut bin)
+        .map_err(|err| Error::generic(err.to_string()))?;
+    if bin.len() != size {
+        Err(err!(
+            "unexpected end of document, read {} of {} bytes",
+            bin.len(),
+            size
+        ))?
+    }
+
+    super::from_bin(&bin, ctx)
+}
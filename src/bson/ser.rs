@@ -1,5 +1,7 @@
 use crate::buffer::Buffer;
+use crate::error::{Error, Result};
 use crate::ser::{Context, MapTrait, Return, SeqTrait, Serialize, SerializerTrait, VisitorTrait};
+use std::io;
 
 /// Serialize any serializable type into a BSON byte vec.
 ///
@@ -31,6 +33,52 @@ pub fn to_bin<T: Serialize>(value: &T, context: &mut dyn Context) -> Vec<u8> {
     }
 }
 
+/// Serialize into `buf` reusing its allocated memory, any previous
+/// contents of `buf` will be discarded.
+///
+/// **NOTE** A `Vec<u8>` doesn't guarantee the alignment needed by the serializer,
+/// the memory of `buf` is only reused when it's aligned to `Buffer::ALIGNMENT`
+/// (most allocators always give that much), otherwise it's copied to an aligned [`Buffer`].
+pub fn to_bin_into_vec<T: Serialize>(value: &T, context: &mut dyn Context, buf: &mut Vec<u8>) {
+    let mut bson = BsonSer::with_buffer(Buffer::from_vec(std::mem::take(buf)));
+    value.begin((&mut bson).into(), context);
+    *buf = bson.into_vec();
+}
+
+/// Serialize into a `writer`, like a file or a socket.
+///
+/// **NOTE** Every document starts with its size, so the whole output is
+/// first serialized into memory where sizes can be updated once known,
+/// then passed to the `writer` at once.
+///
+/// A single pass streaming alternative would need a `W: Write + Seek` writer
+/// to go back and patch the size of each document once it's done, it isn't
+/// provided because aligned binary data is padded based on its offset in
+/// the serializer [`Buffer`], so every document would still need to be
+/// buffered to keep them aligned; `seek` also flushes most buffered writers,
+/// making patching the sizes of small nested documents slower than a copy.
+///
+/// ```rust
+/// use toctoc::bson;
+///
+/// let mut out = vec![];
+/// bson::to_writer(&vec![1, 2, 3], &mut (), &mut out).unwrap();
+/// assert_eq!(out, bson::to_bin(&vec![1, 2, 3], &mut ()));
+/// ```
+pub fn to_writer<T: Serialize, W: io::Write>(
+    value: &T,
+    context: &mut dyn Context,
+    mut writer: W,
+) -> Result<()> {
+    let mut bson = BsonSer::new();
+    value.begin((&mut bson).into(), context);
+    let buffer = bson.into_buffer();
+    writer
+        .write_all(buffer.as_slice())
+        .and_then(|_| writer.flush())
+        .map_err(|err| Error::generic(err.to_string()))
+}
+
 pub struct BsonSer<'a> {
     buffer: Buffer,
     doc: Vec<usize>,
@@ -42,8 +90,16 @@ pub struct BsonSer<'a> {
 
 impl<'a> BsonSer<'a> {
     pub fn new() -> Self {
+        Self::with_buffer(Buffer::new())
+    }
+
+    /// Serializer that writes into an existing `buffer` reusing its allocated memory,
+    /// any previous contents of the `buffer` will be discarded
+    pub fn with_buffer(mut buffer: Buffer) -> Self {
+        buffer.clear();
+
         let mut bson = Self {
-            buffer: Buffer::new(),
+            buffer,
             doc: vec![],
             field: None,
             align: Buffer::ALIGNMENT, // Default alignment
@@ -63,6 +119,29 @@ impl<'a> BsonSer<'a> {
         bson
     }
 
+    /// Closes the root document and returns the underling buffer,
+    /// it can be passed to another serializer using `BsonSer::with_buffer`
+    pub fn into_buffer(mut self) -> Buffer {
+        // Root document was already closed by `serialize`
+        if !self.dirty {
+            self.end_doc();
+        }
+        std::mem::replace(&mut self.buffer, Buffer::new())
+    }
+
+    /// Closes the root document and returns the serialized bytes
+    pub fn into_vec(self) -> Vec<u8> {
+        self.into_buffer().into_vec()
+    }
+
+    /// Sets the key of the next value, used to write the fields of a map
+    /// by hand, see [`BsonBinaryWriter`]
+    pub fn key(&mut self, key: &'a str) -> &mut Self {
+        debug_assert!(!key.contains('\0'), "field name contains a nul byte");
+        self.field = Some(key);
+        self
+    }
+
     fn element(&mut self, ty: u8) -> usize {
         // Keep type index to change it later
         let i = self.buffer.len();
@@ -78,6 +157,38 @@ impl<'a> BsonSer<'a> {
         i
     }
 
+    /// Header of aligned binary data (`0x8F`) up to its padding, returns the
+    /// index of its size to be written once known
+    fn aligned_element(&mut self, len: usize, a: usize) -> usize {
+        self.element(0x8F); // Aligned data!
+        let size = self.buffer.len();
+        self.buffer.write_u32(len as u32);
+        self.buffer.write_u32(a as u32);
+
+        if a > self.align {
+            if cfg!(feature = "higher-rank-alignment") {
+                // Buffer must have a higher align requirement
+                self.align = a;
+                self.buffer.replace_u32(11, a as u32);
+            } else {
+                // Sorry, a panic now is better than later figuring the data can't be properly read
+                unimplemented!(
+                    "{} is higher alignment than the default {} isn't supported,\
+                    consider enable the `higher-rank-alignment` feature",
+                    a,
+                    Buffer::ALIGNMENT
+                )
+            }
+        }
+
+        self.buffer.write_u32(0); // Data offset
+        let index = self.buffer.len();
+        let offset = self.buffer.extend_from_slice_aligned(&[], a) - index;
+        self.buffer
+            .replace_u32(index - std::mem::size_of::<u32>(), offset as u32);
+        size
+    }
+
     fn begin_doc(&mut self) {
         let i = self.buffer.len();
         self.buffer.write_u32(0);
@@ -94,6 +205,102 @@ impl<'a> BsonSer<'a> {
     }
 }
 
+/// Writes a binary element (`0x05`) from a stream of bytes, useful to embed
+/// large blobs that aren't fully available in memory.
+///
+/// The element type, key and a placeholder size are written on creation,
+/// the size is updated on `flush` (and when the writer is dropped).
+///
+/// ```rust
+/// use std::io::Write;
+/// use toctoc::bson::{BsonBinaryWriter, BsonSer};
+///
+/// let mut bson = BsonSer::new();
+/// let mut writer = BsonBinaryWriter::new(&mut bson);
+/// writer.write_all(b"hello ").unwrap();
+/// writer.write_all(b"world").unwrap();
+/// writer.flush().unwrap();
+/// drop(writer);
+/// let bin = bson.into_vec();
+/// ```
+///
+/// Inside a map written by hand through the [`VisitorTrait`] and [`MapTrait`]
+/// implementations of [`BsonSer`], use `BsonBinaryWriter::field` instead.
+///
+/// ```rust
+/// use std::io::Write;
+/// use toctoc::bson::{BsonBinaryWriter, BsonSer};
+/// use toctoc::ser::{MapTrait, VisitorTrait};
+///
+/// let mut bson = BsonSer::new();
+/// VisitorTrait::map(&mut bson);
+/// MapTrait::field(&mut bson, "name", &"texture", &mut ());
+/// let mut writer = BsonBinaryWriter::field(&mut bson, "pixels", 4);
+/// writer.write_all(&[0xFF; 64]).unwrap();
+/// drop(writer);
+/// MapTrait::done(&mut bson);
+/// let bin = bson.into_vec();
+/// ```
+pub struct BsonBinaryWriter<'ser, 'a> {
+    bson: &'ser mut BsonSer<'a>,
+    size_offset: usize,
+    data_offset: usize,
+}
+
+impl<'ser, 'a> BsonBinaryWriter<'ser, 'a> {
+    pub fn new(bson: &'ser mut BsonSer<'a>) -> Self {
+        bson.element(0x05);
+        let size_offset = bson.buffer.len();
+        bson.buffer.write_u32(0); // Placeholder size
+        let data_offset = bson.buffer.len();
+        Self {
+            bson,
+            size_offset,
+            data_offset,
+        }
+    }
+
+    /// Writes the field `key` of the current map, with its data aligned
+    /// to `align` just like `Visitor::bytes`
+    pub fn field(bson: &'ser mut BsonSer<'a>, key: &'a str, align: usize) -> Self {
+        bson.key(key);
+        if align == 1 {
+            return Self::new(bson);
+        }
+
+        let size_offset = bson.aligned_element(0, align); // Placeholder size
+        let data_offset = bson.buffer.len();
+        Self {
+            bson,
+            size_offset,
+            data_offset,
+        }
+    }
+
+    fn update_size(&mut self) {
+        let size = self.bson.buffer.len() - self.data_offset;
+        self.bson.buffer.replace_u32(self.size_offset, size as u32);
+    }
+}
+
+impl<'ser, 'a> io::Write for BsonBinaryWriter<'ser, 'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bson.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.update_size();
+        Ok(())
+    }
+}
+
+impl<'ser, 'a> Drop for BsonBinaryWriter<'ser, 'a> {
+    fn drop(&mut self) {
+        self.update_size();
+    }
+}
+
 impl<'a> SerializerTrait for BsonSer<'a> {
     fn serialize(&mut self, s: &dyn Serialize, c: &mut dyn Context) -> Return {
         // Clean up
@@ -110,7 +317,7 @@ impl<'a> SerializerTrait for BsonSer<'a> {
         let mut b = Buffer::new();
         std::mem::swap(&mut self.buffer, &mut b);
 
-        Return::Binary(b.to_vec())
+        Return::Binary(b.into_vec())
     }
 }
 
@@ -152,7 +359,7 @@ impl<'a> VisitorTrait for BsonSer<'a> {
     }
 
     fn long(&mut self, n: i64) {
-        self.element(0x10);
+        self.element(0x12);
         self.buffer.write_i64(n);
     }
 
@@ -161,6 +368,16 @@ impl<'a> VisitorTrait for BsonSer<'a> {
         self.buffer.write_u64(n);
     }
 
+    fn int128(&mut self, n: i128) {
+        self.element(0x86);
+        self.buffer.write_i128(n);
+    }
+
+    fn uint128(&mut self, n: u128) {
+        self.element(0x87);
+        self.buffer.write_u128(n);
+    }
+
     fn single(&mut self, n: f32) {
         self.element(0x85);
         self.buffer.write_f32(n);
@@ -177,31 +394,8 @@ impl<'a> VisitorTrait for BsonSer<'a> {
             self.buffer.write_u32(b.len() as u32);
             self.buffer.extend_from_slice(&b);
         } else {
-            self.element(0x8F); // Aligned data!
-            self.buffer.write_u32(b.len() as u32);
-            self.buffer.write_u32(a as u32);
-
-            if a > self.align {
-                if cfg!(feature = "higher-rank-alignment") {
-                    // Buffer must have a higher align requirement
-                    self.align = a;
-                    self.buffer.replace_u32(11, a as u32);
-                } else {
-                    // Sorry, a panic now is better than later figuring the data can't be properly read
-                    unimplemented!(
-                        "{} is higher alignment than the default {} isn't supported,\
-                        consider enable the `higher-rank-alignment` feature",
-                        a,
-                        Buffer::ALIGNMENT
-                    )
-                }
-            }
-
-            self.buffer.write_u32(0); // Data offset
-            let index = self.buffer.len();
-            let offset = self.buffer.extend_from_slice_aligned(&b, a) - index;
-            self.buffer
-                .replace_u32(index - std::mem::size_of::<u32>(), offset as u32);
+            self.aligned_element(b.len(), a);
+            self.buffer.extend_from_slice(&b);
         }
     }
 
@@ -230,6 +424,8 @@ impl<'a> SeqTrait for BsonSer<'a> {
 
 impl<'a> MapTrait for BsonSer<'a> {
     fn field(&mut self, f: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        // Keys are c_strings, there's no way to represent a '\0' inside them
+        debug_assert!(!f.contains('\0'), "field name contains a nul byte");
         // ? NOTE: We can assume that it will only be used inside this function scope
         self.field = Some(unsafe { std::mem::transmute(f) });
         s.begin(self.into(), c);
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use crate::bson::BsonDe;
+use crate::bytes::guess_align_of;
+use crate::de::{Context, Deserializer, Map, Seq, Visitor};
+use crate::error::{Error, Result};
+use crate::json::escape_str;
+
+/// Converts BSON into JSON, writing each token as soon as is read,
+/// without building any intermediate `json::Value`.
+///
+/// The output is the same of `json::to_string` for the serialized type,
+/// except for binary data, that uses the alignment of the data
+/// inside the BSON buffer.
+///
+/// ```rust
+/// use toctoc::bson;
+///
+/// fn main() -> toctoc::Result<()> {
+///     let b = bson::to_bin(&vec![1u32, 2, 3], &mut ());
+///     let mut j = String::new();
+///     bson::stream_to_json(&b, &mut (), &mut j)?;
+///     assert_eq!(j, "[1,2,3]");
+///     Ok(())
+/// }
+/// ```
+pub fn stream_to_json<W: fmt::Write>(
+    bson: &[u8],
+    ctx: &mut dyn Context,
+    writer: &mut W,
+) -> Result<()> {
+    Deserializer::from(&mut BsonDe::new(bson)).deserialize(&mut JsonStream { w: writer }, ctx)
+}
+
+struct JsonStream<'w> {
+    w: &'w mut dyn fmt::Write,
+}
+
+impl<'w> JsonStream<'w> {
+    fn write(&mut self, s: &str) -> Result<()> {
+        self.w
+            .write_str(s)
+            .map_err(|_| Error::generic("failed to write json".to_string()))
+    }
+
+    fn write_escaped(&mut self, s: &str) -> Result<()> {
+        let mut res = Ok(());
+        escape_str(s, |s| {
+            if res.is_ok() {
+                res = self.w.write_str(s);
+            }
+        });
+        res.map_err(|_| Error::generic("failed to write json".to_string()))
+    }
+}
+
+impl<'w, 'de> Visitor<'de> for JsonStream<'w> {
+    fn null(&mut self, _c: &mut dyn Context) -> Result<()> {
+        self.write("null")
+    }
+
+    fn boolean(&mut self, b: bool) -> Result<()> {
+        self.write(if b { "true" } else { "false" })
+    }
+
+    fn string(&mut self, s: &'de str, _c: &mut dyn Context) -> Result<()> {
+        self.write_escaped(s)
+    }
+
+    fn negative(&mut self, n: i64, _c: &mut dyn Context) -> Result<()> {
+        self.write(itoa::Buffer::new().format(n))
+    }
+
+    fn nonnegative(&mut self, n: u64, _c: &mut dyn Context) -> Result<()> {
+        self.write(itoa::Buffer::new().format(n))
+    }
+
+    fn single(&mut self, n: f32) -> Result<()> {
+        if n.is_finite() {
+            self.write(ryu::Buffer::new().format_finite(n))
+        } else {
+            self.write("null")
+        }
+    }
+
+    fn double(&mut self, n: f64) -> Result<()> {
+        if n.is_finite() {
+            self.write(ryu::Buffer::new().format_finite(n))
+        } else {
+            self.write("null")
+        }
+    }
+
+    fn bytes(&mut self, b: &'de [u8], _c: &mut dyn Context) -> Result<()> {
+        self.write("\"#")?;
+        // Same padding used by the json serializer
+        for _ in 0..(guess_align_of(b.as_ptr()) / 2) {
+            self.write("--")?;
+        }
+        self.write(&bintext::hex::encode(b))?;
+        self.write("\"")
+    }
+
+    fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+        self.write("[")?;
+        let mut first = true;
+        loop {
+            // Comma must be written before visiting the next element
+            let mut element = Element {
+                stream: self,
+                comma: !first,
+            };
+            if !s.visit(&mut element, c)? {
+                break;
+            }
+            first = false;
+        }
+        self.write("]")
+    }
+
+    fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
+        self.write("{")?;
+        let mut first = true;
+        while let Some(k) = m.next()? {
+            if !first {
+                self.write(",")?;
+            }
+            first = false;
+            self.write_escaped(k)?;
+            self.write(":")?;
+            m.visit(self, c)?;
+        }
+        self.write("}")
+    }
+}
+
+/// A sequence doesn't tell if there's a next element until visiting it,
+/// so the comma is only written once the element arrives
+struct Element<'a, 'w> {
+    stream: &'a mut JsonStream<'w>,
+    comma: bool,
+}
+
+impl<'a, 'w> Element<'a, 'w> {
+    fn begin(&mut self) -> Result<&mut JsonStream<'w>> {
+        if self.comma {
+            self.stream.write(",")?;
+        }
+        Ok(self.stream)
+    }
+}
+
+impl<'a, 'w, 'de> Visitor<'de> for Element<'a, 'w> {
+    fn null(&mut self, c: &mut dyn Context) -> Result<()> {
+        self.begin()?.null(c)
+    }
+
+    fn boolean(&mut self, b: bool) -> Result<()> {
+        self.begin()?.boolean(b)
+    }
+
+    fn string(&mut self, s: &'de str, c: &mut dyn Context) -> Result<()> {
+        self.begin()?.string(s, c)
+    }
+
+    fn negative(&mut self, n: i64, c: &mut dyn Context) -> Result<()> {
+        self.begin()?.negative(n, c)
+    }
+
+    fn nonnegative(&mut self, n: u64, c: &mut dyn Context) -> Result<()> {
+        self.begin()?.nonnegative(n, c)
+    }
+
+    fn single(&mut self, n: f32) -> Result<()> {
+        self.begin()?.single(n)
+    }
+
+    fn double(&mut self, n: f64) -> Result<()> {
+        self.begin()?.double(n)
+    }
+
+    fn bytes(&mut self, b: &'de [u8], c: &mut dyn Context) -> Result<()> {
+        self.begin()?.bytes(b, c)
+    }
+
+    fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+        self.begin()?.seq(s, c)
+    }
+
+    fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
+        self.begin()?.map(m, c)
+    }
+}
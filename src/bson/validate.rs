@@ -0,0 +1,76 @@
+use crate::bson::BsonDe;
+use crate::de::{Context, Deserializer, Map, Seq, Visitor};
+use crate::error::Result;
+
+/// Checks that `bin` is a well-formed BSON document without producing any value,
+/// every element type must be known and every size must be within bounds.
+///
+/// ```rust
+/// use toctoc::bson;
+///
+/// let bin = bson::to_bin(&vec!["a", "b"], &mut ());
+/// assert!(bson::validate(&bin).is_ok());
+/// assert!(bson::validate(&bin[..bin.len() - 1]).is_err());
+/// ```
+pub fn validate(bin: &[u8]) -> Result<()> {
+    Deserializer::from(&mut BsonDe::new(bin)).deserialize(&mut Validate, &mut ())
+}
+
+/// Like the `Ignore` visitor but walks into every nested document, because
+/// BSON skips ignored documents by their size without looking at the elements
+struct Validate;
+
+impl<'de> Visitor<'de> for Validate {
+    fn null(&mut self, _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn boolean(&mut self, _b: bool) -> Result<()> {
+        Ok(())
+    }
+
+    fn string(&mut self, _s: &'de str, _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn bytes(&mut self, _b: &'de [u8], _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn negative(&mut self, _n: i64, _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn nonnegative(&mut self, _n: u64, _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn int128(&mut self, _n: i128, _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn uint128(&mut self, _n: u128, _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn single(&mut self, _n: f32) -> Result<()> {
+        Ok(())
+    }
+
+    fn double(&mut self, _n: f64) -> Result<()> {
+        Ok(())
+    }
+
+    // Recursion is bounded by the `BsonDe` nesting depth limit
+    fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+        while s.visit(self, c)? {}
+        Ok(())
+    }
+
+    fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
+        while m.next()?.is_some() {
+            m.visit(self, c)?;
+        }
+        Ok(())
+    }
+}
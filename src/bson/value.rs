@@ -0,0 +1,260 @@
+use std::borrow::Cow;
+
+use crate::bson::{Array, Document};
+use crate::bytes::guess_align_of;
+use crate::de::{self, Deserialize, Map, Seq, Visitor};
+use crate::error::Result;
+use crate::ser::{self, Serialize};
+use crate::Place;
+
+/// Any valid BSON value.
+///
+/// Integers keep the width they were written with, except for `i8` and `u8`
+/// which are read as `Int64` and `UInt64`.
+///
+/// Like [`json::Value`](crate::json::Value) this type has a non-recursive
+/// drop implementation so it is safe to build arbitrarily deeply nested instances.
+///
+/// ```rust
+/// use toctoc::bson::{self, Value};
+///
+/// let bin = bson::to_bin(&vec![1u32, 2], &mut ());
+/// let value: Value = bson::from_bin(&bin, &mut ()).unwrap();
+/// match &value {
+///     Value::Array(array) => assert_eq!(array[1], Value::UInt32(2)),
+///     _ => unreachable!(),
+/// }
+/// assert_eq!(bson::to_bin(&value, &mut ()), bin);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'a> {
+    Null,
+    Bool(bool),
+    Int32(i32),
+    Int64(i64),
+    UInt32(u32),
+    UInt64(u64),
+    Int128(i128),
+    UInt128(u128),
+    Double(f64),
+    Single(f32),
+    String(Cow<'a, str>),
+    Binary {
+        /// Unaligned binary data
+        bytes: Cow<'a, [u8]>,
+        /// Desired bytes alignment
+        align: usize,
+    },
+    Array(Array<'a>),
+    Document(Document<'a>),
+}
+
+impl<'a> Default for Value<'a> {
+    /// The default value is null.
+    fn default() -> Self {
+        Value::Null
+    }
+}
+
+impl<'a> Serialize for Value<'a> {
+    fn begin(&self, v: ser::Visitor, c: &mut dyn ser::Context) -> ser::Done {
+        match self {
+            Value::Null => v.null(),
+            Value::Bool(b) => v.boolean(*b),
+            Value::Int32(n) => v.int(*n),
+            Value::Int64(n) => v.long(*n),
+            Value::UInt32(n) => v.uint(*n),
+            Value::UInt64(n) => v.ulong(*n),
+            Value::Int128(n) => v.int128(*n),
+            Value::UInt128(n) => v.uint128(*n),
+            Value::Double(n) => v.double(*n),
+            Value::Single(n) => v.single(*n),
+            Value::String(s) => v.string(s),
+            Value::Binary { bytes, align } => v.bytes(bytes, *align),
+            Value::Array(array) => {
+                let mut seq = v.seq();
+                for e in array.into_iter() {
+                    seq = seq.element(e, c);
+                }
+                seq.done()
+            }
+            Value::Document(document) => {
+                let mut map = v.map();
+                for (k, e) in document.into_iter() {
+                    map = map.field(k, e, c);
+                }
+                map.done()
+            }
+        }
+    }
+}
+
+impl<'a, 'de: 'a> Deserialize<'de> for Value<'a> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'a, 'de: 'a> Visitor<'de> for Place<Value<'a>> {
+            fn null(&mut self, _c: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::Null);
+                Ok(())
+            }
+
+            fn boolean(&mut self, b: bool) -> Result<()> {
+                self.out = Some(Value::Bool(b));
+                Ok(())
+            }
+
+            fn string(&mut self, s: &'de str, _c: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::String(Cow::Borrowed(s)));
+                Ok(())
+            }
+
+            fn negative(&mut self, n: i64, _c: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::Int64(n));
+                Ok(())
+            }
+
+            fn nonnegative(&mut self, n: u64, _c: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::UInt64(n));
+                Ok(())
+            }
+
+            fn nonnegative_u32(&mut self, n: u32, _c: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::UInt32(n));
+                Ok(())
+            }
+
+            fn negative_i32(&mut self, n: i32, _c: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::Int32(n));
+                Ok(())
+            }
+
+            fn int128(&mut self, n: i128, _c: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::Int128(n));
+                Ok(())
+            }
+
+            fn uint128(&mut self, n: u128, _c: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::UInt128(n));
+                Ok(())
+            }
+
+            fn double(&mut self, n: f64) -> Result<()> {
+                self.out = Some(Value::Double(n));
+                Ok(())
+            }
+
+            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn de::Context) -> Result<()> {
+                let mut array = Array::new();
+                let mut element: Option<Value> = None;
+                while s.visit(Place::new(&mut element), c)? {
+                    if let Some(e) = element.take() {
+                        array.push(e);
+                    }
+                }
+                self.out = Some(Value::Array(array));
+                Ok(())
+            }
+
+            fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn de::Context) -> Result<()> {
+                let mut document = Document::new();
+                let mut value: Option<Value> = None;
+                while let Some(key) = m.next()? {
+                    m.visit(Place::new(&mut value), c)?;
+                    if let Some(v) = value.take() {
+                        document.insert(key.to_owned(), v);
+                    }
+                }
+                self.out = Some(Value::Document(document));
+                Ok(())
+            }
+
+            fn single(&mut self, n: f32) -> Result<()> {
+                self.out = Some(Value::Single(n));
+                Ok(())
+            }
+
+            fn bytes(&mut self, b: &'de [u8], _: &mut dyn de::Context) -> Result<()> {
+                self.out = Some(Value::Binary {
+                    bytes: Cow::Borrowed(b),
+                    align: guess_align_of(b.as_ptr()),
+                });
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bson;
+
+    #[test]
+    fn round_trip() {
+        let mut document = Document::new();
+        document.insert("null".to_string(), Value::Null);
+        document.insert("bool".to_string(), Value::Bool(true));
+        document.insert("i32".to_string(), Value::Int32(-3));
+        document.insert("i64".to_string(), Value::Int64(i64::min_value()));
+        document.insert("u32".to_string(), Value::UInt32(u32::max_value()));
+        document.insert("u64".to_string(), Value::UInt64(u64::max_value()));
+        document.insert("i128".to_string(), Value::Int128(i128::min_value()));
+        document.insert("u128".to_string(), Value::UInt128(u128::max_value()));
+        document.insert("f64".to_string(), Value::Double(-0.5));
+        document.insert("f32".to_string(), Value::Single(1.5));
+        document.insert("str".to_string(), Value::String(Cow::Borrowed("text")));
+        document.insert(
+            "array".to_string(),
+            Value::Array(vec![Value::Null, Value::Int32(1)].into_iter().collect()),
+        );
+        let value = Value::Document(document);
+
+        let bin = bson::to_bin(&value, &mut ());
+        let out: Value = bson::from_bin(&bin, &mut ()).unwrap();
+        assert_eq!(out, value);
+
+        // Strings are borrowed from the input
+        match &out {
+            Value::Document(document) => match &document["str"] {
+                Value::String(Cow::Borrowed(s)) => assert_eq!(*s, "text"),
+                other => panic!("{:?}", other),
+            },
+            _ => unreachable!(),
+        }
+
+        let bin = bson::to_bin(&7u8, &mut ());
+        let out: Value = bson::from_bin(&bin, &mut ()).unwrap();
+        assert_eq!(out, Value::UInt64(7));
+    }
+
+    #[test]
+    fn binary() {
+        let value = Value::Binary {
+            bytes: Cow::Owned(vec![1, 2, 3, 4, 5]),
+            align: 4,
+        };
+        let bin = bson::to_bin(&value, &mut ());
+        let out: Value = bson::from_bin(&bin, &mut ()).unwrap();
+        match out {
+            Value::Binary { bytes, align } => {
+                assert_eq!(&bytes[..], &[1, 2, 3, 4, 5]);
+                assert!(align >= 4);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn deeply_nested_drop() {
+        let mut value = Value::Null;
+        for i in 0..100_000 {
+            value = if i % 2 == 0 {
+                Value::Array(vec![value].into_iter().collect())
+            } else {
+                Value::Document(vec![("k".to_string(), value)].into_iter().collect())
+            };
+        }
+        // no stack overflow when `value` goes out of scope
+    }
+}
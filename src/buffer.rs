@@ -2,14 +2,17 @@
 
 use paste::paste;
 use std::alloc::{alloc, dealloc, realloc, Layout};
+use std::io;
 use std::ptr::null_mut;
-use std::slice::IterMut;
+use std::slice::{IterMut, SliceIndex};
 
 /// Like a byte `Vec` but with underling buffer always aligned with
 /// the maximum alignment requirement given by `extend_from_slice_aligned`
 pub struct Buffer {
     ptr: *mut u8,
     ptr_align: usize,
+    /// Alignment of the `Layout` the memory was allocated with
+    layout_align: usize,
     cap: usize,
     len: usize,
 }
@@ -22,15 +25,40 @@ impl Buffer {
         Buffer {
             ptr: null_mut(),
             ptr_align: Self::ALIGNMENT,
+            layout_align: Self::ALIGNMENT,
             cap: 0,
             len: 0,
         }
     }
 
+    /// Creates a buffer able to hold `capacity` bytes without reallocating
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut buf = Self::new();
+        if capacity > 0 {
+            buf.grow(capacity);
+        }
+        buf
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
 
+    /// Removes all the contents from the buffer, but keeps the allocated memory
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Shortens the buffer to `len` bytes, does nothing if it's already
+    /// shorter; keeps the allocated memory
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
     pub fn as_ptr(&self) -> *const u8 {
         self.ptr
     }
@@ -45,26 +73,53 @@ impl Buffer {
 
     pub fn reserve(&mut self, len: usize) {
         if len > self.cap {
-            let cap = (len << 1).max(4); // new capacity
-            let layout = Layout::from_size_align(cap, self.ptr_align).unwrap();
-
-            let ptr = unsafe {
-                if self.cap == 0 {
-                    alloc(layout)
-                } else {
-                    realloc(
-                        self.ptr,
-                        Layout::from_size_align_unchecked(self.len, self.ptr_align),
-                        cap,
-                    )
-                }
+            self.grow((len << 1).max(4));
+        }
+    }
+
+    /// Like `reserve` but allocates exactly `len` bytes
+    fn reserve_exact(&mut self, len: usize) {
+        if len > self.cap {
+            self.grow(len);
+        }
+    }
+
+    /// Allocates the memory with the layout of a `Vec<u8>` so `into_vec` doesn't
+    /// have to copy it, unless the allocator doesn't give enough alignment
+    fn grow(&mut self, cap: usize) {
+        unsafe {
+            let ptr = if self.cap == 0 {
+                self.layout_align = 1;
+                alloc(Layout::from_size_align_unchecked(cap, 1))
+            } else {
+                let layout = Layout::from_size_align_unchecked(self.cap, self.layout_align);
+                realloc(self.ptr, layout, cap)
             };
 
-            self.ptr = ptr;
+            if ptr.align_offset(self.ptr_align) == 0 {
+                self.ptr = ptr;
+            } else {
+                let aligned = alloc(Layout::from_size_align(cap, self.ptr_align).unwrap());
+                std::ptr::copy_nonoverlapping(ptr, aligned, self.len);
+                dealloc(
+                    ptr,
+                    Layout::from_size_align_unchecked(cap, self.layout_align),
+                );
+                self.ptr = aligned;
+                self.layout_align = self.ptr_align;
+            }
             self.cap = cap;
         }
     }
 
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        if self.cap == 0 {
+            &mut []
+        } else {
+            unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+        }
+    }
+
     pub fn extend_from_slice(&mut self, slice: &[u8]) {
         let len = slice.len() + self.len;
         self.reserve(len);
@@ -76,6 +131,25 @@ impl Buffer {
         self.len = len;
     }
 
+    pub fn push(&mut self, byte: u8) {
+        self.reserve(self.len + 1);
+        unsafe {
+            *self.ptr.add(self.len) = byte;
+        }
+        self.len += 1;
+    }
+
+    /// Extends the buffer with bytes produced lazily, the memory needed
+    /// is reserved upfront using the iterator `size_hint`
+    pub fn extend_from_iter<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve_exact(self.len + lower);
+        for byte in iter {
+            self.push(byte);
+        }
+    }
+
     /// Extends the buffer repeating the same byte `val` a
     /// certain amount of `times`
     pub fn extend_repeating(&mut self, val: u8, times: usize) {
@@ -93,15 +167,53 @@ impl Buffer {
     ///
     /// Returns the index on the buffer where the written `slice` starts
     pub fn extend_from_slice_aligned(&mut self, slice: &[u8], align: usize) -> usize {
-        let padding = unsafe { self.ptr.add(self.len).align_offset(align) };
         self.ptr_align = self.ptr_align.max(align); // Alloc using the maximum allocation
+
+        // The padding depends on the address, so the memory must be aligned first
+        let len = self.len + align + slice.len();
+        if len > self.cap || self.ptr.align_offset(self.ptr_align) != 0 {
+            self.grow((len << 1).max(self.cap));
+        }
+
+        let padding = unsafe { self.ptr.add(self.len).align_offset(align) };
         self.extend_repeating(0, padding);
         let start = self.len();
         self.extend_from_slice(slice);
         start
     }
 
-    pub fn to_vec(self) -> Vec<u8> {
+    /// Takes ownership of the `Vec` memory, only copies it when the
+    /// allocation isn't aligned to `Buffer::ALIGNMENT`
+    pub fn from_vec(vec: Vec<u8>) -> Self {
+        if vec.capacity() == 0 {
+            return Self::new();
+        }
+
+        if vec.as_ptr().align_offset(Self::ALIGNMENT) != 0 {
+            let mut buf = Self::with_capacity(vec.len());
+            buf.extend_from_slice(&vec);
+            return buf;
+        }
+
+        let mut vec = std::mem::ManuallyDrop::new(vec);
+        Buffer {
+            ptr: vec.as_mut_ptr(),
+            ptr_align: Self::ALIGNMENT,
+            layout_align: 1,
+            cap: vec.capacity(),
+            len: vec.len(),
+        }
+    }
+
+    /// Returns the buffer memory as a `Vec`, only copies it when it wasn't
+    /// allocated with the layout of a `Vec<u8>`
+    pub fn into_vec(self) -> Vec<u8> {
+        if self.cap == 0 {
+            return Vec::new();
+        }
+        if self.layout_align != 1 {
+            return self.as_slice().to_vec();
+        }
         unsafe {
             let vec = Vec::from_raw_parts(self.ptr, self.len, self.cap);
             std::mem::forget(self); // Avoid double free
@@ -134,7 +246,7 @@ impl Drop for Buffer {
             if self.ptr != null_mut() {
                 dealloc(
                     self.ptr,
-                    Layout::from_size_align_unchecked(self.cap, Self::ALIGNMENT),
+                    Layout::from_size_align_unchecked(self.cap, self.layout_align),
                 )
             }
         }
@@ -153,7 +265,7 @@ macro_rules! write_impl {
     };
 }
 
-write_impl!(u8, i8, u32, i32, u64, i64, f32, f64);
+write_impl!(u8, i8, u32, i32, u64, i64, u128, i128, f32, f64);
 
 macro_rules! replace_impl {
     ($($t:ty),*) => {
@@ -173,23 +285,55 @@ macro_rules! replace_impl {
 // ? NOTE: This is the only needed value
 replace_impl!(u32);
 
-impl std::ops::Index<usize> for Buffer {
-    type Output = u8;
+impl<I: SliceIndex<[u8]>> std::ops::Index<I> for Buffer {
+    type Output = I::Output;
 
-    fn index(&self, index: usize) -> &Self::Output {
-        if index >= self.len {
-            panic!()
-        }
-        unsafe { self.get_unchecked(index) }
+    fn index(&self, index: I) -> &Self::Output {
+        &self.as_slice()[index]
     }
 }
 
-impl std::ops::IndexMut<usize> for Buffer {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        if index >= self.len {
-            panic!()
-        }
-        unsafe { self.get_mut_unchecked(index) }
+impl<I: SliceIndex<[u8]>> std::ops::IndexMut<I> for Buffer {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        &mut self.as_mut_slice()[index]
+    }
+}
+
+impl std::ops::Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl std::ops::DerefMut for Buffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl AsRef<[u8]> for Buffer {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsMut<[u8]> for Buffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+/// Also makes `&mut Buffer` a writer, by the std blanket impl
+impl io::Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
     }
 }
 
@@ -208,10 +352,167 @@ mod tests {
         assert_eq!(buf.len(), 6);
         assert_eq!(buf.ptr.align_offset(4), 0); // Alignment was kept
 
-        let v = buf.to_vec();
+        let v = buf.into_vec();
         assert_eq!(v, d.to_vec());
     }
 
+    #[test]
+    fn vec_round_trip() {
+        let mut v = Vec::with_capacity(32);
+        v.extend_from_slice(b"round trip");
+        let ptr = v.as_ptr();
+
+        let mut buf = Buffer::from_vec(v);
+        assert_eq!(buf.as_slice(), &b"round trip"[..]);
+        assert_eq!(buf.as_ptr().align_offset(Buffer::ALIGNMENT), 0);
+        if ptr.align_offset(Buffer::ALIGNMENT) == 0 {
+            // Memory was reused
+            assert_eq!(buf.as_ptr(), ptr);
+            assert_eq!(buf.capacity(), 32);
+        }
+
+        buf.extend_from_slice(b"!");
+        let v = buf.into_vec();
+        assert_eq!(v, b"round trip!".to_vec());
+
+        assert!(Buffer::from_vec(vec![]).into_vec().is_empty());
+    }
+
+    #[test]
+    fn vec_layout() {
+        // Grows past the `Vec` memory it took
+        let mut buf = Buffer::from_vec(vec![1, 2, 3, 4]);
+        buf.extend_from_slice(&[5; 100]);
+        assert_eq!(buf.as_ptr().align_offset(Buffer::ALIGNMENT), 0);
+        assert_eq!(buf.len(), 104);
+
+        // Alignments the allocator doesn't give by default
+        let mut buf = Buffer::new();
+        buf.write_u8(1);
+        let start = buf.extend_from_slice_aligned(&[2; 8], 4096);
+        buf.extend_from_slice(&[3; 8192]);
+        assert_eq!(buf.as_ptr().align_offset(4096), 0);
+        assert_eq!(start, 4096);
+        let v = buf.into_vec();
+        assert_eq!(v.len(), 4096 + 8 + 8192);
+        assert_eq!(&v[4096..4104], &[2; 8][..]);
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut buf = Buffer::with_capacity(16);
+        assert_eq!(buf.capacity(), 16);
+        assert_eq!(buf.len(), 0);
+        assert_eq!(buf.as_ptr().align_offset(Buffer::ALIGNMENT), 0);
+
+        // Writes within the capacity don't reallocate
+        let ptr = buf.as_ptr();
+        buf.write_u64(1);
+        buf.write_u32(2);
+        buf.extend_from_slice(&[3, 4, 5, 6]);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert_eq!(buf.capacity(), 16);
+        assert_eq!(buf.len(), 16);
+
+        // Grows past it
+        buf.write_u8(7);
+        assert!(buf.capacity() > 16);
+        assert_eq!(&buf.as_slice()[12..], &[3, 4, 5, 6, 7][..]);
+
+        let buf = Buffer::with_capacity(0);
+        assert_eq!(buf.capacity(), 0);
+        assert_eq!(buf.as_slice(), &[][..]);
+    }
+
+    #[test]
+    fn clear_and_truncate() {
+        let mut buf = Buffer::new();
+        buf.extend_from_slice(&[0, 1, 2, 3, 4, 5]);
+        let cap = buf.capacity();
+
+        buf.truncate(8);
+        assert_eq!(buf.as_slice(), &[0, 1, 2, 3, 4, 5][..]);
+
+        buf.truncate(2);
+        assert_eq!(buf.as_slice(), &[0, 1][..]);
+        assert_eq!(buf.capacity(), cap);
+        buf.write_u8(9);
+        assert_eq!(buf.as_slice(), &[0, 1, 9][..]);
+
+        let ptr = buf.as_ptr();
+        buf.clear();
+        assert_eq!(buf.len(), 0);
+        assert_eq!(buf.as_slice(), &[][..]);
+        assert_eq!(buf.capacity(), cap);
+        buf.extend_from_slice(&[7, 7]);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert_eq!(buf.as_slice(), &[7, 7][..]);
+    }
+
+    #[test]
+    fn io_write() {
+        use std::io::{BufWriter, Write};
+
+        let mut writer = BufWriter::with_capacity(4, Buffer::new());
+        writer.write_all(b"hello").unwrap();
+        write!(writer, " {}!", 42).unwrap();
+        let buf = writer.into_inner().ok().unwrap();
+        assert_eq!(buf.as_slice(), &b"hello 42!"[..]);
+
+        // Through a mutable reference
+        let mut buf = Buffer::new();
+        buf.write_u8(b'>');
+        let mut writer = BufWriter::new(&mut buf);
+        writer.write_all(b" ok").unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        assert_eq!(buf.as_slice(), &b"> ok"[..]);
+    }
+
+    #[test]
+    fn extend_from_iter() {
+        let mut buf = Buffer::new();
+        buf.extend_from_iter(b"abcdef".iter().copied());
+        assert_eq!(buf.as_slice(), &b"abcdef"[..]);
+        // Exact size iterators don't over allocate
+        assert_eq!(buf.capacity(), 6);
+
+        buf.push(b'-');
+        // Unknown size, grows as needed
+        buf.extend_from_iter(b"0123456789".iter().copied().filter(|b| b % 2 == 0));
+        assert_eq!(buf.as_slice(), &b"abcdef-02468"[..]);
+
+        let mut buf = Buffer::with_capacity(8);
+        buf.extend_from_iter(0..4);
+        assert_eq!(buf.capacity(), 8);
+        assert_eq!(buf.as_slice(), &[0, 1, 2, 3][..]);
+    }
+
+    #[test]
+    fn as_slice_conversions() {
+        fn sum(bytes: &[u8]) -> u32 {
+            bytes.iter().map(|b| *b as u32).sum()
+        }
+
+        fn sum_ref<T: AsRef<[u8]>>(bytes: T) -> u32 {
+            sum(bytes.as_ref())
+        }
+
+        let mut buf = Buffer::new();
+        assert_eq!(sum(&buf), 0);
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(sum(&buf), 6);
+        assert_eq!(sum_ref(&buf), 6);
+        assert_eq!(&buf[1..], &[2, 3][..]);
+
+        buf.as_mut().reverse();
+        buf.sort_unstable();
+        buf[0] = 4;
+        assert_eq!(&*buf, &[4, 2, 3][..]);
+    }
+
     #[test]
     fn write_data_aligned() {
         let mut buf = Buffer::new();
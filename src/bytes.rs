@@ -9,6 +9,7 @@
 use crate::error::{Error, Result};
 use crate::{de, ser, Place};
 use std::mem::{align_of, size_of};
+use std::sync::Arc;
 
 /// Wrapper around slices or vec to be (de)serialize as bytes
 #[derive(Default, Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -20,6 +21,50 @@ impl<'a, T: Binary<'a>> Bytes<T> {
     }
 }
 
+/// Compare directly with a slice of elements, like `bytes == &[1u32, 2, 3][..]`
+impl<'b, T: SliceTarget> PartialEq<&'b [T::Item]> for Bytes<T>
+where
+    T::Item: PartialEq,
+{
+    fn eq(&self, other: &&'b [T::Item]) -> bool {
+        self.0.as_slice() == *other
+    }
+}
+
+/// Helper trait to extract the element type of `Bytes` contents
+pub trait SliceTarget {
+    type Item;
+    fn as_slice(&self) -> &[Self::Item];
+}
+
+impl<T> SliceTarget for Vec<T> {
+    type Item = T;
+    fn as_slice(&self) -> &[T] {
+        &self[..]
+    }
+}
+
+impl<T> SliceTarget for &[T] {
+    type Item = T;
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> SliceTarget for Box<[T]> {
+    type Item = T;
+    fn as_slice(&self) -> &[T] {
+        &self[..]
+    }
+}
+
+impl<T> SliceTarget for Arc<[T]> {
+    type Item = T;
+    fn as_slice(&self) -> &[T] {
+        &self[..]
+    }
+}
+
 impl<'a, T: Binary<'a>> ser::Serialize for Bytes<T> {
     fn begin(&self, v: ser::Visitor, _: &mut dyn ser::Context) -> ser::Done {
         let (b, align) = Binary::as_bytes(&self.0);
@@ -52,12 +97,7 @@ pub trait Binary<'a>: Sized + 'a {
 
 impl<'a, T: ByValue + 'a> Binary<'a> for Vec<T> {
     fn as_bytes(&self) -> (&[u8], usize) {
-        (
-            unsafe {
-                std::slice::from_raw_parts(self.as_ptr() as *const u8, self.len() * size_of::<T>())
-            },
-            align_of::<T>(),
-        )
+        slice_as_bytes(self)
     }
 
     fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
@@ -68,14 +108,30 @@ impl<'a, T: ByValue + 'a> Binary<'a> for Vec<T> {
     }
 }
 
+impl<'a, T: ByValue + 'a> Binary<'a> for Box<[T]> {
+    fn as_bytes(&self) -> (&[u8], usize) {
+        slice_as_bytes(self)
+    }
+
+    fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
+        Ok(Vec::from_bytes(bytes)?.into_boxed_slice())
+    }
+}
+
+/// Owned and shareable, useful to hand the same deserialized buffer to many readers
+impl<'a, T: ByValue + 'a> Binary<'a> for Arc<[T]> {
+    fn as_bytes(&self) -> (&[u8], usize) {
+        slice_as_bytes(self)
+    }
+
+    fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
+        Ok(Arc::from(<&[T]>::from_bytes(bytes)?))
+    }
+}
+
 impl<'a, T: ByValue + 'a> Binary<'a> for &'a [T] {
     fn as_bytes(&self) -> (&[u8], usize) {
-        (
-            unsafe {
-                std::slice::from_raw_parts(self.as_ptr() as *const u8, self.len() * size_of::<T>())
-            },
-            align_of::<T>(),
-        )
+        slice_as_bytes(self)
     }
 
     fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
@@ -96,18 +152,82 @@ impl<'a, T: ByValue + 'a> Binary<'a> for &'a [T] {
     }
 }
 
+/// Views the elements of a slice as bytes, alongside their alignment
+fn slice_as_bytes<T: ByValue>(slice: &[T]) -> (&[u8], usize) {
+    let () = T::PADDING_CHECK;
+    (
+        unsafe {
+            std::slice::from_raw_parts(slice.as_ptr() as *const u8, std::mem::size_of_val(slice))
+        },
+        align_of::<T>(),
+    )
+}
+
+/// Derives [`ByValue`](trait.ByValue.html) for `#[repr(C)]` structs with only
+/// `ByValue` fields, which rules out any pointers or references, and without
+/// padding bytes between or after the fields.
+///
+/// ```rust
+/// use toctoc::bytes::{Binary, ByValue};
+///
+/// #[derive(Clone, Copy, ByValue)]
+/// #[repr(C)]
+/// struct Vertex {
+///     position: [f32; 3],
+///     uv: [f32; 2],
+/// }
+///
+/// let vertices = vec![Vertex { position: [0.0; 3], uv: [1.0; 2] }];
+/// let (bytes, align) = vertices.as_bytes();
+/// assert_eq!(bytes.len(), 20);
+/// assert_eq!(align, 4);
+/// ```
+///
+/// Without `#[repr(C)]` the struct layout isn't guaranteed to be the same across builds:
+///
+/// ```compile_fail
+/// use toctoc::bytes::ByValue;
+///
+/// #[derive(Clone, Copy, ByValue)]
+/// struct Vertex {
+///     position: [f32; 3],
+/// }
+/// ```
+///
+/// Padding bytes are uninitialized, so they can't be exposed as bytes either:
+///
+/// ```compile_fail
+/// use toctoc::bytes::ByValue;
+///
+/// #[derive(Clone, Copy, ByValue)]
+/// #[repr(C)]
+/// struct Padded {
+///     a: u8,
+///     b: u32,
+/// }
+/// ```
+pub use toctoc_internal::ByValue;
+
 /// Blanket trait implemented by all types that are represented by value
 ///
 /// **WARNING** Be very careful when implementing this trait on structs
 /// make sure no pointers or borrows are present and the
 /// (Data Layout)[https://doc.rust-lang.org/nomicon/repr-rust.html]
-/// of the struct is consistent across builds;
-pub unsafe trait ByValue: Copy {}
+/// of the struct is consistent across builds and has no padding bytes;
+/// prefer `#[derive(ByValue)]` which checks all of that.
+pub unsafe trait ByValue: Copy {
+    /// Fails to compile when the type has padding bytes, it's evaluated
+    /// every time the type is viewed as bytes
+    #[doc(hidden)]
+    const PADDING_CHECK: () = ();
+}
 
 macro_rules! by_val {
     ($($t:tt),*) => { $(unsafe impl ByValue for $t {})* };
     (<$($v:literal),*>) => {
-        $(unsafe impl<T:ByValue> ByValue for [T; $v] {})*
+        $(unsafe impl<T:ByValue> ByValue for [T; $v] {
+            const PADDING_CHECK: () = T::PADDING_CHECK;
+        })*
     };
     // ($(>$($t:ident),*<),*) => {
     //     $(unsafe impl<$($t: ByValue,)*> ByValue for ($($t),*) {})*
@@ -134,13 +254,10 @@ by_val!(< 1,  2,  3,  4,  5,  6,  7,  8,  9, 10, 11, 12, 13, 14, 15, 16,
 /// Figure out the highest rank alignment of a pointer.
 /// A higher the alignment rank have more memory flexibility, which means
 /// it can be casted to any type that require a lower rank alignment.
+/// Guesses go up to 128 bytes, enough for cache lines and AVX-512 types.
 pub fn guess_align_of<T>(p: *const T) -> usize {
-    const ALIGNMENTS: [usize; 6] = [1, 2, 4, 8, 16, 32];
-    let p = p as usize;
-    1 << ALIGNMENTS[..]
-        .iter()
-        .position(|a| (*a & p) != 0)
-        .unwrap_or(6)
+    const MAX_RANK: u32 = 7; // 128 bytes
+    1 << (p as usize).trailing_zeros().min(MAX_RANK)
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -165,6 +282,23 @@ mod tests {
         is_align!(u8, u16, u32, u64, u128);
     }
 
+    #[test]
+    fn align_guessing_wide() {
+        use std::alloc::{alloc, dealloc, Layout};
+
+        let layout = Layout::from_size_align(1024, 256).unwrap();
+        unsafe {
+            let p = alloc(layout);
+            assert_eq!(guess_align_of(p.add(64)), 64);
+            assert_eq!(guess_align_of(p.add(128)), 128);
+            assert_eq!(guess_align_of(p.add(96)), 32);
+            assert_eq!(guess_align_of(p.add(1)), 1);
+            // Capped at 128
+            assert_eq!(guess_align_of(p), 128);
+            dealloc(p, layout);
+        }
+    }
+
     #[test]
     fn binary_cast() {
         let v = vec![[4u32, 4u32], [4u32, 4u32], [4u32, 4u32]];
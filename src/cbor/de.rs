@@ -0,0 +1,478 @@
+use paste::paste;
+use std::str;
+
+use super::ser::{ARRAY, BYTES, MAP, NEGATIVE, TAG, TEXT, UNSIGNED};
+use crate::cbor::CborDeOptions;
+use crate::de::{Context, Deserialize, DeserializerTrait, Map, Seq, Visitor};
+use crate::error::{Error, Result};
+
+/// Deserialize a CBOR byte slice into any deserializable type.
+///
+/// ```rust
+/// use toctoc::{cbor, Deserialize};
+///
+/// #[derive(Deserialize, Debug)]
+/// struct Example {
+///     code: u32,
+///     message: String,
+/// }
+///
+/// fn main() -> toctoc::Result<()> {
+///     let b = b"\xa2\x64code\x18\xc8\x67message\x62Ok";
+///     let out: Example = cbor::from_bin(b, &mut ())?;
+///     println!("{:?}", out);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn from_bin<'de, T: Deserialize<'de>>(b: &'de [u8], ctx: &mut dyn Context) -> Result<T> {
+    let mut out = None;
+    CborDe::new(b).deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
+/// Deserialize a CBOR byte slice with custom `options`.
+///
+/// ```rust
+/// use toctoc::cbor::{self, CborDeOptions};
+///
+/// let options = CborDeOptions { max_depth: 2 };
+///
+/// let b = cbor::to_bin(&vec![vec![1u32]], &mut ());
+/// let out: toctoc::Result<Vec<Vec<u32>>> = cbor::from_bin_with_options(&b, &mut (), options);
+/// assert!(out.is_ok());
+///
+/// let b = cbor::to_bin(&vec![vec![vec![1u32]]], &mut ());
+/// let out: toctoc::Result<Vec<Vec<Vec<u32>>>> = cbor::from_bin_with_options(&b, &mut (), options);
+/// assert!(out.is_err());
+/// ```
+pub fn from_bin_with_options<'de, T: Deserialize<'de>>(
+    b: &'de [u8],
+    ctx: &mut dyn Context,
+    options: CborDeOptions,
+) -> Result<T> {
+    let mut out = None;
+    let mut de = CborDe::new(b);
+    de.max_depth = options.max_depth;
+    de.deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
+/// **NOTE** Tags are skipped, the tagged data item is deserialized as if it
+/// had none. Indefinite length arrays and maps are supported but not
+/// indefinite length strings, they can't be borrowed from the input.
+pub struct CborDe<'de> {
+    /// Remaining buffer slice
+    buffer: &'de [u8],
+    /// Byte index, used to report errors
+    index: usize,
+    /// Maximum number of nested arrays, maps and tags allowed
+    max_depth: usize,
+    /// Current number of nested arrays, maps and tags
+    depth: usize,
+}
+
+/// The "break" stop code that ends indefinite length items
+const BREAK: u8 = 0xFF;
+
+macro_rules! read_byte_impl {
+    ($($t:ty),*) => {
+        $(paste! {
+            fn [<read_ $t>] (&mut self) -> Result<$t> {
+                let mut a = [0; std::mem::size_of::<$t>()];
+                a.copy_from_slice(self.read_bytes(std::mem::size_of::<$t>())?);
+                Ok($t::from_be_bytes(a))
+            }
+        })*
+    };
+}
+
+/// Provides various functions to read bytes from the inner buffer
+/// and interpreting as big endian bytes many primitive types
+impl<'de> CborDe<'de> {
+    pub fn new(buffer: &'de [u8]) -> Self {
+        Self {
+            buffer,
+            index: 0,
+            max_depth: CborDeOptions::default().max_depth,
+            depth: 0,
+        }
+    }
+
+    /// Begin deserialization
+    fn begin(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        self.visit(v, c)?;
+
+        // All input was consumed
+        if !self.buffer.is_empty() {
+            Err(err!("buffer has {} bytes left", self.buffer.len()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        let initial = self.read_u8()?;
+        let (major, info) = (initial >> 5, initial & 0x1F);
+        match major {
+            UNSIGNED => {
+                let n = self.read_arg(info)?;
+                v.nonnegative(n, c)?;
+            }
+            NEGATIVE => {
+                // Encoded as `-1 - n`
+                let n = self.read_arg(info)?;
+                if n <= i64::max_value() as u64 {
+                    v.negative(!(n as i64), c)?;
+                } else {
+                    v.int128(-1 - n as i128, c)?;
+                }
+            }
+            BYTES => {
+                let len = self.read_len(info)?;
+                let b = self.read_bytes(len)?;
+                v.bytes(b, c)?;
+            }
+            TEXT => {
+                let len = self.read_len(info)?;
+                let s = self.read_str(len)?;
+                v.string(s, c)?;
+            }
+            ARRAY => {
+                let len = self.read_count(info)?;
+                self.visit_seq(len, v, c)?;
+            }
+            MAP => {
+                let len = self.read_count(info)?;
+                self.visit_map(len, v, c)?;
+            }
+            TAG => {
+                self.read_arg(info)?;
+                self.enter()?;
+                self.visit(v, c)?;
+                self.depth -= 1;
+            }
+            // Floats and simple values
+            _ => match info {
+                20 => v.boolean(false)?,
+                21 => v.boolean(true)?,
+                // Undefined is treated as null
+                22 | 23 => v.null(c)?,
+                25 => {
+                    let n = self.read_u16()?;
+                    v.single(f16_to_f32(n))?;
+                }
+                26 => {
+                    let n = self.read_f32()?;
+                    v.single(n)?;
+                }
+                27 => {
+                    let n = self.read_f64()?;
+                    v.double(n)?;
+                }
+                31 => Err(err!("unexpected break"))?,
+                info => Err(err!("unsupported simple value ({})", info))?,
+            },
+        }
+        Ok(())
+    }
+
+    fn visit_seq(
+        &mut self,
+        len: Option<usize>,
+        v: &mut dyn Visitor<'de>,
+        c: &mut dyn Context,
+    ) -> Result<()> {
+        self.enter()?;
+        let mut stack = Stack::new(len, self);
+        v.seq(&mut stack, c)?;
+        let remaining = stack.remaining;
+        self.depth -= 1;
+        // No matter what skip the entire array
+        self.skip_values(remaining)
+    }
+
+    fn visit_map(
+        &mut self,
+        len: Option<usize>,
+        v: &mut dyn Visitor<'de>,
+        c: &mut dyn Context,
+    ) -> Result<()> {
+        self.enter()?;
+        // Keys and values are counted apart
+        let mut stack = Stack::new(len.map(|len| len.saturating_mul(2)), self);
+        v.map(&mut stack, c)?;
+        let remaining = stack.remaining;
+        self.depth -= 1;
+        // No matter what skip the entire map
+        self.skip_values(remaining)
+    }
+
+    /// Keeps track of the nesting depth to avoid overflowing the stack
+    #[inline(always)]
+    fn enter(&mut self) -> Result<()> {
+        if self.depth >= self.max_depth {
+            Err(Error::depth_exceeded(self.max_depth))?
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Skips `n` values (or up to the break when `None`) without recursion,
+    /// nested arrays and maps push the number of values they hold
+    fn skip_values(&mut self, n: Option<usize>) -> Result<()> {
+        if n == Some(0) {
+            return Ok(());
+        }
+        let mut stack = vec![n];
+        while let Some(top) = stack.last_mut() {
+            match top {
+                Some(0) => {
+                    stack.pop();
+                    continue;
+                }
+                Some(n) => *n -= 1,
+                None if self.peek_break() => {
+                    self.read_u8()?;
+                    stack.pop();
+                    continue;
+                }
+                None => {}
+            }
+
+            let initial = self.read_u8()?;
+            let (major, info) = (initial >> 5, initial & 0x1F);
+            match major {
+                UNSIGNED | NEGATIVE => {
+                    self.read_arg(info)?;
+                }
+                BYTES | TEXT => {
+                    let len = self.read_len(info)?;
+                    self.read_bytes(len)?;
+                }
+                ARRAY => stack.push(self.read_count(info)?),
+                MAP => {
+                    let len = self.read_count(info)?;
+                    stack.push(len.map(|len| len.saturating_mul(2)));
+                }
+                TAG => {
+                    // The tagged data item is left to skip
+                    self.read_arg(info)?;
+                    stack.push(Some(1));
+                }
+                // Floats and simple values
+                _ => match info {
+                    24 => self.read_bytes(1).map(drop)?,
+                    25 => self.read_bytes(2).map(drop)?,
+                    26 => self.read_bytes(4).map(drop)?,
+                    27 => self.read_bytes(8).map(drop)?,
+                    28..=30 => Err(err!("reserved additional information ({})", info))?,
+                    31 => Err(err!("unexpected break"))?,
+                    _ => {}
+                },
+            }
+        }
+        Ok(())
+    }
+
+    read_byte_impl!(u8, u16, u32, u64, f32, f64);
+
+    /// Reads the argument of a data item given by its additional information
+    fn read_arg(&mut self, info: u8) -> Result<u64> {
+        Ok(match info {
+            0..=23 => info as u64,
+            24 => self.read_u8()? as u64,
+            25 => self.read_u16()? as u64,
+            26 => self.read_u32()? as u64,
+            27 => self.read_u64()?,
+            31 => Err(err!("unexpected indefinite length"))?,
+            _ => Err(err!("reserved additional information ({})", info))?,
+        })
+    }
+
+    /// Reads the length of bytes and text strings
+    fn read_len(&mut self, info: u8) -> Result<usize> {
+        if info == 31 {
+            Err(err!("indefinite length strings aren't supported"))?
+        }
+        let n = self.read_arg(info)?;
+        // Longer than any buffer, `read_bytes` will fail
+        Ok(n.min(usize::max_value() as u64) as usize)
+    }
+
+    /// Reads the number of elements of arrays and maps, `None` for indefinite lengths
+    fn read_count(&mut self, info: u8) -> Result<Option<usize>> {
+        if info == 31 {
+            return Ok(None);
+        }
+        let n = self.read_arg(info)?;
+        Ok(Some(n.min(usize::max_value() as u64) as usize))
+    }
+
+    fn peek_break(&self) -> bool {
+        self.buffer.first() == Some(&BREAK)
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'de [u8]> {
+        if length <= self.buffer.len() {
+            let (buf, rem) = self.buffer.split_at(length);
+            self.buffer = rem;
+            self.index += length;
+            Ok(buf)
+        } else {
+            Err(err!(
+                "expected to read {} bytes but buffer only have {} left",
+                length,
+                self.buffer.len()
+            ))
+        }
+    }
+
+    fn read_str(&mut self, length: usize) -> Result<&'de str> {
+        let bytes = self.read_bytes(length)?;
+        if !faster_utf8_validator::validate(bytes) {
+            Err(Error::invalid_utf8())?
+        }
+        Ok(unsafe { str::from_utf8_unchecked(bytes) })
+    }
+
+    /// Reads a map key, only text strings are supported
+    fn read_key(&mut self) -> Result<&'de str> {
+        let initial = self.read_u8()?;
+        if initial >> 5 != TEXT {
+            Err(err!("map keys must be strings"))?
+        }
+        let len = self.read_len(initial & 0x1F)?;
+        self.read_str(len)
+    }
+}
+
+/// Decodes a half precision float, as given by the RFC 8949 appendix D
+fn f16_to_f32(half: u16) -> f32 {
+    let exp = (half >> 10) & 0x1F;
+    let mant = (half & 0x3FF) as f32;
+    let n = if exp == 0 {
+        mant * 2f32.powi(-24)
+    } else if exp != 31 {
+        (mant + 1024.0) * 2f32.powi(exp as i32 - 25)
+    } else if mant == 0.0 {
+        f32::INFINITY
+    } else {
+        f32::NAN
+    };
+    if half & 0x8000 != 0 {
+        -n
+    } else {
+        n
+    }
+}
+
+struct Stack<'a, 'de: 'de> {
+    /// Number of values left, keys count as values, `None` for indefinite
+    /// lengths until the break is found
+    remaining: Option<usize>,
+    de: &'a mut CborDe<'de>,
+    /// Last key or element index visited, used to annotate errors
+    key: &'de str,
+    count: usize,
+}
+
+impl<'a, 'de: 'de> Stack<'a, 'de> {
+    fn new(remaining: Option<usize>, de: &'a mut CborDe<'de>) -> Self {
+        Self {
+            remaining,
+            de,
+            key: "",
+            count: 0,
+        }
+    }
+
+    /// Takes the next value, false when there are no more of them
+    fn take(&mut self) -> Result<bool> {
+        match &mut self.remaining {
+            Some(0) => Ok(false),
+            Some(n) => {
+                *n -= 1;
+                Ok(true)
+            }
+            None if self.de.peek_break() => {
+                self.de.read_u8()?;
+                self.remaining = Some(0);
+                Ok(false)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// Every value takes at least one byte, which bounds the hint of corrupted inputs
+    fn hint(&self, values: Option<usize>) -> (usize, Option<usize>) {
+        match values {
+            Some(values) => {
+                let n = values.min(self.de.buffer.len());
+                (n, Some(n))
+            }
+            None => (0, Some(self.de.buffer.len())),
+        }
+    }
+}
+
+impl<'a, 'de: 'de> Seq<'de> for Stack<'a, 'de> {
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<bool> {
+        if self.take()? {
+            let index = self.count;
+            self.count += 1;
+            self.de
+                .visit(v, c)
+                .map_err(|e| e.append_path_index(index))?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.hint(self.remaining)
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        let remaining = self.remaining.replace(0);
+        self.de.skip_values(remaining)
+    }
+}
+
+impl<'a, 'de: 'de> Map<'de> for Stack<'a, 'de> {
+    fn next(&mut self) -> Result<Option<&'de str>> {
+        if self.take()? {
+            self.key = self.de.read_key()?;
+            Ok(Some(self.key))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        match &mut self.remaining {
+            Some(0) => Err(err!("map value visited without its key"))?,
+            Some(n) => *n -= 1,
+            None => {}
+        }
+        let key = self.key;
+        self.de.visit(v, c).map_err(|e| e.append_path_segment(key))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.hint(self.remaining.map(|n| n / 2))
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        let remaining = self.remaining.replace(0);
+        self.de.skip_values(remaining)
+    }
+}
+
+impl<'de> DeserializerTrait<'de> for CborDe<'de> {
+    fn deserialize(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        self.begin(v, c)
+            .map_err(|e| e.append_line_and_column(0, self.index))
+    }
+}
@@ -0,0 +1,22 @@
+//! [CBOR](https://tools.ietf.org/html/rfc8949) data format.
+//!
+//! Integers, lengths and headers are written in their shortest form and
+//! arrays and maps always have a definite length, so `{"a":[1,-1]}` is
+//! serialized as:
+//!
+//! ```text
+//! \xa1 \x61 a \x82 \x01 \x20
+//! ```
+//!
+//! Map keys are always text strings, tags are ignored while deserializing.
+//!
+
+mod ser;
+pub use self::ser::{to_bin, CborSer};
+
+mod de;
+pub use self::de::CborDe;
+pub use self::de::{from_bin, from_bin_with_options};
+
+mod options;
+pub use self::options::CborDeOptions;
@@ -0,0 +1,13 @@
+/// Options for `from_bin_with_options`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CborDeOptions {
+    /// Maximum number of nested arrays, maps and tags, deeper inputs fail
+    /// with `Error::depth_exceeded` instead of overflowing the stack
+    pub max_depth: usize,
+}
+
+impl Default for CborDeOptions {
+    fn default() -> Self {
+        Self { max_depth: 128 }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::ser::Headers;
+use crate::ser::{Context, MapTrait, Return, SeqTrait, Serialize, SerializerTrait, VisitorTrait};
+
+/// Serialize any serializable type into a CBOR byte vec.
+///
+/// ```rust
+/// use toctoc::{cbor, Serialize};
+///
+/// #[derive(Serialize, Debug)]
+/// struct Example {
+///     code: u32,
+///     message: String,
+/// }
+///
+/// fn main() {
+///     let example = Example {
+///         code: 200,
+///         message: "Ok".to_owned(),
+///     };
+///
+///     let b = cbor::to_bin(&example, &mut ());
+///     assert_eq!(b, b"\xa2\x64code\x18\xc8\x67message\x62Ok");
+/// }
+/// ```
+pub fn to_bin<T: Serialize>(value: &T, context: &mut dyn Context) -> Vec<u8> {
+    let mut cbor = CborSer::new();
+    match cbor.serialize(value, context) {
+        Return::Text(_) => unreachable!(),
+        Return::Binary(b) => b,
+    }
+}
+
+/// Major types of the data items
+pub(super) const UNSIGNED: u8 = 0;
+pub(super) const NEGATIVE: u8 = 1;
+pub(super) const BYTES: u8 = 2;
+pub(super) const TEXT: u8 = 3;
+pub(super) const ARRAY: u8 = 4;
+pub(super) const MAP: u8 = 5;
+pub(super) const TAG: u8 = 6;
+
+/// Encodes the initial byte of a data item of the `major` type followed
+/// by its argument `n` in the shortest form, returns the encoded length
+pub(super) fn encode_head(major: u8, n: u64) -> ([u8; 9], usize) {
+    let mut head = [0; 9];
+    let size = if n < 24 {
+        head[0] = major << 5 | n as u8;
+        1
+    } else if n <= u8::max_value() as u64 {
+        head[0] = major << 5 | 24;
+        head[1] = n as u8;
+        2
+    } else if n <= u16::max_value() as u64 {
+        head[0] = major << 5 | 25;
+        head[1..3].copy_from_slice(&(n as u16).to_be_bytes());
+        3
+    } else if n <= u32::max_value() as u64 {
+        head[0] = major << 5 | 26;
+        head[1..5].copy_from_slice(&(n as u32).to_be_bytes());
+        5
+    } else {
+        head[0] = major << 5 | 27;
+        head[1..9].copy_from_slice(&n.to_be_bytes());
+        9
+    };
+    (head, size)
+}
+
+/// **NOTE** The number of elements of arrays and maps is only known once
+/// they are done, so a 9 bytes header is reserved and then replaced by the
+/// shortest one, the output is moved back to remove the unused bytes once
+/// the outermost array or map is done. Indefinite lengths are never written.
+///
+/// Binary data is written without any padding, so the alignment of
+/// `Bytes` isn't kept.
+pub struct CborSer {
+    buffer: Vec<u8>,
+    headers: Headers,
+}
+
+impl CborSer {
+    pub fn new() -> Self {
+        Self {
+            buffer: vec![],
+            headers: Headers::new(9),
+        }
+    }
+
+    /// Returns the serialized bytes
+    pub fn into_vec(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    fn write_head(&mut self, major: u8, n: u64) {
+        let (head, size) = encode_head(major, n);
+        self.buffer.extend_from_slice(&head[..size]);
+    }
+
+    fn begin_collection(&mut self) {
+        self.headers.begin(&mut self.buffer);
+    }
+
+    /// Writes the shortest header of the `major` type in the reserved one
+    fn end_collection(&mut self, major: u8) {
+        self.headers.end(&mut self.buffer, |len, reserved| {
+            let (head, size) = encode_head(major, len as u64);
+            reserved[..size].copy_from_slice(&head[..size]);
+            size
+        });
+    }
+
+    fn count(&mut self) {
+        self.headers.count();
+    }
+}
+
+impl Default for CborSer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerializerTrait for CborSer {
+    fn serialize(&mut self, s: &dyn Serialize, c: &mut dyn Context) -> Return {
+        self.buffer.clear();
+        self.headers.clear();
+
+        s.begin(self.into(), c);
+
+        Return::Binary(std::mem::take(&mut self.buffer))
+    }
+}
+
+impl VisitorTrait for CborSer {
+    fn null(&mut self) {
+        self.buffer.push(0xF6);
+    }
+
+    fn boolean(&mut self, b: bool) {
+        self.buffer.push(if b { 0xF5 } else { 0xF4 });
+    }
+
+    fn string(&mut self, s: &str) {
+        self.write_head(TEXT, s.len() as u64);
+        self.buffer.extend_from_slice(s.as_bytes());
+    }
+
+    fn long(&mut self, n: i64) {
+        if n >= 0 {
+            self.ulong(n as u64)
+        } else {
+            // Negative integers are written as `-1 - n`
+            self.write_head(NEGATIVE, !n as u64)
+        }
+    }
+
+    fn ulong(&mut self, n: u64) {
+        self.write_head(UNSIGNED, n)
+    }
+
+    /// Negative integers down to `-2^64` have their own encoding,
+    /// other values out of the 64 bits range are written as strings
+    fn int128(&mut self, n: i128) {
+        if n >= 0 {
+            self.uint128(n as u128)
+        } else if n >= -(1 << 64) {
+            self.write_head(NEGATIVE, !n as u64)
+        } else {
+            self.string(itoa::Buffer::new().format(n))
+        }
+    }
+
+    fn single(&mut self, n: f32) {
+        self.buffer.push(0xFA);
+        self.buffer.extend_from_slice(&n.to_be_bytes());
+    }
+
+    fn double(&mut self, n: f64) {
+        self.buffer.push(0xFB);
+        self.buffer.extend_from_slice(&n.to_be_bytes());
+    }
+
+    fn bytes(&mut self, b: &[u8], _: usize) {
+        self.write_head(BYTES, b.len() as u64);
+        self.buffer.extend_from_slice(b);
+    }
+
+    fn seq(&mut self) -> &mut dyn SeqTrait {
+        self.begin_collection();
+        self
+    }
+
+    fn map(&mut self) -> &mut dyn MapTrait {
+        self.begin_collection();
+        self
+    }
+}
+
+impl SeqTrait for CborSer {
+    fn element(&mut self, s: &dyn Serialize, c: &mut dyn Context) {
+        self.count();
+        s.begin(self.into(), c);
+    }
+
+    fn done(&mut self) {
+        self.end_collection(ARRAY)
+    }
+}
+
+impl MapTrait for CborSer {
+    fn field(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        self.count();
+        self.string(k);
+        s.begin(self.into(), c);
+    }
+
+    fn done(&mut self) {
+        self.end_collection(MAP)
+    }
+}
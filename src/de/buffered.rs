@@ -0,0 +1,201 @@
+use crate::de::{Context, Deserialize, Map, Seq, Visitor};
+use crate::error::{Error, Result};
+use crate::Place;
+
+/// Any value that can be later replayed into a visitor, as many times as
+/// needed, strings and bytes are always borrowed from the input.
+///
+/// Not public API, used by the derive macros for `#[toctoc(untagged)]` enums
+/// and by [`Flatten`](super::Flatten).
+#[doc(hidden)]
+pub enum Buffered<'de> {
+    Null,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    String(&'de str),
+    Bytes(&'de [u8]),
+    Seq(Vec<Buffered<'de>>),
+    Map(Vec<(&'de str, Buffered<'de>)>),
+}
+
+impl<'de> Buffered<'de> {
+    /// Replays the buffered value into `v`
+    pub fn visit(&self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        match self {
+            Buffered::Null => v.null(c),
+            Buffered::Bool(b) => v.boolean(*b),
+            Buffered::I64(n) => v.negative(*n, c),
+            Buffered::U64(n) => v.nonnegative(*n, c),
+            Buffered::I128(n) => v.int128(*n, c),
+            Buffered::U128(n) => v.uint128(*n, c),
+            Buffered::F32(n) => v.single(*n),
+            Buffered::F64(n) => v.double(*n),
+            Buffered::String(s) => v.string(*s, c),
+            Buffered::Bytes(b) => v.bytes(*b, c),
+            Buffered::Seq(elements) => v.seq(&mut BufferedSeq { elements, index: 0 }, c),
+            Buffered::Map(entries) => v.map(&mut BufferedMap::new(entries), c),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Buffered<'de> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de> Visitor<'de> for Place<Buffered<'de>> {
+            fn null(&mut self, _: &mut dyn Context) -> Result<()> {
+                self.out = Some(Buffered::Null);
+                Ok(())
+            }
+
+            fn boolean(&mut self, b: bool) -> Result<()> {
+                self.out = Some(Buffered::Bool(b));
+                Ok(())
+            }
+
+            fn string(&mut self, s: &'de str, _: &mut dyn Context) -> Result<()> {
+                self.out = Some(Buffered::String(s));
+                Ok(())
+            }
+
+            fn negative(&mut self, n: i64, _: &mut dyn Context) -> Result<()> {
+                self.out = Some(Buffered::I64(n));
+                Ok(())
+            }
+
+            fn nonnegative(&mut self, n: u64, _: &mut dyn Context) -> Result<()> {
+                self.out = Some(Buffered::U64(n));
+                Ok(())
+            }
+
+            fn int128(&mut self, n: i128, _: &mut dyn Context) -> Result<()> {
+                self.out = Some(Buffered::I128(n));
+                Ok(())
+            }
+
+            fn uint128(&mut self, n: u128, _: &mut dyn Context) -> Result<()> {
+                self.out = Some(Buffered::U128(n));
+                Ok(())
+            }
+
+            fn double(&mut self, n: f64) -> Result<()> {
+                self.out = Some(Buffered::F64(n));
+                Ok(())
+            }
+
+            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+                let mut elements = vec![];
+                let mut element = None;
+                while s.visit(Buffered::begin(&mut element), c)? {
+                    if let Some(e) = element.take() {
+                        elements.push(e);
+                    }
+                }
+                self.out = Some(Buffered::Seq(elements));
+                Ok(())
+            }
+
+            fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
+                let mut entries = vec![];
+                let mut value = None;
+                while let Some(k) = m.next()? {
+                    m.visit(Buffered::begin(&mut value), c)?;
+                    if let Some(v) = value.take() {
+                        entries.push((k, v));
+                    }
+                }
+                self.out = Some(Buffered::Map(entries));
+                Ok(())
+            }
+
+            fn single(&mut self, n: f32) -> Result<()> {
+                self.out = Some(Buffered::F32(n));
+                Ok(())
+            }
+
+            fn bytes(&mut self, b: &'de [u8], _: &mut dyn Context) -> Result<()> {
+                self.out = Some(Buffered::Bytes(b));
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
+struct BufferedSeq<'a, 'de> {
+    elements: &'a [Buffered<'de>],
+    index: usize,
+}
+
+impl<'a, 'de> Seq<'de> for BufferedSeq<'a, 'de> {
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<bool> {
+        match self.elements.get(self.index) {
+            Some(e) => {
+                self.index += 1;
+                e.visit(v, c)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.elements.len() - self.index;
+        (len, Some(len))
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        self.index = self.elements.len();
+        Ok(())
+    }
+}
+
+pub(super) struct BufferedMap<'a, 'de> {
+    entries: &'a [(&'de str, Buffered<'de>)],
+    index: usize,
+    value: Option<&'a Buffered<'de>>,
+}
+
+impl<'a, 'de> BufferedMap<'a, 'de> {
+    pub(super) fn new(entries: &'a [(&'de str, Buffered<'de>)]) -> Self {
+        Self {
+            entries,
+            index: 0,
+            value: None,
+        }
+    }
+}
+
+impl<'a, 'de> Map<'de> for BufferedMap<'a, 'de> {
+    fn next(&mut self) -> Result<Option<&'de str>> {
+        match self.entries.get(self.index) {
+            Some((k, v)) => {
+                self.index += 1;
+                self.value = Some(v);
+                Ok(Some(*k))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        match self.value.take() {
+            Some(value) => value.visit(v, c),
+            None => Err(Error::expecting("map key"))?,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.entries.len() - self.index;
+        (len, Some(len))
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        self.index = self.entries.len();
+        self.value = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use crate::de::Context;
+use crate::error::Result;
+use crate::export::{Asset, Entity, Hint};
+
+/// Remembers the assets returned by the `inner` context, so each asset hint
+/// is only resolved once, entities aren't cached.
+///
+/// ```rust
+/// use std::any::TypeId;
+/// use toctoc::de::{CachedContext, Context};
+/// use toctoc::export::{Asset, AssetHandle, Hint};
+///
+/// #[derive(Default)]
+/// struct Loader {
+///     loads: u32,
+/// }
+///
+/// impl Context for Loader {
+///     fn asset(&mut self, _: Hint) -> toctoc::Result<Asset> {
+///         self.loads += 1;
+///         Ok(Asset {
+///             handle: AssetHandle::Plain(self.loads),
+///             id: (TypeId::of::<Loader>(), self.loads),
+///         })
+///     }
+/// }
+///
+/// let mut ctx = CachedContext::new(Loader::default());
+/// ctx.asset(Hint::Str("texture.png")).unwrap();
+/// ctx.asset(Hint::Str("texture.png")).unwrap();
+/// assert_eq!(ctx.into_inner().loads, 1);
+/// ```
+pub struct CachedContext<C: Context> {
+    inner: C,
+    cache: HashMap<String, Asset>,
+}
+
+impl<C: Context> CachedContext<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Forgets all the cached assets
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
+/// Cache key of a hint, each variant has its own prefix so they never collide
+fn key(hint: Hint) -> String {
+    match hint {
+        Hint::Null => String::from("null"),
+        Hint::Number(n) => format!("u:{}", n),
+        Hint::Str(s) => format!("s:{}", s),
+        Hint::Bytes(b) => format!("b:{}", bintext::hex::encode(b)),
+    }
+}
+
+impl<C: Context> Context for CachedContext<C> {
+    fn entity(&mut self, e: Hint) -> Result<Entity> {
+        self.inner.entity(e)
+    }
+
+    fn asset(&mut self, a: Hint) -> Result<Asset> {
+        let key = key(a);
+        if let Some(asset) = self.cache.get(&key) {
+            return Ok(asset.clone());
+        }
+
+        // Errors aren't cached, the next lookup will try again
+        let asset = self.inner.asset(a)?;
+        self.cache.insert(key, asset.clone());
+        Ok(asset)
+    }
+}
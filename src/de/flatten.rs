@@ -0,0 +1,35 @@
+use crate::de::buffered::BufferedMap;
+use crate::de::{Buffered, Context, Deserialize, Map, Visitor};
+use crate::error::Result;
+
+/// Buffers the map entries that weren't claimed by any sibling field,
+/// so they can be replayed into every `#[toctoc(flatten)]` field, it's also
+/// used to hold the variant fields of `#[toctoc(tag = "...")]` enums until
+/// the tag is found.
+///
+/// Not public API, used by the derive macros.
+#[doc(hidden)]
+pub struct Flatten<'de> {
+    entries: Vec<(&'de str, Buffered<'de>)>,
+}
+
+impl<'de> Flatten<'de> {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+
+    /// Buffers the value of the field `k`, must be called right after `m.next()`
+    pub fn insert(&mut self, k: &'de str, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
+        let mut value = None;
+        m.visit(Buffered::begin(&mut value), c)?;
+        if let Some(value) = value {
+            self.entries.push((k, value));
+        }
+        Ok(())
+    }
+
+    /// Visits all the buffered entries as a map
+    pub fn visit(&self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        v.map(&mut BufferedMap::new(&self.entries), c)
+    }
+}
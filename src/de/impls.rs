@@ -1,8 +1,19 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque};
+use std::convert::TryFrom;
 use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{
+    NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32,
+    NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use std::ops::{Range, RangeInclusive};
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::de::{Context, Deserialize, Map, Seq, Visitor};
+use crate::de::{cautious_capacity, Context, Deserialize, Map, Seq, Visitor};
 use crate::error::{Error, Result};
 use crate::Place;
 
@@ -18,6 +29,24 @@ impl<'de> Deserialize<'de> for () {
     }
 }
 
+/// Read from `null` without requiring `T: Deserialize`, missing fields are
+/// also accepted since there's nothing to read
+impl<'de, T: ?Sized> Deserialize<'de> for PhantomData<T> {
+    #[inline]
+    fn default() -> Option<Self> {
+        Some(PhantomData)
+    }
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de, T: ?Sized> Visitor<'de> for Place<PhantomData<T>> {
+            fn null(&mut self, _: &mut dyn Context) -> Result<()> {
+                self.out = Some(PhantomData);
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
 impl<'de> Deserialize<'de> for bool {
     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
         impl<'de> Visitor<'de> for Place<bool> {
@@ -42,6 +71,34 @@ impl<'de> Deserialize<'de> for String {
     }
 }
 
+impl<'de> Deserialize<'de> for char {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de> Visitor<'de> for Place<char> {
+            fn string(&mut self, s: &str, _: &mut dyn Context) -> Result<()> {
+                // Must be a string with exactly one unicode scalar value
+                match s.chars().next() {
+                    Some(ch) if s.chars().count() == 1 => self.out = Some(ch),
+                    _ => Err(Error::expecting("string with a single char"))?,
+                }
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
+impl<'de> Deserialize<'de> for PathBuf {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de> Visitor<'de> for Place<PathBuf> {
+            fn string(&mut self, s: &str, _: &mut dyn Context) -> Result<()> {
+                self.out = Some(PathBuf::from(s));
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
 impl<'a, 'de: 'a> Deserialize<'de> for &'a str {
     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
         impl<'a, 'de: 'a> Visitor<'de> for Place<&'a str> {
@@ -55,7 +112,7 @@ impl<'a, 'de: 'a> Deserialize<'de> for &'a str {
 }
 
 macro_rules! signed {
-    ($ty:ident) => {
+    ($ty:ident $(, $native:ident)?) => {
         impl<'de> Deserialize<'de> for $ty {
             fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
                 impl<'de> Visitor<'de> for Place<$ty> {
@@ -76,6 +133,14 @@ macro_rules! signed {
                             Err(Error::out_of_range(stringify!($ty)))?
                         }
                     }
+
+                    $(
+                        // Native width, no range check needed
+                        fn $native(&mut self, n: $ty, _: &mut dyn Context) -> Result<()> {
+                            self.out = Some(n);
+                            Ok(())
+                        }
+                    )?
                 }
                 Place::new(out)
             }
@@ -84,12 +149,12 @@ macro_rules! signed {
 }
 signed!(i8);
 signed!(i16);
-signed!(i32);
+signed!(i32, negative_i32);
 signed!(i64);
 signed!(isize);
 
 macro_rules! unsigned {
-    ($ty:ident) => {
+    ($ty:ident $(, $native:ident)?) => {
         impl<'de> Deserialize<'de> for $ty {
             fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
                 impl<'de> Visitor<'de> for Place<$ty> {
@@ -101,6 +166,14 @@ macro_rules! unsigned {
                             Err(Error::out_of_range(stringify!($ty)))?
                         }
                     }
+
+                    $(
+                        // Native width, no range check needed
+                        fn $native(&mut self, n: $ty, _: &mut dyn Context) -> Result<()> {
+                            self.out = Some(n);
+                            Ok(())
+                        }
+                    )?
                 }
                 Place::new(out)
             }
@@ -109,10 +182,101 @@ macro_rules! unsigned {
 }
 unsigned!(u8);
 unsigned!(u16);
-unsigned!(u32);
+unsigned!(u32, nonnegative_u32);
 unsigned!(u64);
 unsigned!(usize);
 
+macro_rules! int128 {
+    ($ty:ident) => {
+        impl<'de> Deserialize<'de> for $ty {
+            fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+                impl<'de> Visitor<'de> for Place<$ty> {
+                    fn negative(&mut self, n: i64, _: &mut dyn Context) -> Result<()> {
+                        let n =
+                            $ty::try_from(n).map_err(|_| Error::out_of_range(stringify!($ty)))?;
+                        self.out = Some(n);
+                        Ok(())
+                    }
+
+                    fn nonnegative(&mut self, n: u64, _: &mut dyn Context) -> Result<()> {
+                        let n =
+                            $ty::try_from(n).map_err(|_| Error::out_of_range(stringify!($ty)))?;
+                        self.out = Some(n);
+                        Ok(())
+                    }
+
+                    fn int128(&mut self, n: i128, _: &mut dyn Context) -> Result<()> {
+                        let n =
+                            $ty::try_from(n).map_err(|_| Error::out_of_range(stringify!($ty)))?;
+                        self.out = Some(n);
+                        Ok(())
+                    }
+
+                    fn uint128(&mut self, n: u128, _: &mut dyn Context) -> Result<()> {
+                        let n =
+                            $ty::try_from(n).map_err(|_| Error::out_of_range(stringify!($ty)))?;
+                        self.out = Some(n);
+                        Ok(())
+                    }
+
+                    // Values out of the 64 bits range are written as strings
+                    fn string(&mut self, s: &str, _: &mut dyn Context) -> Result<()> {
+                        match s.parse() {
+                            Ok(n) => self.out = Some(n),
+                            Err(_) => Err(err!("invalid `{}` string `{}`", stringify!($ty), s))?,
+                        }
+                        Ok(())
+                    }
+                }
+                Place::new(out)
+            }
+        }
+    };
+}
+int128!(i128);
+int128!(u128);
+
+macro_rules! nonzero {
+    ($ty:ident, $prim:ident) => {
+        impl<'de> Deserialize<'de> for $ty {
+            fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+                impl<'de> Visitor<'de> for Place<$ty> {
+                    fn negative(&mut self, n: i64, c: &mut dyn Context) -> Result<()> {
+                        let mut prim = None;
+                        $prim::begin(&mut prim).negative(n, c)?;
+                        if let Some(n) = prim {
+                            self.out =
+                                Some($ty::new(n).ok_or(Error::out_of_range(stringify!($ty)))?);
+                        }
+                        Ok(())
+                    }
+
+                    fn nonnegative(&mut self, n: u64, c: &mut dyn Context) -> Result<()> {
+                        let mut prim = None;
+                        $prim::begin(&mut prim).nonnegative(n, c)?;
+                        if let Some(n) = prim {
+                            self.out =
+                                Some($ty::new(n).ok_or(Error::out_of_range(stringify!($ty)))?);
+                        }
+                        Ok(())
+                    }
+                }
+                Place::new(out)
+            }
+        }
+    };
+}
+nonzero!(NonZeroU8, u8);
+nonzero!(NonZeroU16, u16);
+nonzero!(NonZeroU32, u32);
+nonzero!(NonZeroU64, u64);
+nonzero!(NonZeroUsize, usize);
+nonzero!(NonZeroI8, i8);
+nonzero!(NonZeroI16, i16);
+nonzero!(NonZeroI32, i32);
+nonzero!(NonZeroI64, i64);
+nonzero!(NonZeroIsize, isize);
+
 // * MOD: Better support for single and double precistion
 // * floats (avoid any expensive casts whenever possible)
 
@@ -174,80 +338,130 @@ impl<'de> Deserialize<'de> for f32 {
     }
 }
 
-impl<'de, T: Deserialize<'de>> Deserialize<'de> for Box<T> {
-    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
-        impl<'de, T> Visitor<'de> for Place<Box<T>>
-        where
-            T: Deserialize<'de>,
-        {
-            fn null(&mut self, c: &mut dyn Context) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).null(c)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+macro_rules! smart_pointer {
+    ($ptr:ident) => {
+        impl<'de, T: Deserialize<'de>> Deserialize<'de> for $ptr<T> {
+            fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+                impl<'de, T> Visitor<'de> for Place<$ptr<T>>
+                where
+                    T: Deserialize<'de>,
+                {
+                    fn null(&mut self, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).null(c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
 
-            fn boolean(&mut self, b: bool) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).boolean(b)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+                    fn boolean(&mut self, b: bool) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).boolean(b)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
 
-            fn string(&mut self, s: &'de str, c: &mut dyn Context) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).string(s, c)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+                    fn string(&mut self, s: &'de str, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).string(s, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
 
-            fn negative(&mut self, n: i64, c: &mut dyn Context) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).negative(n, c)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+                    fn negative(&mut self, n: i64, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).negative(n, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
 
-            fn nonnegative(&mut self, n: u64, c: &mut dyn Context) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).nonnegative(n, c)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+                    fn nonnegative(&mut self, n: u64, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).nonnegative(n, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
 
-            fn single(&mut self, n: f32) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).single(n)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+                    fn nonnegative_u32(&mut self, n: u32, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).nonnegative_u32(n, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
 
-            fn double(&mut self, n: f64) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).double(n)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+                    fn negative_i32(&mut self, n: i32, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).negative_i32(n, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
 
-            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).seq(s, c)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+                    fn short_int(&mut self, n: i16, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).short_int(n, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
 
-            fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).map(m, c)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
+                    fn int128(&mut self, n: i128, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).int128(n, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
+
+                    fn uint128(&mut self, n: u128, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).uint128(n, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
+
+                    fn single(&mut self, n: f32) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).single(n)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
+
+                    fn double(&mut self, n: f64) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).double(n)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
+
+                    fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).seq(s, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
+
+                    fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).map(m, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
+
+                    fn bytes(&mut self, b: &'de [u8], c: &mut dyn Context) -> Result<()> {
+                        let mut out = None;
+                        Deserialize::begin(&mut out).bytes(b, c)?;
+                        self.out = Some($ptr::new(out.unwrap()));
+                        Ok(())
+                    }
+                }
+
+                Place::new(out)
             }
         }
-
-        Place::new(out)
-    }
+    };
 }
 
+smart_pointer!(Box);
+smart_pointer!(Rc);
+smart_pointer!(Arc);
+
 impl<'de, T: Deserialize<'de>> Deserialize<'de> for Option<T> {
     #[inline]
     fn default() -> Option<Self> {
@@ -419,7 +633,7 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for Vec<T> {
     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
         impl<'de, T: Deserialize<'de>> Visitor<'de> for Place<Vec<T>> {
             fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
-                let mut vec = vec![];
+                let mut vec = Vec::with_capacity(cautious_capacity::<T>(s.size_hint()));
                 let mut element = None;
                 while s.visit(Deserialize::begin(&mut element), c)? {
                     element.take().map(|e| vec.push(e));
@@ -433,6 +647,42 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for Vec<T> {
     }
 }
 
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for VecDeque<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for Place<VecDeque<T>> {
+            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+                let mut deque = VecDeque::with_capacity(cautious_capacity::<T>(s.size_hint()));
+                let mut element = None;
+                while s.visit(Deserialize::begin(&mut element), c)? {
+                    element.take().map(|e| deque.push_back(e));
+                }
+                self.out = Some(deque);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for LinkedList<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for Place<LinkedList<T>> {
+            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+                let mut list = LinkedList::new();
+                let mut element = None;
+                while s.visit(Deserialize::begin(&mut element), c)? {
+                    element.take().map(|e| list.push_back(e));
+                }
+                self.out = Some(list);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
 impl<'de, K, V, H> Deserialize<'de> for HashMap<K, V, H>
 where
     K: FromStr + Hash + Eq,
@@ -447,7 +697,8 @@ where
             H: BuildHasher + Default,
         {
             fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
-                let mut hashmap = HashMap::with_hasher(H::default());
+                let capacity = cautious_capacity::<(K, V)>(m.size_hint());
+                let mut hashmap = HashMap::with_capacity_and_hasher(capacity, H::default());
                 let mut element = None;
                 while let Some(k) = m.next()? {
                     let k = K::from_str(k).map_err(|_| Error::invalid_map_key(k.to_string()))?;
@@ -482,3 +733,122 @@ impl<'de, K: FromStr + Ord, V: Deserialize<'de>> Deserialize<'de> for BTreeMap<K
         Place::new(out)
     }
 }
+
+/// Duplicated elements are ignored, only the first one is kept
+impl<'de, T, H> Deserialize<'de> for HashSet<T, H>
+where
+    T: Deserialize<'de> + Hash + Eq,
+    H: BuildHasher + Default,
+{
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de, T, H> Visitor<'de> for Place<HashSet<T, H>>
+        where
+            T: Deserialize<'de> + Hash + Eq,
+            H: BuildHasher + Default,
+        {
+            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+                let mut set = HashSet::with_hasher(H::default());
+                let mut element = None;
+                while s.visit(Deserialize::begin(&mut element), c)? {
+                    element.take().map(|e| set.insert(e));
+                }
+                self.out = Some(set);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+/// Duplicated elements are ignored, only the first one is kept
+impl<'de, T: Deserialize<'de> + Ord> Deserialize<'de> for BTreeSet<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de, T: Deserialize<'de> + Ord> Visitor<'de> for Place<BTreeSet<T>> {
+            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+                let mut set = BTreeSet::new();
+                let mut element = None;
+                while s.visit(Deserialize::begin(&mut element), c)? {
+                    element.take().map(|e| set.insert(e));
+                }
+                self.out = Some(set);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+/// Reads the `[start, end]` sequence used by ranges, anything other
+/// than exactly two elements is an error
+fn range<'de, T: Deserialize<'de>>(s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<(T, T)> {
+    let mut start = None;
+    s.visit(Deserialize::begin(&mut start), c)?;
+    let start = start.ok_or(Error::missing_element(0))?;
+
+    let mut end = None;
+    s.visit(Deserialize::begin(&mut end), c)?;
+    let end = end.ok_or(Error::missing_element(1))?;
+
+    if s.visit(<dyn Visitor>::ignore(), c)? {
+        Err(Error::expecting("range with only 2 elements"))?
+    }
+    Ok((start, end))
+}
+
+/// Read from a two elements sequence `[start, end]`
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Range<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for Place<Range<T>> {
+            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+                let (start, end) = range(s, c)?;
+                self.out = Some(start..end);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+/// Read from a two elements sequence `[start, end]`, with `end` included
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for RangeInclusive<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for Place<RangeInclusive<T>> {
+            fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
+                let (start, end) = range(s, c)?;
+                self.out = Some(start..=end);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+macro_rules! net_addr {
+    ($ty:ident) => {
+        impl<'de> Deserialize<'de> for $ty {
+            fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+                impl<'de> Visitor<'de> for Place<$ty> {
+                    fn string(&mut self, s: &str, _: &mut dyn Context) -> Result<()> {
+                        match s.parse() {
+                            Ok(addr) => self.out = Some(addr),
+                            Err(_) => Err(err!("invalid `{}` string `{}`", stringify!($ty), s))?,
+                        }
+                        Ok(())
+                    }
+                }
+                Place::new(out)
+            }
+        }
+    };
+}
+
+net_addr!(IpAddr);
+net_addr!(Ipv4Addr);
+net_addr!(Ipv6Addr);
+net_addr!(SocketAddr);
+net_addr!(SocketAddrV4);
+net_addr!(SocketAddrV6);
@@ -130,9 +130,31 @@
 //! }
 //! ```
 
+mod buffered;
+#[cfg(not(feature = "any-context"))]
+mod context;
+mod flatten;
 mod impls;
+mod time;
+#[cfg(feature = "base64")]
+mod with;
+
+#[cfg(not(feature = "any-context"))]
+pub use self::context::CachedContext;
 
-use crate::error::{Error, Result};
+#[doc(hidden)]
+pub use self::buffered::Buffered;
+#[doc(hidden)]
+pub use self::flatten::Flatten;
+#[cfg(feature = "base64")]
+#[doc(hidden)]
+pub use self::with::deserialize_base64;
+
+#[cfg(feature = "rfc3339")]
+pub use crate::ser::SystemTimeRfc3339;
+pub use crate::ser::SystemTimeUnix;
+
+use crate::error::{Error, ErrorKind, Result};
 use crate::export::{Asset, Entity, Hint};
 
 /// Trait for data structures that can be deserialized from a JSON string.
@@ -226,6 +248,50 @@ pub trait Visitor<'de> {
         Err(Error::not_expected("nonnegative"))?
     }
 
+    /// Formats with a native 32 bits unsigned integer may call this instead,
+    /// by default forwards to `nonnegative`
+    fn nonnegative_u32(&mut self, n: u32, c: &mut dyn Context) -> Result<()> {
+        self.nonnegative(n as u64, c)
+    }
+
+    /// Formats with a native 32 bits signed integer may call this instead,
+    /// by default forwards to `negative`
+    fn negative_i32(&mut self, n: i32, c: &mut dyn Context) -> Result<()> {
+        self.negative(n as i64, c)
+    }
+
+    /// Formats with a native 16 bits integer may call this instead,
+    /// by default forwards to `negative` or `nonnegative`
+    fn short_int(&mut self, n: i16, c: &mut dyn Context) -> Result<()> {
+        if n < 0 {
+            self.negative(n as i64, c)
+        } else {
+            self.nonnegative(n as u64, c)
+        }
+    }
+
+    /// Formats with a native 128 bits integer may call this instead,
+    /// by default forwards to `negative` or `nonnegative` if the value fits
+    fn int128(&mut self, n: i128, c: &mut dyn Context) -> Result<()> {
+        if n < 0 {
+            if n < i64::min_value() as i128 {
+                Err(Error::out_of_range("i64"))?
+            }
+            self.negative(n as i64, c)
+        } else {
+            self.uint128(n as u128, c)
+        }
+    }
+
+    /// Formats with a native 128 bits unsigned integer may call this instead,
+    /// by default forwards to `nonnegative` if the value fits
+    fn uint128(&mut self, n: u128, c: &mut dyn Context) -> Result<()> {
+        if n > u64::max_value() as u128 {
+            Err(Error::out_of_range("u64"))?
+        }
+        self.nonnegative(n as u64, c)
+    }
+
     fn double(&mut self, n: f64) -> Result<()> {
         let _ = n;
         Err(Error::not_expected("double"))?
@@ -256,13 +322,63 @@ pub trait Visitor<'de> {
     }
 }
 
+/// Capacity to pre-allocate for `T` elements from a `size_hint`, capped so
+/// a bogus hint can't exhaust the memory
+pub(crate) fn cautious_capacity<T>(hint: (usize, Option<usize>)) -> usize {
+    const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+    hint.0
+        .min(MAX_PREALLOC_BYTES / std::mem::size_of::<T>().max(1))
+}
+
 pub trait Seq<'de> {
     fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<bool>;
+
+    /// Bounds on the number of elements left to visit, like `Iterator::size_hint`,
+    /// used to pre-allocate collections
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
+    /// Skips all the remaining elements.
+    ///
+    /// The default implementation recurses for every nested level,
+    /// formats should override it to skip without recursion
+    fn ignore_remaining(&mut self, c: &mut dyn Context) -> Result<()> {
+        while self.visit(<dyn Visitor>::ignore(), c)? {}
+        Ok(())
+    }
 }
 
 pub trait Map<'de> {
     fn next(&mut self) -> Result<Option<&'de str>>;
     fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()>;
+
+    /// Rough estimate of how many fields are left to visit, useful to pre-allocate
+    /// field tracking data; it's only advisory and shouldn't be relied on for exactness.
+    ///
+    /// Defaults to the upper bound of `size_hint`, formats only need to override it
+    /// when they can guess better than that
+    fn fields_remaining(&self) -> Option<usize> {
+        self.size_hint().1
+    }
+
+    /// Bounds on the number of fields left to visit, like `Iterator::size_hint`,
+    /// used to pre-allocate collections
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
+    /// Skips all the remaining fields, must not be called between `next`
+    /// and `visit`.
+    ///
+    /// The default implementation recurses for every nested level,
+    /// formats should override it to skip without recursion
+    fn ignore_remaining(&mut self, c: &mut dyn Context) -> Result<()> {
+        while self.next()?.is_some() {
+            self.visit(<dyn Visitor>::ignore(), c)?;
+        }
+        Ok(())
+    }
 }
 
 /// Trait that can resolves complex types based on some context.
@@ -282,5 +398,42 @@ pub trait Context {
 #[cfg(not(feature = "any-context"))]
 impl Context for () {}
 
+/// Moves on to the next context when `result` is a `not_expected` error
+#[cfg(not(feature = "any-context"))]
+fn fallback<T>(result: Result<T>, next: impl FnOnce() -> Result<T>) -> Result<T> {
+    match result {
+        Err(err) if matches!(err.kind(), ErrorKind::UnexpectedType { .. }) => next(),
+        result => result,
+    }
+}
+
+#[cfg(not(feature = "any-context"))]
+macro_rules! context_tuple {
+    ($($n:tt $t:ident)+) => {
+        /// Composes many contexts, each one is tried in order until one of them
+        /// doesn't fail with `Error::not_expected`
+        impl<$($t: Context),+> Context for ($($t,)+) {
+            fn entity(&mut self, e: Hint) -> Result<Entity> {
+                let result = Err(Error::not_expected("entity"));
+                $(let result = fallback(result, || self.$n.entity(e));)+
+                result
+            }
+
+            fn asset(&mut self, a: Hint) -> Result<Asset> {
+                let result = Err(Error::not_expected("asset"));
+                $(let result = fallback(result, || self.$n.asset(a));)+
+                result
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "any-context"))]
+context_tuple!(0 C0 1 C1);
+#[cfg(not(feature = "any-context"))]
+context_tuple!(0 C0 1 C1 2 C2);
+#[cfg(not(feature = "any-context"))]
+context_tuple!(0 C0 1 C1 2 C2 3 C3);
+
 #[cfg(feature = "any-context")]
 pub trait Context = std::any::Any;
@@ -0,0 +1,64 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::de::{Context, Deserialize, Visitor};
+use crate::error::{Error, Result};
+use crate::ser::SystemTimeUnix;
+use crate::Place;
+
+#[cfg(feature = "rfc3339")]
+use crate::ser::SystemTimeRfc3339;
+
+impl<'de> Deserialize<'de> for SystemTimeUnix {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de> Visitor<'de> for Place<SystemTimeUnix> {
+            fn nonnegative(&mut self, n: u64, _: &mut dyn Context) -> Result<()> {
+                match UNIX_EPOCH.checked_add(Duration::from_secs(n)) {
+                    Some(t) => self.out = Some(SystemTimeUnix(t)),
+                    None => Err(Error::out_of_range("SystemTime"))?,
+                }
+                Ok(())
+            }
+
+            fn negative(&mut self, n: i64, _: &mut dyn Context) -> Result<()> {
+                let d = Duration::from_secs(n.unsigned_abs());
+                match UNIX_EPOCH.checked_sub(d) {
+                    Some(t) => self.out = Some(SystemTimeUnix(t)),
+                    None => Err(Error::out_of_range("SystemTime"))?,
+                }
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
+#[cfg(feature = "rfc3339")]
+impl<'de> Deserialize<'de> for SystemTimeRfc3339 {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de> Visitor<'de> for Place<SystemTimeRfc3339> {
+            fn string(&mut self, s: &str, _: &mut dyn Context) -> Result<()> {
+                use ::time::format_description::well_known::Rfc3339;
+                use ::time::OffsetDateTime;
+
+                let t = match OffsetDateTime::parse(s, &Rfc3339) {
+                    Ok(t) => t,
+                    Err(_) => Err(err!("invalid RFC 3339 string `{}`", s))?,
+                };
+                let nanos = t.unix_timestamp_nanos();
+                let abs = nanos.unsigned_abs();
+                let d = Duration::new((abs / 1_000_000_000) as u64, (abs % 1_000_000_000) as u32);
+                let t = if nanos >= 0 {
+                    UNIX_EPOCH.checked_add(d)
+                } else {
+                    UNIX_EPOCH.checked_sub(d)
+                };
+                match t {
+                    Some(t) => self.out = Some(SystemTimeRfc3339(t)),
+                    None => Err(Error::out_of_range("SystemTime"))?,
+                }
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
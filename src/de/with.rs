@@ -0,0 +1,59 @@
+use crate::bytes::{Binary, Bytes};
+use crate::de::{Context, Deserialize, Visitor};
+use crate::error::{Error, Result};
+
+make_place!(Place);
+
+/// Begins the visitor of a `#[toctoc(base64)]` field, used by the derive macros.
+/// The string is decoded and handed over to the `Bytes` visitor, so any of the
+/// base64 variants is accepted, with or without padding. Native bytes are also
+/// accepted, as written by the binary formats (BSON, msgpack and CBOR).
+///
+/// Not public API.
+pub fn deserialize_base64<'de, T>(out: &mut Option<Bytes<T>>) -> &mut dyn Visitor<'de>
+where
+    T: for<'a> Binary<'a>,
+{
+    Place::new(out)
+}
+
+impl<'de, T> Visitor<'de> for Place<Bytes<T>>
+where
+    T: for<'a> Binary<'a>,
+{
+    fn string(&mut self, s: &'de str, c: &mut dyn Context) -> Result<()> {
+        // Both alphabets only differ on these two characters
+        let config = if s.contains(&['-', '_'][..]) {
+            base64::URL_SAFE
+        } else {
+            base64::STANDARD
+        };
+
+        let len = (s.len() + 3) / 4 * 3;
+        let mut buffer = aligned_buffer(len);
+        let bytes = as_bytes_mut(&mut buffer);
+        let len = match base64::decode_config_slice(s, config, bytes) {
+            Ok(len) => len,
+            Err(_) => Err(err!("invalid base64 string `{}`", s))?,
+        };
+
+        Deserialize::begin(&mut self.out).bytes(&bytes[..len], c)
+    }
+
+    fn bytes(&mut self, b: &'de [u8], c: &mut dyn Context) -> Result<()> {
+        // Binary formats don't align the bytes unless asked to, so they are copied
+        let mut buffer = aligned_buffer(b.len());
+        let bytes = as_bytes_mut(&mut buffer);
+        bytes[..b.len()].copy_from_slice(b);
+        Deserialize::begin(&mut self.out).bytes(&bytes[..b.len()], c)
+    }
+}
+
+/// Buffer of `u128`s large enough for `len` bytes, so they are aligned for any `ByValue` element
+fn aligned_buffer(len: usize) -> Vec<u128> {
+    vec![0u128; len / 16 + 1]
+}
+
+fn as_bytes_mut(buffer: &mut [u128]) -> &mut [u8] {
+    unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 16) }
+}
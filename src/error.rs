@@ -7,7 +7,7 @@ macro_rules! err {
     ($($tt:tt)*) => {{
         let mut s = String::new();
         ufmt::uwrite!(&mut s, $($tt)*).unwrap();
-        Error(s)
+        Error::generic(s)
     }}
 }
 
@@ -16,7 +16,7 @@ macro_rules! err {
 macro_rules! err {
     // IMPORTANT use `tt` fragments instead of `expr` fragments (i.e. `$($exprs:expr),*`)
     ($($tt:tt)*) => {{
-        Error(format!($($tt)*))
+        Error::generic(format!($($tt)*))
     }}
 }
 
@@ -30,55 +30,165 @@ pub type Result<T> = anyhow::Result<T>;
 
 ///////////////////////////////////////////////////////////////////////////////
 
-/// Error type when deserialization fails.
+/// What went wrong, for when the error must be handled by code instead
+/// of just being logged
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Any error without a more specific kind, like the ones created with [`Error::generic`]
+    Other,
+    /// The visitor doesn't accept this type of data
+    UnexpectedType {
+        got: &'static str,
+    },
+    /// Input is malformed, was expecting something else
+    Expecting(String),
+    MissingField(&'static str),
+    UnknownField(String),
+    MissingElement(usize),
+    UnknownVariant(String),
+    NoMatchingVariant(String),
+    InvalidUtf8,
+    NotEnoughOffset {
+        needed: usize,
+        current: usize,
+    },
+    LowerAlignmentRank {
+        needed: usize,
+        current: usize,
+    },
+    NotAligned {
+        align: usize,
+        offset: usize,
+    },
+    InvalidMapKey(String),
+    DepthExceeded(usize),
+    NonFiniteFloat,
+    OutOfRange(&'static str),
+}
+
+/// Error type when serialization or deserialization fails.
 ///
-/// Kept just like a `String` because it's simpler. In the current target application
-/// the error will be always logged so the used can take action. Therefore there is no reason
-/// to have a underlying enum representation just to be later converted into a string any way.
-pub struct Error(pub(crate) String);
+/// Holds the [`ErrorKind`] along side the message that will be displayed,
+/// in most cases the error will just be logged so the message is built
+/// upfront, along with the line and column when available.
+///
+/// Errors raised inside maps or sequences are displayed with the path
+/// to the failing value, like `$.user.tags[1]: not expected `string``.
+pub struct Error {
+    /// Boxed to keep `Result` small, it's returned by every visit of the
+    /// recursive deserializers
+    inner: Box<ErrorImpl>,
+}
+
+struct ErrorImpl {
+    kind: ErrorKind,
+    message: String,
+    /// Path segments (`.field` or `[index]`) from the innermost value out
+    path: Vec<String>,
+    /// Line and column of the input where the error was found
+    position: Option<(usize, usize)>,
+}
 
 impl Error {
+    fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.inner.kind = kind;
+        self
+    }
+
     pub(crate) fn append_line_and_column(mut self, line: usize, column: usize) -> Self {
+        self.inner.position = Some((line, column));
         if cfg!(feature = "ufmt1") {
-            ufmt::uwrite!(&mut self.0, ", {}:{}", line, column).unwrap();
+            ufmt::uwrite!(&mut self.inner.message, ", {}:{}", line, column).unwrap();
         } else {
             use std::fmt::Write;
-            write!(&mut self.0, ", {}:{}", line, column).unwrap();
+            write!(&mut self.inner.message, ", {}:{}", line, column).unwrap();
         }
         self
     }
 
+    /// Marks the error as raised inside the map field `field`,
+    /// called while the error propagates so the innermost field comes first
+    pub fn append_path_segment(mut self, field: &str) -> Self {
+        let mut segment = String::with_capacity(field.len() + 1);
+        segment.push('.');
+        segment.push_str(field);
+        self.inner.path.push(segment);
+        self
+    }
+
+    /// Same as `append_path_segment` but for the sequence element `index`
+    pub fn append_path_index(mut self, index: usize) -> Self {
+        let mut segment = String::new();
+        segment.push('[');
+        segment.push_str(itoa::Buffer::new().format(index));
+        segment.push(']');
+        self.inner.path.push(segment);
+        self
+    }
+
+    /// Kind of the error
+    pub fn kind(&self) -> &ErrorKind {
+        &self.inner.kind
+    }
+
+    /// Line and column of the input where the error was found, both starting
+    /// at 1 for JSON; binary formats use line 0 and the byte offset as column.
+    /// Always `None` for the SIMD JSON deserializer
+    pub fn position(&self) -> Option<(usize, usize)> {
+        self.inner.position
+    }
+
     pub fn unknown() -> Self {
-        Self("unknown error".to_string())
+        Self::generic("unknown error".to_string())
     }
 
     /// Generic error
     pub fn generic(err: String) -> Self {
-        Self(err)
+        Self {
+            inner: Box::new(ErrorImpl {
+                kind: ErrorKind::Other,
+                message: err,
+                path: vec![],
+                position: None,
+            }),
+        }
     }
 
     /// Not expected visit
-    pub fn not_expected(msg: &str) -> Self {
-        err!("not expected `{}`", msg)
+    pub fn not_expected(msg: &'static str) -> Self {
+        err!("not expected `{}`", msg).with_kind(ErrorKind::UnexpectedType { got: msg })
     }
 
     /// Was expecting something
     pub fn expecting(msg: &str) -> Self {
-        err!("expecting `{}`", msg)
+        err!("expecting `{}`", msg).with_kind(ErrorKind::Expecting(msg.to_string()))
     }
 
     /// Missing field inside a map
-    pub fn missing_field(field: &str) -> Self {
-        err!("missing field `{}`", field)
+    pub fn missing_field(field: &'static str) -> Self {
+        err!("missing field `{}`", field).with_kind(ErrorKind::MissingField(field))
+    }
+
+    /// Field not declared by the type being deserialized
+    pub fn unknown_field(field: &str) -> Self {
+        err!("unknown field `{}`", field).with_kind(ErrorKind::UnknownField(field.to_string()))
     }
 
     /// Missing element inside a sequence, most likely a tuple
     pub fn missing_element(index: usize) -> Self {
-        err!("missing tuple element {}", index)
+        err!("missing tuple element {}", index).with_kind(ErrorKind::MissingElement(index))
     }
 
     pub fn unknown_variant(variant: &str) -> Self {
         err!("unknown variant `{}`", variant)
+            .with_kind(ErrorKind::UnknownVariant(variant.to_string()))
+    }
+
+    /// None of the variants of an untagged enum could be deserialized
+    pub fn no_matching_variant(ty: &str) -> Self {
+        err!("data did not match any variant of untagged enum `{}`", ty)
+            .with_kind(ErrorKind::NoMatchingVariant(ty.to_string()))
     }
 
     // /// Invalid char
@@ -91,7 +201,7 @@ impl Error {
 
     /// Found an invalid UTF8 sequence
     pub fn invalid_utf8() -> Self {
-        Self("invalid utf8".to_string())
+        Self::generic("invalid utf8".to_string()).with_kind(ErrorKind::InvalidUtf8)
     }
 
     /// When there isn't enough alignment needed to decode and align a hex sequence
@@ -101,6 +211,7 @@ impl Error {
             needed,
             current
         )
+        .with_kind(ErrorKind::NotEnoughOffset { needed, current })
     }
 
     /// Document specifies the need for a higher rank alignment
@@ -112,6 +223,7 @@ impl Error {
             needed,
             current
         )
+        .with_kind(ErrorKind::LowerAlignmentRank { needed, current })
     }
 
     /// Data isn't aligned;
@@ -124,30 +236,49 @@ impl Error {
             align,
             offset
         )
+        .with_kind(ErrorKind::NotAligned { align, offset })
     }
 
     /// Invalid map key, probably mean that a key couldn't be created
     /// using the `FromStr` trait using this input
-    pub fn invalid_map_key(mut key: String) -> Self {
-        key.insert_str(0, "invalid map key ");
-        Self(key)
+    pub fn invalid_map_key(key: String) -> Self {
+        err!("invalid map key {}", &key[..]).with_kind(ErrorKind::InvalidMapKey(key))
+    }
+
+    /// Input nested deeper than the deserializer allows
+    pub fn depth_exceeded(max_depth: usize) -> Self {
+        err!("maximum nesting depth of {} exceeded", max_depth)
+            .with_kind(ErrorKind::DepthExceeded(max_depth))
+    }
+
+    /// `NaN` or infinite float that the format can't represent
+    pub fn non_finite_float() -> Self {
+        Self::generic("non-finite float can't be serialized".to_string())
+            .with_kind(ErrorKind::NonFiniteFloat)
     }
 
     /// Out of range of some type
-    pub fn out_of_range(ty: &str) -> Self {
-        err!("out of range of `{}`", ty)
+    pub fn out_of_range(ty: &'static str) -> Self {
+        err!("out of range of `{}`", ty).with_kind(ErrorKind::OutOfRange(ty))
     }
 }
 
 impl Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.0)
+        Display::fmt(self, f)
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.0)
+        if !self.inner.path.is_empty() {
+            f.write_str("$")?;
+            for segment in self.inner.path.iter().rev() {
+                f.write_str(segment)?;
+            }
+            f.write_str(": ")?;
+        }
+        f.write_str(&self.inner.message)
     }
 }
 
@@ -1,8 +1,11 @@
 pub use std::borrow::Cow;
 pub use std::boxed::Box;
+pub use std::convert::{From, TryFrom};
+pub use std::default::Default;
+pub use std::marker::Copy;
 pub use std::option::Option::{self, None, Some};
 pub use std::result::Result::{Err, Ok};
-pub use std::string::String;
+pub use std::string::{String, ToString};
 
 pub use self::help::Str as str;
 pub use self::help::Usize as usize;
@@ -13,6 +16,7 @@ mod help {
 }
 
 /// Context hint to help decide which `Entity` or `Asset` return
+#[derive(Clone, Copy)]
 pub enum Hint<'a> {
     /// Null or default asset or entity
     Null,
@@ -25,6 +29,7 @@ pub enum Hint<'a> {
 }
 
 /// Implementation independent asset handle
+#[derive(Clone)]
 pub enum AssetHandle<T> {
     Atomic(std::sync::Arc<T>),
     RefCounted(std::rc::Rc<T>),
@@ -32,13 +37,47 @@ pub enum AssetHandle<T> {
 }
 
 /// Asset handle with type information
+#[derive(Clone)]
 pub struct Asset {
     pub handle: AssetHandle<u32>,
     pub id: (std::any::TypeId, u32),
 }
 
 /// Entity type, should be compatible with most ecs crates
+#[derive(Clone, Copy)]
 pub struct Entity(pub u64);
 
 /// Hex conversion utility
 pub use bintext::hex;
+
+/// Base64 configs of `#[toctoc(base64)]` fields
+#[cfg(feature = "base64")]
+pub use base64;
+
+/// Expands the (de)serialization of a `#[toctoc(base64)]` field, used by the
+/// derive macros so a missing `base64` feature is reported by name.
+///
+/// Not public API.
+#[cfg(feature = "base64")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __base64_field {
+    (serialize, $value:expr, $config:ident) => {
+        &$crate::ser::SerializeBase64 {
+            value: $value,
+            config: $crate::export::base64::$config,
+        }
+    };
+    (deserialize) => {
+        $crate::de::deserialize_base64
+    };
+}
+
+#[cfg(not(feature = "base64"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __base64_field {
+    ($($_:tt)*) => {
+        compile_error!("`#[toctoc(base64)]` requires the `base64` feature of toctoc")
+    };
+}
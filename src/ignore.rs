@@ -34,6 +34,14 @@ impl<'de> Visitor<'de> for Ignore {
         Ok(())
     }
 
+    fn int128(&mut self, _n: i128, _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn uint128(&mut self, _n: u128, _c: &mut dyn Context) -> Result<()> {
+        Ok(())
+    }
+
     fn single(&mut self, _n: f32) -> Result<()> {
         Ok(())
     }
@@ -43,14 +51,11 @@ impl<'de> Visitor<'de> for Ignore {
     }
 
     fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> Result<()> {
-        while s.visit(&mut Ignore, c)? {}
-        Ok(())
+        // Let the format skip nested levels without recursion
+        s.ignore_remaining(c)
     }
 
     fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
-        while let Some(_) = m.next()? {
-            m.visit(&mut Ignore, c)?
-        }
-        Ok(())
+        m.ignore_remaining(c)
     }
 }
@@ -1,6 +1,6 @@
 use std::iter::FromIterator;
 use std::mem::ManuallyDrop;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Index, IndexMut};
 use std::ptr;
 
 use crate::json::{drop, Value};
@@ -26,6 +26,43 @@ impl<'de> Array<'de> {
     pub fn new() -> Self {
         Array { inner: Vec::new() }
     }
+
+    /// Empty array with space for at least `capacity` values
+    pub fn with_capacity(capacity: usize) -> Self {
+        Array {
+            inner: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Reserves space for at least `additional` more values
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    /// Appends a value to the back of the array.
+    pub fn push(&mut self, v: Value<'de>) {
+        self.inner.push(v);
+    }
+
+    /// Removes the last value, it's the caller responsibility to drop
+    /// it safely if deeply nested.
+    pub fn pop(&mut self) -> Option<Value<'de>> {
+        self.inner.pop()
+    }
+}
+
+impl<'de> Index<usize> for Array<'de> {
+    type Output = Value<'de>;
+
+    fn index(&self, i: usize) -> &Value<'de> {
+        &self.inner[i]
+    }
+}
+
+impl<'de> IndexMut<usize> for Array<'de> {
+    fn index_mut(&mut self, i: usize) -> &mut Value<'de> {
+        &mut self.inner[i]
+    }
 }
 
 impl<'de> Deref for Array<'de> {
@@ -1,11 +1,13 @@
 use std::char;
+use std::marker::PhantomData;
 use std::mem;
+use std::ptr;
+use std::slice;
 use std::str;
 
-use self::Event::*;
-use crate::de::{Context, Deserialize, Map, Seq, Visitor};
+use crate::de::{Context, Deserialize, DeserializerTrait, Map, Seq, Visitor};
 use crate::error::{Error, Result};
-use crate::json::HEX_HINT;
+use crate::json::JsonDeOptions;
 
 /// Deserialize a JSON string into any deserializable type.
 ///
@@ -19,198 +21,218 @@ use crate::json::HEX_HINT;
 /// }
 ///
 /// fn main() -> toctoc::Result<()> {
-///     let j = r#" {"code": 200, "message": "reminiscent of Serde"} "#;
+///     let mut j = r#" {"code": 200, "message": "reminiscent of Serde"} "#.to_string();
 ///
-///     let out: Example = json::from_str(&j, &mut ())?;
+///     let out: Example = json::from_str(&mut j, &mut ())?;
 ///     println!("{:?}", out);
 ///
 ///     Ok(())
 /// }
 /// ```
-pub fn from_str<'de, T: Deserialize<'de>>(j: &'de mut str, ctx: &mut dyn Context) -> Result<T> {
+pub fn from_str<'de, T: Deserialize<'de>>(json: &'de mut str, ctx: &mut dyn Context) -> Result<T> {
+    let mut out = None;
+    let mut de = JsonDe::new(json)?;
+    de.deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
+/// Same as `from_str` but with custom `options`, `from_str` uses
+/// `JsonDeOptions::default()`.
+///
+/// ```rust
+/// use toctoc::json::{self, JsonDeOptions, Value};
+///
+/// let options = JsonDeOptions { max_depth: 2 };
+///
+/// let mut j = "[[1]]".to_string();
+/// let out: toctoc::Result<Value> = json::from_str_with_options(&mut j, &mut (), options);
+/// assert!(out.is_ok());
+///
+/// let mut j = "[[[1]]]".to_string();
+/// let out: toctoc::Result<Value> = json::from_str_with_options(&mut j, &mut (), options);
+/// assert!(out.is_err());
+/// ```
+pub fn from_str_with_options<'de, T: Deserialize<'de>>(
+    json: &'de mut str,
+    ctx: &mut dyn Context,
+    options: JsonDeOptions,
+) -> Result<T> {
     let mut out = None;
-    from_str_impl(j, T::begin(&mut out), ctx)?;
-    out.ok_or(Error)
+    let mut de = JsonDe::new(json)?;
+    de.max_depth = options.max_depth;
+    de.deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
+/// Deserialize JSON bytes into any deserializable type, `json` must be valid UTF-8.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let mut j = br#"["a","b"]"#.to_vec();
+/// let out: Vec<&str> = json::from_slice(&mut j, &mut ()).unwrap();
+/// assert_eq!(out, vec!["a", "b"]);
+/// ```
+pub fn from_slice<'de, T: Deserialize<'de>>(
+    json: &'de mut [u8],
+    ctx: &mut dyn Context,
+) -> Result<T> {
+    let json = std::str::from_utf8_mut(json).map_err(|_| Error::invalid_utf8())?;
+    from_str(json, ctx)
 }
 
-struct Deserializer<'a, 'b> {
-    input: &'a [u8],
+/// Non SIMD JSON deserializer.
+///
+/// Strings are unescaped in place, the unescaped form is never longer than
+/// the escaped one, so every `&'de str` handed to the visitors is a slice
+/// of the original input, no matter if it had escape sequences or not.
+pub struct JsonDe<'de> {
+    ptr: *mut u8,
+    len: usize,
     pos: usize,
-    buffer: Vec<u8>,
-    stack: Vec<(&'b mut dyn Visitor, Layer<'b>)>,
+    /// Maximum number of nested arrays and objects allowed
+    max_depth: usize,
+    /// Current number of nested arrays and objects
+    depth: usize,
+    /// Number of new lines found so far, kept up to date because unescaped
+    /// strings may leave new lines behind in the input
+    line: usize,
+    /// Where the current line starts
+    line_start: usize,
+    marker: PhantomData<&'de mut str>,
+}
+
+enum Event {
+    Negative(i64),
+    Nonnegative(u64),
+    Float(f64),
 }
 
-enum Layer<'a> {
-    Seq(Box<dyn Seq + 'a>),
-    Map(Box<dyn Map + 'a>),
+use self::Event::*;
+
+macro_rules! overflow {
+    ($a:ident * 10 + $b:ident, $c:expr) => {
+        $a >= $c / 10 && ($a > $c / 10 || $b > $c % 10)
+    };
 }
 
-impl<'a, 'b> Drop for Deserializer<'a, 'b> {
-    fn drop(&mut self) {
-        // Drop layers in reverse order.
-        while !self.stack.is_empty() {
-            self.stack.pop();
+impl<'de> JsonDe<'de> {
+    /// Never fails, returns a `Result` to keep the same signature of the SIMD deserializer
+    pub fn new(json: &'de mut str) -> Result<Self> {
+        let bytes = unsafe { json.as_bytes_mut() };
+        Ok(Self {
+            ptr: bytes.as_mut_ptr(),
+            len: bytes.len(),
+            pos: 0,
+            max_depth: JsonDeOptions::default().max_depth,
+            depth: 0,
+            line: 0,
+            line_start: 0,
+            marker: PhantomData,
+        })
+    }
+
+    /// Keeps track of the nesting depth to avoid overflowing the stack
+    #[inline(always)]
+    fn enter_nested(&mut self) -> Result<()> {
+        if self.depth >= self.max_depth {
+            Err(Error::depth_exceeded(self.max_depth))?
         }
+        self.depth += 1;
+        Ok(())
     }
-}
 
-fn from_str_impl<'de>(
-    j: &'de mut str,
-    mut visitor: &mut dyn Visitor<'de>,
-    context: &mut dyn Context,
-) -> Result<()> {
-    let mut de = Deserializer {
-        input: j.as_bytes(),
-        pos: 0,
-        buffer: Vec::new(),
-        stack: Vec::new(),
-    };
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        let peek = match self.parse_whitespace() {
+            Some(b) => b,
+            None => Err(Error::expecting("value"))?,
+        };
+        self.bump();
+        match peek {
+            b'"' => {
+                let s = self.parse_str()?;
+                // ! FIXME Not good for all occasions
+                if s.starts_with('#') {
+                    let mut a = 0;
+                    for ch in s.as_bytes().iter().skip(1) {
+                        if *ch != b'-' {
+                            break;
+                        }
+                        a += 1;
+                    }
 
-    'outer: loop {
-        let layer = match de.event()? {
-            Null => {
-                visitor.null(context)?;
-                None
-            }
-            Bool(b) => {
-                visitor.boolean(b)?;
-                None
-            }
-            Negative(n) => {
-                visitor.negative(n, context)?;
-                None
-            }
-            Nonnegative(n) => {
-                visitor.nonnegative(n, context)?;
-                None
-            }
-            Float(n) => {
-                visitor.double(n)?;
-                None
-            }
-            Str(s) => {
-                if s.chars().last() == Some(HEX_HINT) {
-                    let c = s.len() - 1;
-                    let b = bintext::hex::decode(&s[..c]).map_err(|_| Error)?;
-                    // TODO: Copy the bytes into the string
-                    //visitor.bytes(b.as_slice(), context)?;
-                    todo!()
+                    let b = unsafe {
+                        let s = mem::transmute(s);
+                        bintext::hex::decode_aligned(s, a + 1, a.max(1))
+                            .map_err(|err| Error::generic(err.to_string()))?
+                    };
+
+                    v.bytes(b, c)?;
                 } else {
-                    // TODO Scape bytes inside the input string
-                    //visitor.string(s, context)?;
-                    todo!()
+                    v.string(s, c)?;
                 }
-                None
             }
-            SeqStart => {
-                let seq = careful!(visitor.seq(context)? as Box<dyn Seq>);
-                Some(Layer::Seq(seq))
+            digit @ b'0'..=b'9' => self.visit_number(true, digit, v, c)?,
+            b'-' => {
+                let first_digit = self.next_or_nul();
+                self.visit_number(false, first_digit, v, c)?;
+            }
+            b'[' => {
+                self.enter_nested()?;
+                let mut stack = Stack::new(self);
+                v.seq(&mut stack, c)?;
+                // No matter what consume the entire array
+                Seq::ignore_remaining(&mut stack, c)?;
+                self.depth -= 1;
+            }
+            b'{' => {
+                self.enter_nested()?;
+                let mut stack = Stack::new(self);
+                v.map(&mut stack, c)?;
+                // No matter what consume the entire object
+                Map::ignore_remaining(&mut stack, c)?;
+                self.depth -= 1;
             }
-            MapStart => {
-                let map = careful!(visitor.map(context)? as Box<dyn Map>);
-                Some(Layer::Map(map))
+            b'n' => {
+                self.parse_ident(b"ull")?;
+                v.null(c)?;
             }
-        };
-
-        let mut accept_comma;
-        let mut layer = match layer {
-            Some(layer) => {
-                accept_comma = false;
-                layer
+            b't' => {
+                self.parse_ident(b"rue")?;
+                v.boolean(true)?;
             }
-            None => match de.stack.pop() {
-                Some(frame) => {
-                    accept_comma = true;
-                    visitor = frame.0;
-                    frame.1
-                }
-                None => break 'outer,
-            },
-        };
-
-        loop {
-            match de.parse_whitespace().unwrap_or(b'\0') {
-                b',' if accept_comma => {
-                    de.bump();
-                    break;
-                }
-                close @ b']' | close @ b'}' => {
-                    de.bump();
-                    match &mut layer {
-                        Layer::Seq(seq) if close == b']' => seq.finish()?,
-                        Layer::Map(map) if close == b'}' => map.finish()?,
-                        _ => return Err(Error),
-                    };
-                    let frame = match de.stack.pop() {
-                        Some(frame) => frame,
-                        None => break 'outer,
-                    };
-                    accept_comma = true;
-                    visitor = frame.0;
-                    layer = frame.1;
-                }
-                _ => {
-                    if accept_comma {
-                        return Err(Error);
-                    } else {
-                        break;
-                    }
-                }
+            b'f' => {
+                self.parse_ident(b"alse")?;
+                v.boolean(false)?;
             }
+            _ => Err(Error::expecting("value"))?,
         }
+        Ok(())
+    }
 
-        match layer {
-            Layer::Seq(mut seq) => {
-                let inner = careful!(seq.element()? as &mut dyn Visitor);
-                let outer = mem::replace(&mut visitor, inner);
-                de.stack.push((outer, Layer::Seq(seq)));
-            }
-            Layer::Map(mut map) => {
-                match de.parse_whitespace() {
-                    Some(b'"') => de.bump(),
-                    _ => return Err(Error),
-                }
-                let inner = {
-                    let k = de.parse_str()?;
-                    careful!(map.key(k)? as &mut dyn Visitor)
-                };
-                match de.parse_whitespace() {
-                    Some(b':') => de.bump(),
-                    _ => return Err(Error),
-                }
-                let outer = mem::replace(&mut visitor, inner);
-                de.stack.push((outer, Layer::Map(map)));
-            }
+    fn visit_number(
+        &mut self,
+        nonnegative: bool,
+        first_digit: u8,
+        v: &mut dyn Visitor<'de>,
+        c: &mut dyn Context,
+    ) -> Result<()> {
+        match self.parse_integer(nonnegative, first_digit)? {
+            Negative(n) => v.negative(n, c),
+            Nonnegative(n) => v.nonnegative(n, c),
+            Float(n) => v.double(n),
         }
     }
 
-    match de.parse_whitespace() {
-        Some(_) => Err(Error),
-        None => Ok(()),
+    #[inline(always)]
+    fn at(&self, i: usize) -> u8 {
+        debug_assert!(i < self.len);
+        unsafe { *self.ptr.add(i) }
     }
-}
 
-enum Event<'a> {
-    Null,
-    Bool(bool),
-    Str(&'a str),
-    Negative(i64),
-    Nonnegative(u64),
-    Float(f64),
-    SeqStart,
-    MapStart,
-}
-
-macro_rules! overflow {
-    ($a:ident * 10 + $b:ident, $c:expr) => {
-        $a >= $c / 10 && ($a > $c / 10 || $b > $c % 10)
-    };
-}
-
-impl<'a, 'b> Deserializer<'a, 'b> {
     fn next(&mut self) -> Option<u8> {
-        if self.pos < self.input.len() {
-            let ch = self.input[self.pos];
+        if self.pos < self.len {
+            let ch = self.at(self.pos);
             self.pos += 1;
             Some(ch)
         } else {
@@ -223,8 +245,8 @@ impl<'a, 'b> Deserializer<'a, 'b> {
     }
 
     fn peek(&mut self) -> Option<u8> {
-        if self.pos < self.input.len() {
-            Some(self.input[self.pos])
+        if self.pos < self.len {
+            Some(self.at(self.pos))
         } else {
             None
         }
@@ -238,118 +260,143 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         self.pos += 1;
     }
 
-    fn parse_str(&mut self) -> Result<&str> {
-        fn result(bytes: &[u8]) -> &str {
-            // The input is assumed to be valid UTF-8 and the \u-escapes are
-            // checked along the way, so don't need to check here.
-            unsafe { str::from_utf8_unchecked(bytes) }
+    /// Consumes the `peek`ed character before failing, so the
+    /// error position points at it
+    #[cold]
+    fn bump_unexpected(&mut self, peek: Option<u8>) {
+        if peek.is_some() {
+            self.bump();
         }
+    }
 
-        // Index of the first byte not yet copied into the scratch space.
-        let mut start = self.pos;
-        self.buffer.clear();
+    /// Parses a string and unescapes it in place. Assumes the previous byte
+    /// read was a quote.
+    fn parse_str(&mut self) -> Result<&'de mut str> {
+        let start = self.pos;
+        // Where the next unescaped byte will be written, always behind `pos`
+        let mut write = self.pos;
+        // Start of the current run of bytes without escape sequences
+        let mut run = self.pos;
 
         loop {
-            while self.pos < self.input.len() && !ESCAPE[usize::from(self.input[self.pos])] {
-                self.pos += 1;
+            // Jump to the next quote, backslash or control character
+            let rest =
+                unsafe { slice::from_raw_parts(self.ptr.add(self.pos), self.len - self.pos) };
+            self.pos += find_escape(rest);
+            if self.pos == self.len {
+                self.fill_gap(write, run);
+                Err(Error::expecting("\""))?
             }
-            if self.pos == self.input.len() {
-                return Err(Error);
+
+            // Move the latest run to close the gap left by previous escapes
+            let n = self.pos - run;
+            if write != run {
+                unsafe { ptr::copy(self.ptr.add(run), self.ptr.add(write), n) };
             }
-            match self.input[self.pos] {
+            write += n;
+
+            match self.at(self.pos) {
                 b'"' => {
-                    if self.buffer.is_empty() {
-                        // Fast path: return a slice of the raw JSON without any
-                        // copying.
-                        let borrowed = &self.input[start..self.pos];
-                        self.pos += 1;
-                        return Ok(result(borrowed));
-                    } else {
-                        self.buffer.extend_from_slice(&self.input[start..self.pos]);
-                        self.pos += 1;
-                        return Ok(result(&self.buffer));
-                    }
+                    self.fill_gap(write, self.pos);
+                    self.pos += 1;
+                    // The input is valid UTF-8 and the \u-escapes are
+                    // checked along the way, so don't need to check here.
+                    return Ok(unsafe {
+                        let bytes = slice::from_raw_parts_mut(self.ptr.add(start), write - start);
+                        str::from_utf8_unchecked_mut(bytes)
+                    });
                 }
                 b'\\' => {
-                    self.buffer.extend_from_slice(&self.input[start..self.pos]);
+                    let gap = self.pos;
                     self.pos += 1;
-                    self.parse_escape()?;
-                    start = self.pos;
+                    write = match self.parse_escape(write) {
+                        Ok(write) => write,
+                        Err(err) => {
+                            self.fill_gap(write, gap);
+                            return Err(err);
+                        }
+                    };
+                    run = self.pos;
                 }
                 _ => {
-                    return Err(Error);
+                    self.fill_gap(write, self.pos);
+                    self.pos += 1;
+                    Err(err!("control character in string"))?
                 }
             }
         }
     }
 
+    /// Overwrites the bytes left behind by the unescaping with spaces, they may
+    /// be part of a multi-byte character and the input must remain valid UTF-8
+    #[inline(always)]
+    fn fill_gap(&mut self, write: usize, end: usize) {
+        if write < end {
+            unsafe { ptr::write_bytes(self.ptr.add(write), b' ', end - write) };
+        }
+    }
+
     fn next_or_eof(&mut self) -> Result<u8> {
-        self.next().ok_or(Error)
+        match self.next() {
+            Some(ch) => Ok(ch),
+            None => Err(Error::expecting("\""))?,
+        }
     }
 
-    /// Parses a JSON escape sequence and appends it into the scratch space. Assumes
-    /// the previous byte read was a backslash.
-    fn parse_escape(&mut self) -> Result<()> {
+    /// Parses a JSON escape sequence writing the unescaped bytes at `write`.
+    /// Assumes the previous byte read was a backslash.
+    ///
+    /// Returns the new write position, no escape sequence is shorter than
+    /// its unescaped form, therefore it never goes past `pos`.
+    fn parse_escape(&mut self, write: usize) -> Result<usize> {
         let ch = self.next_or_eof()?;
 
-        match ch {
-            b'"' => self.buffer.push(b'"'),
-            b'\\' => self.buffer.push(b'\\'),
-            b'/' => self.buffer.push(b'/'),
-            b'b' => self.buffer.push(b'\x08'),
-            b'f' => self.buffer.push(b'\x0c'),
-            b'n' => self.buffer.push(b'\n'),
-            b'r' => self.buffer.push(b'\r'),
-            b't' => self.buffer.push(b'\t'),
-            b'u' => {
-                let c = match self.decode_hex_escape()? {
-                    0xDC00..=0xDFFF => {
-                        return Err(Error);
+        let c = match ch {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\x08',
+            b'f' => '\x0c',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => match self.decode_hex_escape()? {
+                0xDC00..=0xDFFF => Err(err!("lone leading surrogate in hex escape"))?,
+
+                // Non-BMP characters are encoded as a sequence of
+                // two hex escapes, representing UTF-16 surrogates.
+                n1 @ 0xD800..=0xDBFF => {
+                    if self.next_or_eof()? != b'\\' || self.next_or_eof()? != b'u' {
+                        Err(err!("unexpected end of hex escape"))?
                     }
 
-                    // Non-BMP characters are encoded as a sequence of
-                    // two hex escapes, representing UTF-16 surrogates.
-                    n1 @ 0xD800..=0xDBFF => {
-                        if self.next_or_eof()? != b'\\' {
-                            return Err(Error);
-                        }
-                        if self.next_or_eof()? != b'u' {
-                            return Err(Error);
-                        }
-
-                        let n2 = self.decode_hex_escape()?;
-
-                        if n2 < 0xDC00 || n2 > 0xDFFF {
-                            return Err(Error);
-                        }
+                    let n2 = self.decode_hex_escape()?;
 
-                        let n = (u32::from(n1 - 0xD800) << 10 | u32::from(n2 - 0xDC00)) + 0x1_0000;
-
-                        match char::from_u32(n) {
-                            Some(c) => c,
-                            None => {
-                                return Err(Error);
-                            }
-                        }
+                    if !(0xDC00..=0xDFFF).contains(&n2) {
+                        Err(err!("lone leading surrogate in hex escape"))?
                     }
 
-                    n => match char::from_u32(u32::from(n)) {
+                    let n = (u32::from(n1 - 0xD800) << 10 | u32::from(n2 - 0xDC00)) + 0x1_0000;
+
+                    match char::from_u32(n) {
                         Some(c) => c,
-                        None => {
-                            return Err(Error);
-                        }
-                    },
-                };
+                        None => Err(err!("invalid unicode code point"))?,
+                    }
+                }
 
-                self.buffer
-                    .extend_from_slice(c.encode_utf8(&mut [0_u8; 4]).as_bytes());
-            }
-            _ => {
-                return Err(Error);
-            }
-        }
+                n => match char::from_u32(u32::from(n)) {
+                    Some(c) => c,
+                    None => Err(err!("invalid unicode code point"))?,
+                },
+            },
+            _ => Err(err!("invalid escape"))?,
+        };
 
-        Ok(())
+        let mut buf = [0_u8; 4];
+        let bytes = c.encode_utf8(&mut buf).as_bytes();
+        debug_assert!(write + bytes.len() <= self.pos);
+        unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), self.ptr.add(write), bytes.len()) };
+        Ok(write + bytes.len())
     }
 
     fn decode_hex_escape(&mut self) -> Result<u16> {
@@ -363,20 +410,107 @@ impl<'a, 'b> Deserializer<'a, 'b> {
                 b'd' | b'D' => n * 16_u16 + 13_u16,
                 b'e' | b'E' => n * 16_u16 + 14_u16,
                 b'f' | b'F' => n * 16_u16 + 15_u16,
-                _ => {
-                    return Err(Error);
-                }
+                _ => Err(err!("invalid hex escape"))?,
             };
         }
         Ok(n)
     }
 
+    /// Consumes the separator before the next element of an array or object,
+    /// returns `false` when the `close` character was found instead
+    fn parse_separator(&mut self, close: u8, first: &mut bool) -> Result<bool> {
+        match self.parse_whitespace() {
+            Some(b) if b == close => {
+                self.bump();
+                Ok(false)
+            }
+            Some(b',') if !*first => {
+                self.bump();
+                Ok(true)
+            }
+            Some(_) if *first => {
+                *first = false;
+                Ok(true)
+            }
+            other => {
+                self.bump_unexpected(other);
+                Err(Error::expecting(if close == b']' {
+                    ", or ]"
+                } else {
+                    ", or }"
+                }))?
+            }
+        }
+    }
+
+    /// Parses an object field name followed by `:`
+    fn parse_key(&mut self) -> Result<&'de str> {
+        match self.parse_whitespace() {
+            Some(b'"') => self.bump(),
+            other => {
+                self.bump_unexpected(other);
+                Err(Error::expecting("field name"))?
+            }
+        }
+        let k = self.parse_str()?;
+        match self.parse_whitespace() {
+            Some(b':') => self.bump(),
+            other => {
+                self.bump_unexpected(other);
+                Err(Error::expecting(":"))?
+            }
+        }
+        Ok(k)
+    }
+
+    /// Skips the remaining of an array or object (and everything nested
+    /// inside them) without recursion, while still validating the input
+    fn skip_nested(&mut self, state: IgnoreState, c: &mut dyn Context) -> Result<()> {
+        let mut stack = vec![state];
+        while let Some(state) = stack.last_mut() {
+            let close = state.close;
+            if !self.parse_separator(close, &mut state.first)? {
+                stack.pop();
+                continue;
+            }
+
+            if close == b'}' {
+                self.parse_key()?;
+            }
+
+            match self.parse_whitespace() {
+                Some(b'[') => {
+                    self.bump();
+                    stack.push(IgnoreState {
+                        close: b']',
+                        first: true,
+                    });
+                }
+                Some(b'{') => {
+                    self.bump();
+                    stack.push(IgnoreState {
+                        close: b'}',
+                        first: true,
+                    });
+                }
+                // Not nested, so it won't recurse
+                _ => self.visit(<dyn Visitor>::ignore(), c)?,
+            }
+        }
+        Ok(())
+    }
+
     fn parse_whitespace(&mut self) -> Option<u8> {
         loop {
             match self.peek() {
-                Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') => {
+                Some(b' ') | Some(b'\t') | Some(b'\r') => {
                     self.bump();
                 }
+                Some(b'\n') => {
+                    self.bump();
+                    self.line += 1;
+                    self.line_start = self.pos;
+                }
                 other => {
                     return other;
                 }
@@ -387,14 +521,8 @@ impl<'a, 'b> Deserializer<'a, 'b> {
     fn parse_ident(&mut self, ident: &[u8]) -> Result<()> {
         for expected in ident {
             match self.next() {
-                None => {
-                    return Err(Error);
-                }
-                Some(next) => {
-                    if next != *expected {
-                        return Err(Error);
-                    }
-                }
+                Some(next) if next == *expected => {}
+                _ => Err(Error::expecting("value"))?,
             }
         }
         Ok(())
@@ -405,7 +533,7 @@ impl<'a, 'b> Deserializer<'a, 'b> {
             b'0' => {
                 // There can be only one leading '0'.
                 match self.peek_or_nul() {
-                    b'0'..=b'9' => Err(Error),
+                    b'0'..=b'9' => Err(err!("invalid number"))?,
                     _ => self.parse_number(nonnegative, 0),
                 }
             }
@@ -439,7 +567,7 @@ impl<'a, 'b> Deserializer<'a, 'b> {
                     }
                 }
             }
-            _ => Err(Error),
+            _ => Err(err!("invalid number"))?,
         }
     }
 
@@ -480,8 +608,8 @@ impl<'a, 'b> Deserializer<'a, 'b> {
                 } else {
                     let neg = (significand as i64).wrapping_neg();
 
-                    // Convert into a float if we underflow.
-                    if neg > 0 {
+                    // Convert into a float if we underflow, or on `-0`.
+                    if neg >= 0 {
                         Float(-(significand as f64))
                     } else {
                         Negative(neg)
@@ -519,7 +647,7 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         }
 
         if !at_least_one_digit {
-            return Err(Error);
+            Err(err!("invalid number"))?
         }
 
         match self.peek_or_nul() {
@@ -551,9 +679,7 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         // Make sure a digit follows the exponent place.
         let mut exp = match self.next_or_nul() {
             c @ b'0'..=b'9' => i32::from(c - b'0'),
-            _ => {
-                return Err(Error);
-            }
+            _ => Err(err!("invalid number"))?,
         };
 
         while let c @ b'0'..=b'9' = self.peek_or_nul() {
@@ -588,7 +714,7 @@ impl<'a, 'b> Deserializer<'a, 'b> {
     ) -> Result<f64> {
         // Error instead of +/- infinity.
         if significand != 0 && positive_exp {
-            return Err(Error);
+            Err(Error::out_of_range("f64"))?
         }
 
         while let b'0'..=b'9' = self.peek_or_nul() {
@@ -596,37 +722,146 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         }
         Ok(if nonnegative { 0.0 } else { -0.0 })
     }
+}
 
-    fn event(&mut self) -> Result<Event> {
-        let peek = match self.parse_whitespace() {
-            Some(b) => b,
-            None => return Err(Error),
-        };
-        self.bump();
-        match peek {
-            b'"' => self.parse_str().map(Str),
-            digit @ b'0'..=b'9' => self.parse_integer(true, digit),
-            b'-' => {
-                let first_digit = self.next_or_nul();
-                self.parse_integer(false, first_digit)
-            }
-            b'{' => Ok(MapStart),
-            b'[' => Ok(SeqStart),
-            b'n' => {
-                self.parse_ident(b"ull")?;
-                Ok(Null)
-            }
-            b't' => {
-                self.parse_ident(b"rue")?;
-                Ok(Bool(true))
-            }
-            b'f' => {
-                self.parse_ident(b"alse")?;
-                Ok(Bool(false))
-            }
-            _ => Err(Error),
+struct Stack<'a, 'de: 'a> {
+    de: &'a mut JsonDe<'de>,
+    first: bool,
+    done: bool,
+    /// Last key or element index visited, used to annotate errors
+    key: &'de str,
+    count: usize,
+}
+
+/// Nested level being skipped by `JsonDe::skip_nested`
+struct IgnoreState {
+    close: u8,
+    first: bool,
+}
+
+impl<'a, 'de: 'a> Stack<'a, 'de> {
+    fn new(de: &'a mut JsonDe<'de>) -> Self {
+        Self {
+            de,
+            first: true,
+            done: false,
+            key: "",
+            count: 0,
+        }
+    }
+
+    /// Consumes the separator before the next element, returns `false`
+    /// when the closing character was found instead
+    fn separator(&mut self, close: u8) -> Result<bool> {
+        if self.done {
+            return Ok(false);
+        }
+        if self.de.parse_separator(close, &mut self.first)? {
+            Ok(true)
+        } else {
+            self.done = true;
+            Ok(false)
         }
     }
+
+    /// Skips everything until the closing character
+    fn ignore_remaining(&mut self, close: u8, c: &mut dyn Context) -> Result<()> {
+        if self.done {
+            return Ok(());
+        }
+        self.done = true;
+        self.de.skip_nested(
+            IgnoreState {
+                close,
+                first: self.first,
+            },
+            c,
+        )
+    }
+}
+
+impl<'a, 'de: 'a> Seq<'de> for Stack<'a, 'de> {
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<bool> {
+        if self.separator(b']')? {
+            let index = self.count;
+            self.count += 1;
+            self.de
+                .visit(v, c)
+                .map_err(|e| e.append_path_index(index))?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn ignore_remaining(&mut self, c: &mut dyn Context) -> Result<()> {
+        Stack::ignore_remaining(self, b']', c)
+    }
+}
+
+impl<'a, 'de: 'a> Map<'de> for Stack<'a, 'de> {
+    fn next(&mut self) -> Result<Option<&'de str>> {
+        if !self.separator(b'}')? {
+            return Ok(None);
+        }
+        self.key = self.de.parse_key()?;
+        Ok(Some(self.key))
+    }
+
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        let key = self.key;
+        self.de.visit(v, c).map_err(|e| e.append_path_segment(key))
+    }
+
+    fn ignore_remaining(&mut self, c: &mut dyn Context) -> Result<()> {
+        Stack::ignore_remaining(self, b'}', c)
+    }
+}
+
+impl<'de> DeserializerTrait<'de> for JsonDe<'de> {
+    fn deserialize(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        self.visit(v, c)
+            .and_then(|_| match self.parse_whitespace() {
+                Some(_) => {
+                    self.bump();
+                    Err(err!("trailing characters"))?
+                }
+                None => Ok(()),
+            })
+            .map_err(|e| e.append_line_and_column(self.line + 1, self.pos - self.line_start))
+    }
+}
+
+/// Index of the first quote, backslash or control character of `s` (or its
+/// length), checking 8 bytes at a time in a single pass
+fn find_escape(s: &[u8]) -> usize {
+    const ONES: u64 = 0x0101_0101_0101_0101;
+    const HIGH: u64 = 0x8080_8080_8080_8080;
+
+    let mut i = 0;
+    while i + 8 <= s.len() {
+        let mut chunk = [0; 8];
+        chunk.copy_from_slice(&s[i..i + 8]);
+        let x = u64::from_le_bytes(chunk);
+
+        // High bit set on the bytes that are zero after the xor or below 0x20,
+        // the lowest one is always a match (later bytes may be false positives)
+        let quote = x ^ (ONES * u64::from(b'"'));
+        let backslash = x ^ (ONES * u64::from(b'\\'));
+        let found = (quote.wrapping_sub(ONES) & !quote
+            | backslash.wrapping_sub(ONES) & !backslash
+            | x.wrapping_sub(ONES * 0x20) & !x)
+            & HIGH;
+        if found != 0 {
+            return i + (found.trailing_zeros() / 8) as usize;
+        }
+        i += 8;
+    }
+
+    s[i..]
+        .iter()
+        .position(|&b| b == b'"' || b == b'\\' || b < 0x20)
+        .map_or(s.len(), |n| i + n)
 }
 
 fn f64_from_parts(nonnegative: bool, significand: u64, mut exponent: i32) -> Result<f64> {
@@ -637,7 +872,7 @@ fn f64_from_parts(nonnegative: bool, significand: u64, mut exponent: i32) -> Res
                 if exponent >= 0 {
                     f *= pow;
                     if f.is_infinite() {
-                        return Err(Error);
+                        Err(Error::out_of_range("f64"))?
                     }
                 } else {
                     f /= pow;
@@ -649,7 +884,7 @@ fn f64_from_parts(nonnegative: bool, significand: u64, mut exponent: i32) -> Res
                     break;
                 }
                 if exponent >= 0 {
-                    return Err(Error);
+                    Err(Error::out_of_range("f64"))?
                 }
                 f /= 1e308;
                 exponent += 308;
@@ -694,31 +929,3 @@ static POW10: [f64; 309] = [
     1e290, 1e291, 1e292, 1e293, 1e294, 1e295, 1e296, 1e297, 1e298, 1e299, //
     1e300, 1e301, 1e302, 1e303, 1e304, 1e305, 1e306, 1e307, 1e308,
 ];
-
-const CT: bool = true; // control character \x00..=\x1F
-const QU: bool = true; // quote \x22
-const BS: bool = true; // backslash \x5C
-const O: bool = false; // allow unescaped
-
-// Lookup table of bytes that must be escaped. A value of true at index i means
-// that byte i requires an escape sequence in the input.
-#[rustfmt::skip]
-static ESCAPE: [bool; 256] = [
-    //   1   2   3   4   5   6   7   8   9   A   B   C   D   E   F
-    CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, // 0
-    CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, CT, // 1
-     O,  O, QU,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // 2
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // 3
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // 4
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, BS,  O,  O,  O, // 5
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // 6
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // 7
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // 8
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // 9
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // A
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // B
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // C
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // D
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // E
-     O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O,  O, // F
-];
@@ -0,0 +1,124 @@
+use crate::json::{Number, Value};
+
+/// Builds a [`json::Value`](json/enum.Value.html) from JSON-like syntax.
+///
+/// ```rust
+/// use toctoc::json;
+/// use toctoc::json::Value;
+///
+/// let code = 200;
+/// let value = json!({
+///     "code": code,
+///     "message": "Ok",
+///     "tags": ["a", null, true, -1.5],
+///     "nested": { "empty": [], (String::from("dynamic") + "_key"): {} },
+/// });
+///
+/// let mut j = r#"{
+///     "code": 200,
+///     "message": "Ok",
+///     "tags": ["a", null, true, -1.5],
+///     "nested": { "empty": [], "dynamic_key": {} }
+/// }"#.to_string();
+/// let expected: Value = json::from_str(&mut j, &mut ()).unwrap();
+/// assert_eq!(value, expected);
+///
+/// assert_eq!(json::to_string(&json!([1, "a", {}]), &mut ()), r#"[1,"a",{}]"#);
+/// ```
+///
+/// Values can be any Rust expression convertible into a `Value`, keys are
+/// string literals or parenthesized expressions. Non negative integers are
+/// stored as `Number::U64` just like the deserializer does, so the result
+/// compares equal to the parsed JSON.
+#[macro_export]
+macro_rules! json {
+    ($($json:tt)+) => {
+        $crate::json_internal!($($json)+)
+    };
+}
+
+// Not public API, munches one array element or object field at the time
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    (@array $array:ident ()) => {};
+
+    (@array $array:ident (null $(, $($rest:tt)*)?)) => {
+        $array.push($crate::json::Value::Null);
+        $crate::json_internal!(@array $array ($($($rest)*)?));
+    };
+
+    (@array $array:ident ([$($inner:tt)*] $(, $($rest:tt)*)?)) => {
+        $array.push($crate::json_internal!([$($inner)*]));
+        $crate::json_internal!(@array $array ($($($rest)*)?));
+    };
+
+    (@array $array:ident ({$($inner:tt)*} $(, $($rest:tt)*)?)) => {
+        $array.push($crate::json_internal!({$($inner)*}));
+        $crate::json_internal!(@array $array ($($($rest)*)?));
+    };
+
+    (@array $array:ident ($value:expr $(, $($rest:tt)*)?)) => {
+        $array.push($crate::json::__value($value));
+        $crate::json_internal!(@array $array ($($($rest)*)?));
+    };
+
+    (@object $object:ident ()) => {};
+
+    (@object $object:ident ($key:tt : null $(, $($rest:tt)*)?)) => {
+        $object.insert($crate::export::String::from($key), $crate::json::Value::Null);
+        $crate::json_internal!(@object $object ($($($rest)*)?));
+    };
+
+    (@object $object:ident ($key:tt : [$($inner:tt)*] $(, $($rest:tt)*)?)) => {
+        $object.insert($crate::export::String::from($key), $crate::json_internal!([$($inner)*]));
+        $crate::json_internal!(@object $object ($($($rest)*)?));
+    };
+
+    (@object $object:ident ($key:tt : {$($inner:tt)*} $(, $($rest:tt)*)?)) => {
+        $object.insert($crate::export::String::from($key), $crate::json_internal!({$($inner)*}));
+        $crate::json_internal!(@object $object ($($($rest)*)?));
+    };
+
+    (@object $object:ident ($key:tt : $value:expr $(, $($rest:tt)*)?)) => {
+        $object.insert($crate::export::String::from($key), $crate::json::__value($value));
+        $crate::json_internal!(@object $object ($($($rest)*)?));
+    };
+
+    (null) => {
+        $crate::json::Value::Null
+    };
+
+    ([]) => {
+        $crate::json::Value::Array($crate::json::Array::new())
+    };
+
+    ([$($tt:tt)+]) => {{
+        let mut array = $crate::json::Array::new();
+        $crate::json_internal!(@array array ($($tt)+));
+        $crate::json::Value::Array(array)
+    }};
+
+    ({}) => {
+        $crate::json::Value::Object($crate::json::Object::new())
+    };
+
+    ({$($tt:tt)+}) => {{
+        let mut object = $crate::json::Object::new();
+        $crate::json_internal!(@object object ($($tt)+));
+        $crate::json::Value::Object(object)
+    }};
+
+    ($other:expr) => {
+        $crate::json::__value($other)
+    };
+}
+
+/// Not public API, converts the `json!` values
+#[doc(hidden)]
+pub fn __value<'a, T: Into<Value<'a>>>(value: T) -> Value<'a> {
+    match value.into() {
+        Value::Number(Number::I64(n)) if n >= 0 => Value::Number(Number::U64(n as u64)),
+        value => value,
+    }
+}
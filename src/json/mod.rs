@@ -4,17 +4,25 @@
 //! serializing and deserializing JSON.
 
 mod ser;
-pub use self::ser::to_string;
+pub use self::ser::{
+    to_string, to_string_into, to_string_pretty, to_string_with_options, to_vec, to_vec_into,
+    to_writer,
+};
 pub use ser::JsonSer;
+pub(crate) use ser::{escape_str, to_fmt};
 
 pub use export::*;
 
+mod options;
+pub use self::options::{JsonDeOptions, JsonSerOptions, NonFiniteMode};
+
 #[cfg(not(feature = "simd"))]
 mod de;
 
 #[cfg(not(feature = "simd"))]
 mod export {
-    pub use super::de::from_str;
+    pub use super::de::JsonDe;
+    pub use super::de::{from_slice, from_str, from_str_with_options};
 }
 
 #[cfg(feature = "simd")]
@@ -22,12 +30,24 @@ mod simd;
 
 #[cfg(feature = "simd")]
 mod export {
-    pub use super::simd::from_str;
     pub use super::simd::JsonDe;
+    pub use super::simd::{from_slice, from_str, from_str_with_options};
 }
 
 mod value;
-pub use self::value::Value;
+pub use self::value::{
+    from_value, from_value_with_options, merge_patch, to_value, Value, ValueDeserializer,
+    ValueIndex, ValueSerializer,
+};
+
+mod schema;
+
+mod macros;
+#[doc(hidden)]
+pub use self::macros::__value;
+
+mod validate;
+pub use self::validate::validate;
 
 mod number;
 pub use self::number::Number;
@@ -42,3 +62,12 @@ mod drop;
 
 mod owned;
 pub use self::owned::{from_str_owned, Owned};
+
+mod preamble;
+pub use self::preamble::{from_str_with_preamble, PreambleContext};
+
+mod reader;
+pub use self::reader::from_reader;
+
+mod ndjson;
+pub use self::ndjson::{from_ndjson, to_ndjson, NdJson};
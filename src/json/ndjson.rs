@@ -0,0 +1,88 @@
+use std::marker::PhantomData;
+
+use crate::de::{self, Deserialize};
+use crate::error::Result;
+use crate::ser::{self, Serialize};
+
+/// Serialize every item of `iter` as [NDJSON](http://ndjson.org/),
+/// each one in its own line.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let j = json::to_ndjson(&[vec![1], vec![2, 3]], &mut ());
+/// assert_eq!(j, "[1]\n[2,3]\n");
+/// ```
+pub fn to_ndjson<I>(iter: I, ctx: &mut dyn ser::Context) -> String
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut out = String::new();
+    let mut line = String::new();
+    for value in iter {
+        super::to_string_into(&value, ctx, &mut line);
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Lazily deserialize each line of a [NDJSON](http://ndjson.org/) `input`,
+/// blank lines are skipped.
+///
+/// Every line is deserialized independently, so an invalid line yields
+/// an `Err` but the following lines can still be read.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let mut j = "1\n\nfoo\n3\n".to_string();
+/// let lines: Vec<toctoc::Result<u32>> = json::from_ndjson(&mut j, &mut ()).collect();
+/// assert_eq!(lines.len(), 3);
+/// assert!(lines[1].is_err());
+/// assert_eq!(lines[2].as_ref().unwrap(), &3);
+/// ```
+pub fn from_ndjson<'de, 'c, T: Deserialize<'de>>(
+    input: &'de mut str,
+    ctx: &'c mut dyn de::Context,
+) -> NdJson<'de, 'c, T> {
+    NdJson {
+        rest: input,
+        ctx,
+        marker: PhantomData,
+    }
+}
+
+/// Iterator over the lines of a NDJSON input, see [`from_ndjson`]
+pub struct NdJson<'de, 'c, T> {
+    rest: &'de mut str,
+    ctx: &'c mut dyn de::Context,
+    marker: PhantomData<T>,
+}
+
+impl<'de, 'c, T: Deserialize<'de>> Iterator for NdJson<'de, 'c, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = std::mem::take(&mut self.rest);
+            if rest.is_empty() {
+                return None;
+            }
+
+            let (line, rest) = match rest.find('\n') {
+                Some(i) => {
+                    let (line, rest) = rest.split_at_mut(i);
+                    (line, &mut rest[1..])
+                }
+                None => rest.split_at_mut(rest.len()),
+            };
+            self.rest = rest;
+
+            if !line.trim().is_empty() {
+                return Some(super::from_str(line, self.ctx));
+            }
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Sub};
+
 /// A JSON number represented by some Rust primitive.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Number {
@@ -6,3 +9,140 @@ pub enum Number {
     F32(f32), // * MOD: Single precision to avoid casting between f32 and f64
     F64(f64),
 }
+
+macro_rules! from_primitive {
+    ($($ty:ty => $variant:ident as $as:ty),*) => {
+        $(impl From<$ty> for Number {
+            fn from(n: $ty) -> Self {
+                Number::$variant(n as $as)
+            }
+        })*
+    };
+}
+
+// Smaller integers are widened
+from_primitive!(
+    u8 => U64 as u64,
+    u32 => U64 as u64,
+    u64 => U64 as u64,
+    i32 => I64 as i64,
+    i64 => I64 as i64,
+    f32 => F32 as f32,
+    f64 => F64 as f64
+);
+
+impl Number {
+    /// Gets any kind of number as `f64`, may lose precision.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Number::U64(n) => n as f64,
+            Number::I64(n) => n as f64,
+            Number::F32(n) => n as f64,
+            Number::F64(n) => n,
+        }
+    }
+
+    /// Gets the number as `i64`, `None` for floating point numbers or
+    /// numbers greater than `i64::MAX`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Number::U64(n) if n <= i64::max_value() as u64 => Some(n as i64),
+            Number::I64(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Any integer fits in a `i128`, `None` for floating point numbers
+    fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Number::U64(n) => Some(n as i128),
+            Number::I64(n) => Some(n as i128),
+            _ => None,
+        }
+    }
+
+    /// Integers are represented just like the deserializers do, `U64` when
+    /// nonnegative and `I64` otherwise, `F64` if they don't fit in either
+    fn from_i128(n: i128) -> Self {
+        if n >= 0 {
+            if n <= u64::max_value() as i128 {
+                return Number::U64(n as u64);
+            }
+        } else if n >= i64::min_value() as i128 {
+            return Number::I64(n as i64);
+        }
+        Number::F64(n as f64)
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Number::U64(_) => 0,
+            Number::I64(_) => 1,
+            Number::F32(_) => 2,
+            Number::F64(_) => 3,
+        }
+    }
+
+    /// Mixed operations are done with the widest float, integers become floats
+    /// when mixed with one; at least one of the operands must be a float
+    fn float_op(self, rhs: Self, op32: fn(f32, f32) -> f32, op64: fn(f64, f64) -> f64) -> Self {
+        match (&self, &rhs) {
+            (Number::F64(_), _) | (_, Number::F64(_)) => {
+                Number::F64(op64(self.as_f64(), rhs.as_f64()))
+            }
+            _ => Number::F32(op32(self.as_f64() as f32, rhs.as_f64() as f32)),
+        }
+    }
+}
+
+/// Integers are compared exactly, anything else as `f64`. Numbers with the same value
+/// but different representations (like `U64(1)` and `F64(1.0)`) aren't `==`, so they're
+/// ordered by representation: `U64 < I64 < F32 < F64`.
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let ord = match (self.as_i128(), other.as_i128()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => self.as_f64().partial_cmp(&other.as_f64())?,
+        };
+        Some(ord.then_with(|| self.rank().cmp(&other.rank())))
+    }
+}
+
+macro_rules! arithmetic {
+    ($($trait:ident, $method:ident, $checked:ident;)*) => {
+        $(impl $trait for Number {
+            type Output = Number;
+
+            /// Integer results are promoted to `F64` on overflow
+            fn $method(self, rhs: Number) -> Number {
+                match (self.as_i128(), rhs.as_i128()) {
+                    (Some(a), Some(b)) => match a.$checked(b) {
+                        Some(n) => Number::from_i128(n),
+                        None => Number::F64((a as f64).$method(b as f64)),
+                    },
+                    _ => self.float_op(rhs, |a, b| a.$method(b), |a, b| a.$method(b)),
+                }
+            }
+        })*
+    };
+}
+
+arithmetic!(
+    Add, add, checked_add;
+    Sub, sub, checked_sub;
+    Mul, mul, checked_mul;
+);
+
+impl Div for Number {
+    type Output = Number;
+
+    /// Integers are only kept when the division is exact, otherwise
+    /// (or when dividing by zero) the result is a `F64`
+    fn div(self, rhs: Number) -> Number {
+        match (self.as_i128(), rhs.as_i128()) {
+            (Some(a), Some(b)) if b != 0 && a % b == 0 => Number::from_i128(a / b),
+            (Some(a), Some(b)) => Number::F64(a as f64 / b as f64),
+            _ => self.float_op(rhs, |a, b| a / b, |a, b| a / b),
+        }
+    }
+}
@@ -1,40 +1,82 @@
+#[cfg(not(feature = "preserve-order"))]
 use std::collections::BTreeMap;
 use std::iter::FromIterator;
 use std::mem::{self, ManuallyDrop};
 use std::ops::{Deref, DerefMut};
 use std::ptr;
 
+#[cfg(feature = "preserve-order")]
+use indexmap::IndexMap;
+
 use crate::json::{drop, Value};
 
-/// A `BTreeMap<String, Value>` with a non-recursive drop impl.
+#[cfg(not(feature = "preserve-order"))]
+type Map<'de> = BTreeMap<String, Value<'de>>;
+
+#[cfg(feature = "preserve-order")]
+type Map<'de> = IndexMap<String, Value<'de>>;
+
+/// A `BTreeMap<String, Value>` with a non-recursive drop impl, keys are sorted.
+///
+/// With the `preserve-order` feature it's an `IndexMap<String, Value>` instead,
+/// keeping the keys in insertion order, equality still doesn't depend on the
+/// order of the keys.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Object<'de> {
-    inner: BTreeMap<String, Value<'de>>,
+    inner: Map<'de>,
 }
 
 impl<'de> Drop for Object<'de> {
     fn drop(&mut self) {
-        for (_, child) in mem::replace(&mut self.inner, BTreeMap::new()) {
+        for (_, child) in mem::replace(&mut self.inner, Map::new()) {
             drop::safely(child);
         }
     }
 }
 
-fn take(object: Object) -> BTreeMap<String, Value> {
+/// Moves the inner map out, `ManuallyDrop` prevents the `Object` drop impl
+/// from running afterward and emptying it
+fn take_inner(object: Object) -> Map {
     let object = ManuallyDrop::new(object);
     unsafe { ptr::read(&object.inner) }
 }
 
 impl<'de> Object<'de> {
     pub fn new() -> Self {
+        Object { inner: Map::new() }
+    }
+
+    /// `BTreeMap` doesn't preallocate so `capacity` is ignored unless the
+    /// `preserve-order` feature is enabled
+    #[cfg(not(feature = "preserve-order"))]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let _ = capacity;
+        Self::new()
+    }
+
+    /// Empty object with space for at least `capacity` fields
+    #[cfg(feature = "preserve-order")]
+    pub fn with_capacity(capacity: usize) -> Self {
         Object {
-            inner: BTreeMap::new(),
+            inner: Map::with_capacity(capacity),
         }
     }
+
+    /// Removes a field
+    #[cfg(not(feature = "preserve-order"))]
+    pub fn remove(&mut self, key: &str) -> Option<Value<'de>> {
+        self.inner.remove(key)
+    }
+
+    /// Removes a field keeping the order of the remaining ones
+    #[cfg(feature = "preserve-order")]
+    pub fn remove(&mut self, key: &str) -> Option<Value<'de>> {
+        self.inner.shift_remove(key)
+    }
 }
 
 impl<'de> Deref for Object<'de> {
-    type Target = BTreeMap<String, Value<'de>>;
+    type Target = Map<'de>;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
@@ -49,16 +91,16 @@ impl<'de> DerefMut for Object<'de> {
 
 impl<'de> IntoIterator for Object<'de> {
     type Item = (String, Value<'de>);
-    type IntoIter = <BTreeMap<String, Value<'de>> as IntoIterator>::IntoIter;
+    type IntoIter = <Map<'de> as IntoIterator>::IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        take(self).into_iter()
+        take_inner(self).into_iter()
     }
 }
 
 impl<'a, 'de: 'a> IntoIterator for &'a Object<'de> {
     type Item = (&'a String, &'a Value<'de>);
-    type IntoIter = <&'a BTreeMap<String, Value<'de>> as IntoIterator>::IntoIter;
+    type IntoIter = <&'a Map<'de> as IntoIterator>::IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
@@ -67,7 +109,7 @@ impl<'a, 'de: 'a> IntoIterator for &'a Object<'de> {
 
 impl<'a, 'de: 'a> IntoIterator for &'a mut Object<'de> {
     type Item = (&'a String, &'a mut Value<'de>);
-    type IntoIter = <&'a mut BTreeMap<String, Value<'de>> as IntoIterator>::IntoIter;
+    type IntoIter = <&'a mut Map<'de> as IntoIterator>::IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
@@ -80,7 +122,7 @@ impl<'de> FromIterator<(String, Value<'de>)> for Object<'de> {
         I: IntoIterator<Item = (String, Value<'de>)>,
     {
         Object {
-            inner: BTreeMap::from_iter(iter),
+            inner: Map::from_iter(iter),
         }
     }
 }
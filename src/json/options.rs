@@ -0,0 +1,45 @@
+/// Options for `from_str_with_options`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonDeOptions {
+    /// Maximum number of nested arrays and objects, deeper inputs fail
+    /// with `Error::depth_exceeded` instead of overflowing the stack
+    pub max_depth: usize,
+}
+
+impl Default for JsonDeOptions {
+    fn default() -> Self {
+        Self { max_depth: 128 }
+    }
+}
+
+/// Options for `to_string_with_options`, the defaults match `to_string`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonSerOptions {
+    /// Pretty prints the output using `indent` for each nesting level,
+    /// see `to_string_pretty`
+    pub indent: Option<String>,
+    /// Writes the fields of every map sorted by key, no matter the order
+    /// they were serialized in
+    pub sort_keys: bool,
+    /// Writes every non ASCII char in strings and keys as `\uXXXX` escapes
+    pub escape_non_ascii: bool,
+    /// How `NaN` and infinite floats are written
+    pub non_finite: NonFiniteMode,
+}
+
+/// JSON has no representation for `NaN` and infinite floats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFiniteMode {
+    /// Writes `null`
+    Null,
+    /// Fails with `Error::non_finite_float`
+    Error,
+    /// Writes the strings `"NaN"`, `"Infinity"` and `"-Infinity"`
+    String,
+}
+
+impl Default for NonFiniteMode {
+    fn default() -> Self {
+        NonFiniteMode::Null
+    }
+}
@@ -6,6 +6,16 @@ use std::pin::Pin;
 
 pub type Owned<T> = OwnedRaw<String, T>;
 
+/// Same as `from_str` but takes ownership of the `data`, so `T` can borrow
+/// from it and still be moved around.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let j = r#"{"code":200}"#.to_string();
+/// let value = json::from_str_owned::<json::Value>(j, &mut ()).unwrap();
+/// assert_eq!(unsafe { value.as_ref() }["code"].as_u64(), Some(200));
+/// ```
 pub fn from_str_owned<'de, T: Deserialize<'de>>(
     mut data: String,
     ctx: &mut dyn Context,
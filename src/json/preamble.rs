@@ -0,0 +1,85 @@
+use crate::de::{Context, Deserialize, Deserializer, Map, Visitor};
+use crate::error::Result;
+use crate::json::{JsonDe, Value};
+
+/// Context that needs to load some data (like assets) before the
+/// main JSON body is deserialized
+pub trait PreambleContext: Context {
+    fn load_preamble(&mut self, v: &Value) -> Result<()>;
+}
+
+/// Deserialize a JSON string whose root object contains a `preamble_key` field,
+/// the field is loaded into the context before deserializing any other field.
+///
+/// ```rust
+/// use toctoc::json::{self, PreambleContext, Value};
+///
+/// #[derive(Default)]
+/// struct Registry {
+///     names: Vec<String>,
+/// }
+///
+/// impl toctoc::de::Context for Registry {}
+///
+/// impl PreambleContext for Registry {
+///     fn load_preamble(&mut self, v: &Value) -> toctoc::Result<()> {
+///         if let Value::Array(array) = v {
+///             for e in array {
+///                 if let Value::String(s) = e {
+///                     self.names.push(s.to_string());
+///                 }
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// fn main() -> toctoc::Result<()> {
+///     let mut j = r#"{"assets":["a","b"],"value":1}"#.to_string();
+///     let mut registry = Registry::default();
+///     let _: Value = json::from_str_with_preamble(&mut j, &mut registry, "assets")?;
+///     assert_eq!(registry.names, vec!["a", "b"]);
+///     Ok(())
+/// }
+/// ```
+///
+/// Since deserialization modifies the input in place, the preamble is
+/// read from a copy of `json`. A missing preamble field is not an error
+/// and `load_preamble` won't be called.
+pub fn from_str_with_preamble<'de, T: Deserialize<'de>, C: PreambleContext>(
+    json: &'de mut str,
+    ctx: &mut C,
+    preamble_key: &str,
+) -> Result<T> {
+    let mut copy = json.to_owned();
+    let mut preamble = Preamble {
+        key: preamble_key,
+        out: None,
+    };
+    Deserializer::from(&mut JsonDe::new(&mut copy)?).deserialize(&mut preamble, ctx)?;
+
+    if let Some(value) = &preamble.out {
+        ctx.load_preamble(value)?;
+    }
+
+    super::from_str(json, ctx)
+}
+
+/// Visits only the preamble field of the root object, ignores everything else
+struct Preamble<'a, 'de> {
+    key: &'a str,
+    out: Option<Value<'de>>,
+}
+
+impl<'a, 'de> Visitor<'de> for Preamble<'a, 'de> {
+    fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> Result<()> {
+        while let Some(k) = m.next()? {
+            if k == self.key && self.out.is_none() {
+                m.visit(Value::begin(&mut self.out), c)?;
+            } else {
+                m.visit(<dyn Visitor>::ignore(), c)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use std::io::Read;
+
+use crate::de::{Context, Deserialize};
+use crate::error::{Error, Result};
+
+/// Reads the whole `reader` then deserializes it as JSON.
+///
+/// The input is kept in a buffer that is dropped before returning, so `T`
+/// can't borrow from it, use [`from_str_owned`](super::from_str_owned)
+/// for types with borrowed strings.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let reader = std::io::Cursor::new(r#"{"code":200}"#);
+/// let out: std::collections::BTreeMap<String, u32> = json::from_reader(reader, &mut ()).unwrap();
+/// assert_eq!(out["code"], 200);
+/// ```
+pub fn from_reader<T, R>(mut reader: R, ctx: &mut dyn Context) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+    R: Read,
+{
+    let mut json = vec![];
+    reader
+        .read_to_end(&mut json)
+        .map_err(|err| Error::generic(err.to_string()))?;
+    super::from_slice(&mut json, ctx)
+}
@@ -0,0 +1,200 @@
+//! Basic [JSON Schema](https://json-schema.org/) validation, supports
+//! a subset of the draft-07 keywords:
+//!
+//! - `type` (`string`, `number`, `integer`, `boolean`, `null`, `array` and `object`)
+//! - `required` and `properties` for objects
+//! - `minimum` and `maximum` for numbers
+//! - `minLength` and `maxLength` for strings
+//! - `items` for arrays
+//!
+//! Any other keyword is ignored.
+
+use crate::error::{Error, Result};
+use crate::json::{Number, Object, Value};
+
+impl<'a> Value<'a> {
+    /// Validates this value against a JSON `schema`, returns the first error found.
+    ///
+    /// ```rust
+    /// use toctoc::json::{self, Value};
+    ///
+    /// let mut s = r#"{"type":"object","required":["code"]}"#.to_string();
+    /// let schema: Value = json::from_str(&mut s, &mut ()).unwrap();
+    ///
+    /// let mut j = r#"{"code":200}"#.to_string();
+    /// let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    /// assert!(value.schema_validate(&schema).is_ok());
+    ///
+    /// let mut j = r#"{"message":"Ok"}"#.to_string();
+    /// let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    /// assert!(value.schema_validate(&schema).is_err());
+    /// ```
+    pub fn schema_validate(&self, schema: &Value) -> Result<()> {
+        validate(self, schema, &mut String::from("#"))
+    }
+}
+
+/// `path` points to the value being validated, used for error messages
+fn validate(value: &Value, schema: &Value, path: &mut String) -> Result<()> {
+    let schema = match schema {
+        Value::Bool(true) => return Ok(()),
+        Value::Bool(false) => Err(err!("{}: rejected by schema", &path[..]))?,
+        Value::Object(schema) => schema,
+        _ => Err(err!("{}: invalid schema", &path[..]))?,
+    };
+
+    if let Some(ty) = schema.get("type") {
+        validate_type(value, ty, path)?;
+    }
+
+    match value {
+        Value::Number(_) => validate_number(value, schema, path)?,
+        Value::String(s) => validate_string(s, schema, path)?,
+        Value::Array(array) => {
+            if let Some(items) = schema.get("items") {
+                for (i, e) in array.iter().enumerate() {
+                    let item = match items {
+                        // Tuple validation, each element has its own schema
+                        Value::Array(items) => match items.get(i) {
+                            Some(item) => item,
+                            None => break,
+                        },
+                        _ => items,
+                    };
+
+                    let len = path.len();
+                    path.push('/');
+                    path.push_str(itoa::Buffer::new().format(i));
+                    validate(e, item, path)?;
+                    path.truncate(len);
+                }
+            }
+        }
+        Value::Object(object) => validate_object(object, schema, path)?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn validate_type(value: &Value, ty: &Value, path: &str) -> Result<()> {
+    let matches = |ty: &Value| -> Result<bool> {
+        let ty = match ty {
+            Value::String(ty) => &ty[..],
+            _ => Err(err!("{}: invalid schema `type`", path))?,
+        };
+        Ok(match (ty, value) {
+            ("null", Value::Null) => true,
+            ("boolean", Value::Bool(_)) => true,
+            ("string", Value::String(_)) => true,
+            ("number", Value::Number(_)) => true,
+            ("integer", Value::Number(Number::U64(_))) => true,
+            ("integer", Value::Number(Number::I64(_))) => true,
+            ("integer", Value::Number(Number::F32(n))) => n.fract() == 0.0,
+            ("integer", Value::Number(Number::F64(n))) => n.fract() == 0.0,
+            ("array", Value::Array(_)) => true,
+            ("object", Value::Object(_)) => true,
+            _ => false,
+        })
+    };
+
+    let valid = match ty {
+        // Any of the listed types
+        Value::Array(types) => {
+            let mut valid = false;
+            for ty in types {
+                if matches(ty)? {
+                    valid = true;
+                    break;
+                }
+            }
+            valid
+        }
+        _ => matches(ty)?,
+    };
+
+    if !valid {
+        Err(err!("{}: invalid type", path))?
+    }
+    Ok(())
+}
+
+fn validate_number(value: &Value, schema: &Object, path: &str) -> Result<()> {
+    // Every number can be compared as a `f64`
+    let n = value.as_f64_lossy().unwrap();
+
+    if let Some(min) = schema.get("minimum") {
+        match min.as_f64_lossy() {
+            Some(min) if n < min => Err(err!("{}: number is lower than the minimum", path))?,
+            Some(_) => {}
+            None => Err(err!("{}: invalid schema `minimum`", path))?,
+        }
+    }
+
+    if let Some(max) = schema.get("maximum") {
+        match max.as_f64_lossy() {
+            Some(max) if n > max => Err(err!("{}: number is greater than the maximum", path))?,
+            Some(_) => {}
+            None => Err(err!("{}: invalid schema `maximum`", path))?,
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_string(s: &str, schema: &Object, path: &str) -> Result<()> {
+    // Length is measured in characters not bytes
+    let len = s.chars().count() as u64;
+
+    if let Some(min) = schema.get("minLength") {
+        match min.as_u64() {
+            Some(min) if len < min => Err(err!("{}: string is shorter than minLength", path))?,
+            Some(_) => {}
+            None => Err(err!("{}: invalid schema `minLength`", path))?,
+        }
+    }
+
+    if let Some(max) = schema.get("maxLength") {
+        match max.as_u64() {
+            Some(max) if len > max => Err(err!("{}: string is longer than maxLength", path))?,
+            Some(_) => {}
+            None => Err(err!("{}: invalid schema `maxLength`", path))?,
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_object(object: &Object, schema: &Object, path: &mut String) -> Result<()> {
+    if let Some(required) = schema.get("required") {
+        let required = match required {
+            Value::Array(required) => required,
+            _ => Err(err!("{}: invalid schema `required`", &path[..]))?,
+        };
+        for field in required {
+            match field {
+                Value::String(field) if object.contains_key(&field[..]) => {}
+                Value::String(field) => Err(err!("{}: missing field `{}`", &path[..], &field[..]))?,
+                _ => Err(err!("{}: invalid schema `required`", &path[..]))?,
+            }
+        }
+    }
+
+    if let Some(properties) = schema.get("properties") {
+        let properties = match properties {
+            Value::Object(properties) => properties,
+            _ => Err(err!("{}: invalid schema `properties`", &path[..]))?,
+        };
+        for (k, property) in properties {
+            if let Some(v) = object.get(k) {
+                let len = path.len();
+                path.push('/');
+                path.push_str(k);
+                validate(v, property, path)?;
+                path.truncate(len);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
+use crate::error::{Error, Result};
+use crate::json::{JsonSerOptions, NonFiniteMode};
 use crate::ser::{Context, MapTrait, Return, SeqTrait, Serialize, SerializerTrait, VisitorTrait};
+use std::fmt;
+use std::io;
 
 /// Serialize any serializable type into a JSON string.
 ///
@@ -22,20 +26,328 @@ use crate::ser::{Context, MapTrait, Return, SeqTrait, Serialize, SerializerTrait
 /// }
 /// ```
 pub fn to_string<T: Serialize>(value: &T, context: &mut dyn Context) -> String {
-    let mut json = JsonSer::new();
-    match json.serialize(value, context) {
+    // Never fails with the default options
+    to_string_with_options(value, context, JsonSerOptions::default()).unwrap()
+}
+
+/// Serialize into a JSON string customizing the output with `options`,
+/// fails if a value can't be written with them.
+///
+/// ```rust
+/// use toctoc::json::{self, JsonSerOptions, NonFiniteMode};
+/// use std::collections::HashMap;
+///
+/// let mut map = HashMap::new();
+/// map.insert("b", f64::NAN);
+/// map.insert("á", 1.0);
+///
+/// let options = JsonSerOptions {
+///     sort_keys: true,
+///     escape_non_ascii: true,
+///     non_finite: NonFiniteMode::String,
+///     ..Default::default()
+/// };
+/// let j = json::to_string_with_options(&map, &mut (), options).unwrap();
+/// assert_eq!(j, r#"{"b":"NaN","\u00e1":1.0}"#);
+/// ```
+pub fn to_string_with_options<T: Serialize>(
+    value: &T,
+    context: &mut dyn Context,
+    options: JsonSerOptions,
+) -> Result<String> {
+    let mut json = JsonSer::with_options(options);
+    let t = match SerializerTrait::serialize(&mut json, value, context) {
         Return::Text(t) => t,
         _ => unreachable!(),
+    };
+    match json.invalid.take() {
+        Some(err) => Err(err),
+        None => Ok(t),
     }
 }
 
-pub struct JsonSer {
+/// Serialize into `buf` reusing its allocated memory, any previous
+/// contents of `buf` will be discarded.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let mut buf = String::new();
+/// for n in 0..10u32 {
+///     json::to_string_into(&n, &mut (), &mut buf);
+///     assert_eq!(buf, n.to_string());
+/// }
+/// ```
+pub fn to_string_into<T: Serialize>(value: &T, context: &mut dyn Context, buf: &mut String) {
+    let out = std::mem::take(buf).into_bytes();
+    let mut json = JsonSer::with_buffer(out);
+    match SerializerTrait::serialize(&mut json, value, context) {
+        Return::Text(t) => *buf = t,
+        _ => unreachable!(),
+    }
+}
+
+/// Serialize into UTF-8 encoded JSON bytes, same output as `to_string`.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let v = json::to_vec(&vec!["a", "b"], &mut ());
+/// assert_eq!(v, br#"["a","b"]"#);
+/// ```
+pub fn to_vec<T: Serialize>(value: &T, context: &mut dyn Context) -> Vec<u8> {
+    let mut json = JsonSer::new();
+    value.begin((&mut json).into(), context);
+    json.out
+}
+
+/// Serialize into `buf` reusing its allocated memory, any previous
+/// contents of `buf` will be discarded.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let mut buf = vec![];
+/// for n in 0..10u32 {
+///     json::to_vec_into(&n, &mut (), &mut buf);
+///     assert_eq!(buf, n.to_string().as_bytes());
+/// }
+/// ```
+pub fn to_vec_into<T: Serialize>(value: &T, context: &mut dyn Context, buf: &mut Vec<u8>) {
+    let out = std::mem::take(buf);
+    let mut json = JsonSer::with_buffer(out);
+    value.begin((&mut json).into(), context);
+    *buf = json.out;
+}
+
+/// Serialize into a human readable JSON string, every element and field
+/// is written in its own line using `indent` (like `"  "` or `"\t"`) for each
+/// nesting level.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let j = json::to_string_pretty(&vec![(1, 2)], &mut (), "  ");
+/// assert_eq!(j, "[\n  [\n    1,\n    2\n  ]\n]");
+/// ```
+pub fn to_string_pretty<T: Serialize>(
+    value: &T,
+    context: &mut dyn Context,
+    indent: &str,
+) -> String {
+    let options = JsonSerOptions {
+        indent: Some(indent.to_owned()),
+        ..Default::default()
+    };
+    to_string_with_options(value, context, options).unwrap()
+}
+
+/// Serialize directly into a `writer`, like a file or a socket, without
+/// building the whole JSON string in memory first.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let mut out = vec![];
+/// json::to_writer(&vec![1, 2, 3], &mut (), &mut out).unwrap();
+/// assert_eq!(out, b"[1,2,3]");
+/// ```
+pub fn to_writer<T: Serialize, W: io::Write>(
+    value: &T,
+    context: &mut dyn Context,
+    mut writer: W,
+) -> Result<()> {
+    let mut json = JsonSer::with_writer(&mut writer);
+    value.begin((&mut json).into(), context);
+    json.finish().map_err(|err| Error::generic(err.to_string()))
+}
+
+/// Serialize into a formatter, used by the `Display` impls
+pub(crate) fn to_fmt<T: Serialize>(value: &T, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut writer = FmtWriter(f);
+    let mut json = JsonSer::with_writer(&mut writer);
+    value.begin((&mut json).into(), &mut ());
+    json.finish().map_err(|_| fmt::Error)
+}
+
+/// Passes the `JsonSer` output to a `fmt::Formatter`, the chunks are always
+/// split before a comma so each one of them is valid UTF-8
+struct FmtWriter<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl<'a, 'b> io::Write for FmtWriter<'a, 'b> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let s = std::str::from_utf8(buf)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        self.0
+            .write_str(s)
+            .map_err(|_| io::Error::from(io::ErrorKind::Other))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Buffered output is passed to the writer once it reaches this size
+const WRITER_CHUNK_SIZE: usize = 8 * 1024;
+
+/// JSON serializer, can be kept around to serialize many values
+/// reusing the same output buffer, see [`JsonSer::serialize`]
+pub struct JsonSer<'w> {
     out: Vec<u8>,
+    pretty: Option<PrettyState>,
+    writer: Option<&'w mut dyn io::Write>,
+    /// First error returned by the `writer`, nothing else is written after it
+    error: Option<io::Error>,
+    sort_keys: bool,
+    escape_non_ascii: bool,
+    non_finite: NonFiniteMode,
+    /// Maps being written with `sort_keys`, the innermost one is the last
+    sorted: Vec<SortedMap>,
+    /// First value that couldn't be written with the current options
+    invalid: Option<Error>,
 }
 
-impl JsonSer {
+/// Fields of a map that will be sorted once the map is done
+struct SortedMap {
+    /// Where the first field starts in the output
+    start: usize,
+    /// Key and output position of each field
+    fields: Vec<(String, usize)>,
+}
+
+/// Indentation used by the pretty printed output
+struct PrettyState {
+    indent: String,
+    depth: usize,
+}
+
+impl<'w> JsonSer<'w> {
     pub fn new() -> Self {
-        Self { out: vec![] }
+        Self::with_buffer(vec![])
+    }
+
+    /// Serializer that writes into an existing `out` vec reusing its allocated memory,
+    /// any previous contents of `out` will be discarded
+    pub fn with_buffer(mut out: Vec<u8>) -> Self {
+        out.clear();
+        Self {
+            out,
+            pretty: None,
+            writer: None,
+            error: None,
+            sort_keys: false,
+            escape_non_ascii: false,
+            non_finite: NonFiniteMode::Null,
+            sorted: vec![],
+            invalid: None,
+        }
+    }
+
+    /// Serializer with human readable output, see [`to_string_pretty`]
+    pub fn pretty(indent: &str) -> Self {
+        Self::with_options(JsonSerOptions {
+            indent: Some(indent.to_owned()),
+            ..Default::default()
+        })
+    }
+
+    /// Serializer with custom `options`, see [`to_string_with_options`]
+    pub fn with_options(options: JsonSerOptions) -> Self {
+        let mut json = Self::new();
+        json.pretty = options
+            .indent
+            .map(|indent| PrettyState { indent, depth: 0 });
+        json.sort_keys = options.sort_keys;
+        json.escape_non_ascii = options.escape_non_ascii;
+        json.non_finite = options.non_finite;
+        json
+    }
+
+    /// Serializer that passes its output to `writer` in chunks, call `finish`
+    /// once done to write the remaining output, see [`to_writer`]
+    pub fn with_writer(writer: &'w mut dyn io::Write) -> Self {
+        let mut json = Self::with_buffer(Vec::with_capacity(WRITER_CHUNK_SIZE));
+        json.writer = Some(writer);
+        json
+    }
+
+    /// Serialize `value` discarding the previous output but keeping its
+    /// allocated memory, so serializing values of similar sizes won't
+    /// allocate after the first call.
+    ///
+    /// Serializers created `with_writer` pass their output to the writer
+    /// instead, so only the part not written yet is returned, and the
+    /// output of the previous value is written before starting.
+    ///
+    /// ```rust
+    /// use toctoc::json::JsonSer;
+    ///
+    /// let mut json = JsonSer::new();
+    /// for n in 0..10u32 {
+    ///     assert_eq!(json.serialize(&vec![n], &mut ()), format!("[{}]", n));
+    /// }
+    /// ```
+    pub fn serialize<T: Serialize>(&mut self, value: &T, context: &mut dyn Context) -> &str {
+        self.clear();
+        value.begin(self.into(), context);
+        // Only valid UTF-8 is ever written
+        unsafe { std::str::from_utf8_unchecked(&self.out) }
+    }
+
+    /// Discards the output and any pending state keeping the allocated memory,
+    /// serializers created `with_writer` write the remaining output first
+    /// since the start of it may already have been written
+    pub fn clear(&mut self) {
+        if let (Some(writer), None) = (&mut self.writer, &self.error) {
+            if let Err(err) = writer.write_all(&self.out) {
+                self.error = Some(err);
+            }
+        }
+        self.out.clear();
+        self.sorted.clear();
+        self.invalid = None;
+        if let Some(pretty) = &mut self.pretty {
+            pretty.depth = 0;
+        }
+    }
+
+    /// Writes all the remaining output to the writer, returns the
+    /// first error found while writing
+    pub fn finish(&mut self) -> io::Result<()> {
+        if let Some(writer) = &mut self.writer {
+            if self.error.is_none() {
+                writer.write_all(&self.out)?;
+                writer.flush()?;
+            }
+            self.out.clear();
+        }
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Passes the output buffered so far to the writer, the last byte is kept
+    /// because it may be a comma that still needs to be undone
+    #[inline]
+    fn write_chunk(&mut self) {
+        // Fields of sorted maps are moved around once the map is done
+        if self.out.len() < WRITER_CHUNK_SIZE || !self.sorted.is_empty() {
+            return;
+        }
+        if let Some(writer) = &mut self.writer {
+            let i = self.out.len() - 1;
+            if self.error.is_none() {
+                if let Err(err) = writer.write_all(&self.out[..i]) {
+                    self.error = Some(err);
+                }
+            }
+            let last = self.out[i];
+            self.out.clear();
+            self.out.push(last);
+        }
     }
 
     #[inline]
@@ -47,49 +359,102 @@ impl JsonSer {
         self.out.extend_from_slice(s.as_bytes())
     }
 
-    // Clippy false positive: https://github.com/rust-lang/rust-clippy/issues/5169
-    #[allow(clippy::zero_prefixed_literal)]
     fn push_str_escaped(&mut self, value: &str) {
-        self.out.push(b'"');
-
-        let bytes = value.as_bytes();
-        let mut start = 0;
+        let out = &mut self.out;
+        if self.escape_non_ascii {
+            escape_str(value, |s| escape_non_ascii(s, out));
+        } else {
+            escape_str(value, |s| out.extend_from_slice(s.as_bytes()));
+        }
+    }
 
-        for (i, &byte) in bytes.iter().enumerate() {
-            let escape = ESCAPE[byte as usize];
-            if escape == 0 {
-                continue;
+    #[cold]
+    fn push_non_finite(&mut self, n: f64) {
+        match self.non_finite {
+            NonFiniteMode::Null => self.push_str("null"),
+            NonFiniteMode::Error => {
+                if self.invalid.is_none() {
+                    self.invalid = Some(Error::non_finite_float());
+                }
+                self.push_str("null")
             }
+            NonFiniteMode::String if n.is_nan() => self.push_str("\"NaN\""),
+            NonFiniteMode::String if n > 0.0 => self.push_str("\"Infinity\""),
+            NonFiniteMode::String => self.push_str("\"-Infinity\""),
+        }
+    }
 
-            if start < i {
-                self.push_str(&value[start..i]);
-            }
+    /// Must be called before writing each map field
+    #[inline]
+    fn sort_field(&mut self, f: &str) {
+        if let Some(map) = self.sorted.last_mut() {
+            map.fields.push((f.to_owned(), self.out.len()));
+        }
+    }
 
-            match escape {
-                self::BB => self.push_str("\\b"),
-                self::TT => self.push_str("\\t"),
-                self::NN => self.push_str("\\n"),
-                self::FF => self.push_str("\\f"),
-                self::RR => self.push_str("\\r"),
-                self::QU => self.push_str("\\\""),
-                self::BS => self.push_str("\\\\"),
-                self::U => {
-                    static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
-                    self.push_str("\\u00");
-                    self.push(HEX_DIGITS[(byte >> 4) as usize]);
-                    self.push(HEX_DIGITS[(byte & 0xF) as usize]);
-                }
-                _ => unreachable!(),
+    /// Must be called before `undo_comma` when the map is done, every
+    /// field still ends with a comma so they can be freely reordered
+    fn sort_fields(&mut self) {
+        if !self.sort_keys {
+            return;
+        }
+        let mut map = self.sorted.pop().unwrap();
+        let mut ends: Vec<usize> = map.fields.iter().skip(1).map(|(_, i)| *i).collect();
+        ends.push(self.out.len());
+        let start = map.start;
+        let fields = self.out.split_off(start);
+
+        let mut ranges: Vec<_> = map
+            .fields
+            .drain(..)
+            .zip(ends)
+            .map(|((k, i), end)| (k, (i - start)..(end - start)))
+            .collect();
+        ranges.sort_by(|a, b| a.0.cmp(&b.0));
+        for (_, range) in ranges {
+            self.out.extend_from_slice(&fields[range]);
+        }
+    }
+
+    /// Starts a new line for the next element or field when pretty printing
+    #[inline]
+    fn newline(&mut self) {
+        if let Some(pretty) = &self.pretty {
+            self.out.push(b'\n');
+            for _ in 0..pretty.depth {
+                self.out.extend_from_slice(pretty.indent.as_bytes());
             }
+        }
+    }
 
-            start = i + 1;
+    #[inline]
+    fn indent_in(&mut self) {
+        if let Some(pretty) = &mut self.pretty {
+            pretty.depth += 1;
         }
+    }
 
-        if start != bytes.len() {
-            self.push_str(&value[start..]);
+    /// Must be called after `undo_comma`, empty seqs and maps
+    /// (ended by their `open` char) are kept in the same line
+    #[inline]
+    fn indent_out(&mut self, open: u8) {
+        if let Some(pretty) = &mut self.pretty {
+            pretty.depth -= 1;
+        } else {
+            return;
+        }
+        if self.out.last() != Some(&open) {
+            self.newline();
         }
+    }
 
-        self.push(b'"');
+    #[inline]
+    fn colon(&mut self) {
+        if self.pretty.is_some() {
+            self.push_str(": ");
+        } else {
+            self.push(b':');
+        }
     }
 
     /// **NOTE** Must guarantee that there is at least one element in `out`
@@ -102,7 +467,7 @@ impl JsonSer {
     }
 }
 
-impl SerializerTrait for JsonSer {
+impl<'w> SerializerTrait for JsonSer<'w> {
     fn serialize(&mut self, s: &dyn Serialize, c: &mut dyn Context) -> Return {
         s.begin(self.into(), c);
         let mut v = vec![];
@@ -111,7 +476,7 @@ impl SerializerTrait for JsonSer {
     }
 }
 
-impl VisitorTrait for JsonSer {
+impl<'w> VisitorTrait for JsonSer<'w> {
     fn null(&mut self) {
         self.push_str("null");
     }
@@ -124,6 +489,14 @@ impl VisitorTrait for JsonSer {
         self.push_str_escaped(&s);
     }
 
+    fn short(&mut self, n: i16) {
+        self.push_str(itoa::Buffer::new().format(n));
+    }
+
+    fn ushort(&mut self, n: u16) {
+        self.push_str(itoa::Buffer::new().format(n));
+    }
+
     fn long(&mut self, n: i64) {
         self.push_str(itoa::Buffer::new().format(n));
     }
@@ -136,17 +509,25 @@ impl VisitorTrait for JsonSer {
         if n.is_finite() {
             self.push_str(ryu::Buffer::new().format_finite(n))
         } else {
-            self.push_str("null")
+            self.push_non_finite(n)
         }
     }
 
     fn seq(&mut self) -> &mut dyn SeqTrait {
         self.push(b'[');
+        self.indent_in();
         self
     }
 
     fn map(&mut self) -> &mut dyn MapTrait {
         self.push(b'{');
+        self.indent_in();
+        if self.sort_keys {
+            self.sorted.push(SortedMap {
+                start: self.out.len(),
+                fields: vec![],
+            });
+        }
         self
     }
 
@@ -154,7 +535,7 @@ impl VisitorTrait for JsonSer {
         if n.is_finite() {
             self.push_str(ryu::Buffer::new().format_finite(n))
         } else {
-            self.push_str("null")
+            self.push_non_finite(n as f64)
         }
     }
 
@@ -167,39 +548,136 @@ impl VisitorTrait for JsonSer {
         self.push_str(&bintext::hex::encode(b.as_ref()));
         self.push(b'"');
     }
+
+    #[cfg(feature = "base64")]
+    fn base64(&mut self, b: &[u8], _: usize, config: base64::Config) {
+        self.string(&base64::encode_config(b, config))
+    }
 }
 
-impl SeqTrait for JsonSer {
+impl<'w> SeqTrait for JsonSer<'w> {
     fn element(&mut self, s: &dyn Serialize, c: &mut dyn Context) {
+        self.newline();
         s.begin(self.into(), c);
         self.push(b',');
+        self.write_chunk();
     }
 
     fn done(&mut self) {
         unsafe {
             self.undo_comma();
         }
+        self.indent_out(b'[');
         self.push(b']');
     }
 }
 
-impl MapTrait for JsonSer {
+impl<'w> MapTrait for JsonSer<'w> {
     fn field(&mut self, f: &str, s: &dyn Serialize, c: &mut dyn Context) {
-        self.push(b'\"');
+        self.sort_field(f);
+        self.newline();
+        self.push_str_escaped(f);
+        self.colon();
+        s.begin(self.into(), c);
+        self.push(b',');
+        self.write_chunk();
+    }
+
+    unsafe fn field_raw_key(&mut self, f: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        // Key is trusted to not have any character that needs escaping
+        self.sort_field(f);
+        self.newline();
+        self.push(b'"');
         self.push_str(f);
-        self.push_str("\":");
+        self.push(b'"');
+        self.colon();
         s.begin(self.into(), c);
         self.push(b',');
+        self.write_chunk();
     }
 
     fn done(&mut self) {
+        self.sort_fields();
         unsafe {
             self.undo_comma();
         }
+        self.indent_out(b'{');
         self.push(b'}');
     }
 }
 
+/// Writes `value` as a quoted JSON string, piece by piece, using `push`
+// Clippy false positive: https://github.com/rust-lang/rust-clippy/issues/5169
+#[allow(clippy::zero_prefixed_literal)]
+#[inline]
+pub(crate) fn escape_str<F: FnMut(&str)>(value: &str, mut push: F) {
+    push("\"");
+
+    let bytes = value.as_bytes();
+    let mut start = 0;
+
+    for (i, &byte) in bytes.iter().enumerate() {
+        let escape = ESCAPE[byte as usize];
+        if escape == 0 {
+            continue;
+        }
+
+        if start < i {
+            push(&value[start..i]);
+        }
+
+        match escape {
+            self::BB => push("\\b"),
+            self::TT => push("\\t"),
+            self::NN => push("\\n"),
+            self::FF => push("\\f"),
+            self::RR => push("\\r"),
+            self::QU => push("\\\""),
+            self::BS => push("\\\\"),
+            self::U => {
+                static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+                let u = [
+                    b'\\',
+                    b'u',
+                    b'0',
+                    b'0',
+                    HEX_DIGITS[(byte >> 4) as usize],
+                    HEX_DIGITS[(byte & 0xF) as usize],
+                ];
+                push(unsafe { std::str::from_utf8_unchecked(&u) });
+            }
+            _ => unreachable!(),
+        }
+
+        start = i + 1;
+    }
+
+    if start != bytes.len() {
+        push(&value[start..]);
+    }
+
+    push("\"");
+}
+
+/// Writes the ASCII chars of `s` as they are and the others as `\uXXXX`
+/// escapes, chars outside the BMP are written as UTF-16 surrogate pairs
+fn escape_non_ascii(s: &str, out: &mut Vec<u8>) {
+    static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+    for ch in s.chars() {
+        if ch.is_ascii() {
+            out.push(ch as u8);
+            continue;
+        }
+        let mut units = [0; 2];
+        for unit in ch.encode_utf16(&mut units) {
+            out.extend_from_slice(b"\\u");
+            for shift in &[12, 8, 4, 0] {
+                out.push(HEX_DIGITS[((*unit >> shift) & 0xF) as usize]);
+            }
+        }
+    }
+}
+
 const BB: u8 = b'b'; // \x08
 const TT: u8 = b't'; // \x09
 const NN: u8 = b'n'; // \x0A
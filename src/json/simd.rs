@@ -1,7 +1,10 @@
 use std::mem;
+use std::slice;
+use std::str;
 
 use crate::de::{Context, Deserialize, DeserializerTrait, Map, Seq, Visitor};
 use crate::error::{Error, Result};
+use crate::json::JsonDeOptions;
 use simd_json::{Node, StaticNode};
 
 /// Deserialize a JSON string into any deserializable type.
@@ -27,24 +30,119 @@ use simd_json::{Node, StaticNode};
 pub fn from_str<'de, T: Deserialize<'de>>(json: &'de mut str, ctx: &mut dyn Context) -> Result<T> {
     let mut out = None;
     let mut de = JsonDe::new(json)?;
-    de.visit(T::begin(&mut out), ctx)?;
+    de.deserialize(T::begin(&mut out), ctx)?;
     out.ok_or_else(Error::unknown)
 }
 
+/// Same as `from_str` but with custom `options`, `from_str` uses
+/// `JsonDeOptions::default()`.
+///
+/// ```rust
+/// use toctoc::json::{self, JsonDeOptions, Value};
+///
+/// let options = JsonDeOptions { max_depth: 2 };
+///
+/// let mut j = "[[1]]".to_string();
+/// let out: toctoc::Result<Value> = json::from_str_with_options(&mut j, &mut (), options);
+/// assert!(out.is_ok());
+///
+/// let mut j = "[[[1]]]".to_string();
+/// let out: toctoc::Result<Value> = json::from_str_with_options(&mut j, &mut (), options);
+/// assert!(out.is_err());
+/// ```
+pub fn from_str_with_options<'de, T: Deserialize<'de>>(
+    json: &'de mut str,
+    ctx: &mut dyn Context,
+    options: JsonDeOptions,
+) -> Result<T> {
+    let mut out = None;
+    let mut de = JsonDe::new(json)?;
+    de.max_depth = options.max_depth;
+    de.deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
+/// Deserialize JSON bytes into any deserializable type, `json` must be valid UTF-8,
+/// there's no need to convert it into a `str` first.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// let mut j = br#"["a","b"]"#.to_vec();
+/// let out: Vec<&str> = json::from_slice(&mut j, &mut ()).unwrap();
+/// assert_eq!(out, vec!["a", "b"]);
+/// ```
+pub fn from_slice<'de, T: Deserialize<'de>>(
+    json: &'de mut [u8],
+    ctx: &mut dyn Context,
+) -> Result<T> {
+    let mut out = None;
+    let mut de = JsonDe::from_slice(json)?;
+    de.deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
+/// **NOTE** `-0` is read as the integer `0`, simd-json parses both the same
+/// way and its tape doesn't keep the position of the values to look at the
+/// input, so the sign is only kept by the non-SIMD deserializer. For the same
+/// reason its errors don't have a [`Error::position`].
 pub struct JsonDe<'de> {
     index: usize,
+    /// One past the last node not yet returned by `next_back`
+    back: usize,
     tape: Vec<Node<'de>>,
+    /// Maximum number of nested arrays and objects allowed
+    max_depth: usize,
+    /// Current number of nested arrays and objects
+    depth: usize,
 }
 
 impl<'de> JsonDe<'de> {
     pub fn new(json: &'de mut str) -> Result<Self> {
+        Self::parse(unsafe { json.as_bytes_mut() })
+    }
+
+    /// Same as `new` but validates the `json` bytes as UTF-8 first
+    pub fn from_slice(json: &'de mut [u8]) -> Result<Self> {
+        // simd-json validates the input too, but the type of its errors isn't
+        // exposed, this pass is the only way to report `Error::invalid_utf8`
+        if !faster_utf8_validator::validate(json) {
+            Err(Error::invalid_utf8())?
+        }
+        Self::parse(json)
+    }
+
+    fn parse(json: &'de mut [u8]) -> Result<Self> {
+        let (ptr, len) = (json.as_mut_ptr(), json.len());
+        let tape = match simd_json::to_tape(json) {
+            Ok(tape) => {
+                unsafe { fill_gaps(ptr, &tape) };
+                tape
+            }
+            Err(err) => {
+                unsafe { repair_utf8(ptr, len) };
+                Err(Error::generic(err.to_string()))?
+            }
+        };
         Ok(Self {
             index: 1, // First node is always of type `Static(Null)`,
-            tape: simd_json::to_tape(unsafe { json.as_bytes_mut() })
-                .map_err(|err| Error::generic(err.to_string()))?,
+            back: tape.len(),
+            tape,
+            max_depth: JsonDeOptions::default().max_depth,
+            depth: 0,
         })
     }
 
+    /// Keeps track of the nesting depth to avoid overflowing the stack
+    #[inline(always)]
+    fn enter_nested(&mut self) -> Result<()> {
+        if self.depth >= self.max_depth {
+            Err(Error::depth_exceeded(self.max_depth))?
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
     fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
         use Node::*;
         use StaticNode::*;
@@ -79,11 +177,15 @@ impl<'de> JsonDe<'de> {
                     v.string(s, c)?;
                 }
             }
-            Some(Array(_, e)) => {
-                v.seq(&mut Stack { e, de: self }, c)?;
+            Some(Array(len, e)) => {
+                self.enter_nested()?;
+                v.seq(&mut Stack::new(e, len, self), c)?;
+                self.depth -= 1;
             }
-            Some(Object(_, e)) => {
-                v.map(&mut Stack { e, de: self }, c)?;
+            Some(Object(len, e)) => {
+                self.enter_nested()?;
+                v.map(&mut Stack::new(e, len, self), c)?;
+                self.depth -= 1;
             }
             _ => {}
         }
@@ -91,20 +193,83 @@ impl<'de> JsonDe<'de> {
     }
 }
 
+/// simd-json unescapes strings in place, moving them to the front and leaving
+/// the remaining bytes behind, these start with the continuation bytes of a
+/// character cut in half whenever the string has escapes before a multi-byte
+/// character. Overwrites them with spaces so the input remains valid UTF-8.
+unsafe fn fill_gaps(ptr: *mut u8, tape: &[Node]) {
+    for node in tape {
+        if let Node::String(s) = node {
+            // Always stops at the closing quote
+            let mut i = s.as_ptr() as usize - ptr as usize + s.len();
+            while *ptr.add(i) & 0xC0 == 0x80 {
+                *ptr.add(i) = b' ';
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Same as `fill_gaps` but without the tape, when parsing fails midway
+#[cold]
+unsafe fn repair_utf8(ptr: *mut u8, len: usize) {
+    let bytes = slice::from_raw_parts_mut(ptr, len);
+    let mut i = 0;
+    while let Err(err) = str::from_utf8(&bytes[i..]) {
+        i += err.valid_up_to();
+        let n = err.error_len().unwrap_or(bytes.len() - i);
+        for b in &mut bytes[i..i + n] {
+            *b = b' ';
+        }
+        i += n;
+    }
+}
+
 struct Stack<'a, 'de: 'de> {
     e: usize,
+    /// Elements or fields in the tape node
+    len: usize,
     de: &'a mut JsonDe<'de>,
+    /// Last key or element index visited, used to annotate errors
+    key: &'de str,
+    count: usize,
+}
+
+impl<'a, 'de: 'de> Stack<'a, 'de> {
+    fn new(e: usize, len: usize, de: &'a mut JsonDe<'de>) -> Self {
+        Self {
+            e,
+            len,
+            de,
+            key: "",
+            count: 0,
+        }
+    }
 }
 
 impl<'a, 'de: 'de> Seq<'de> for Stack<'a, 'de> {
     fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<bool> {
         if self.de.index < self.e {
-            JsonDe::visit(self.de, v, c)?;
+            let index = self.count;
+            self.count += 1;
+            JsonDe::visit(self.de, v, c).map_err(|e| e.append_path_index(index))?;
             Ok(true)
         } else {
             Ok(false)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len.saturating_sub(self.count);
+        (len, Some(len))
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        // Jump over the remaining tape nodes
+        self.de.index = self.de.index.max(self.e);
+        self.count = self.len;
+        Ok(())
+    }
 }
 
 impl<'a, 'de: 'de> Map<'de> for Stack<'a, 'de> {
@@ -113,6 +278,8 @@ impl<'a, 'de: 'de> Map<'de> for Stack<'a, 'de> {
 
         if self.de.index < self.e {
             if let Some(String(s)) = self.de.next() {
+                self.key = s;
+                self.count += 1;
                 Ok(Some(s))
             } else {
                 Err(Error::expecting("field name"))?
@@ -123,7 +290,20 @@ impl<'a, 'de: 'de> Map<'de> for Stack<'a, 'de> {
     }
 
     fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
-        JsonDe::visit(self.de, v, c)
+        let key = self.key;
+        JsonDe::visit(self.de, v, c).map_err(|e| e.append_path_segment(key))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len.saturating_sub(self.count);
+        (len, Some(len))
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        // Jump over the remaining tape nodes
+        self.de.index = self.de.index.max(self.e);
+        self.count = self.len;
+        Ok(())
     }
 }
 
@@ -131,9 +311,29 @@ impl<'de> Iterator for JsonDe<'de> {
     type Item = Node<'de>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let v = self.tape.get(self.index).cloned();
-        self.index += 1;
-        v
+        if self.index < self.back {
+            let v = self.tape.get(self.index).cloned();
+            self.index += 1;
+            v
+        } else {
+            None
+        }
+    }
+}
+
+/// Scans the tape backwards, one node at a time.
+///
+/// Composite nodes (arrays and objects) are returned after their children,
+/// so `next_back` is meant for scanning purposes and shouldn't be mixed with
+/// a recursive descent like the one done by the deserializer.
+impl<'de> DoubleEndedIterator for JsonDe<'de> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.back {
+            self.back -= 1;
+            self.tape.get(self.back).cloned()
+        } else {
+            None
+        }
     }
 }
 
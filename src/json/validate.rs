@@ -0,0 +1,19 @@
+use crate::de::{Deserializer, Visitor};
+use crate::error::Result;
+use crate::json::JsonDe;
+
+/// Checks that `json` is well-formed without producing any value.
+///
+/// ```rust
+/// use toctoc::json;
+///
+/// assert!(json::validate(r#"{"a":[1,2,{"b":null}]}"#).is_ok());
+/// assert!(json::validate(r#"{"a":[1,2}"#).is_err());
+/// ```
+///
+/// Deserialization unescapes strings in place, so `json` is validated
+/// from a copy.
+pub fn validate(json: &str) -> Result<()> {
+    let mut copy = json.to_owned();
+    Deserializer::from(&mut JsonDe::new(&mut copy)?).deserialize(<dyn Visitor>::ignore(), &mut ())
+}
@@ -1,9 +1,15 @@
 use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::mem;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use std::vec;
 
 use crate::bytes::guess_align_of;
-use crate::de::{self, Deserialize, Map, Seq, Visitor};
-use crate::error::Result;
-use crate::json::{Array, Number, Object};
+use crate::de::{self, Deserialize, DeserializerTrait, Map, Seq, Visitor};
+use crate::error::{Error, Result};
+use crate::json::{self, Array, JsonDeOptions, Number, Object};
 use crate::ser::{self, Serialize};
 use crate::Place;
 
@@ -23,7 +29,7 @@ use crate::Place;
 /// }
 /// // no stack overflow when `value` goes out of scope
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub enum Value<'a> {
     Null,
     Bool(bool),
@@ -40,6 +46,28 @@ pub enum Value<'a> {
     Object(Object<'a>),
 }
 
+impl<'a> fmt::Display for Value<'a> {
+    /// Formats the value as compact JSON, same output of `json::to_string`.
+    ///
+    /// ```rust
+    /// use toctoc::json::{self, Value};
+    ///
+    /// let mut j = r#"{ "a": [1, 2] }"#.to_string();
+    /// let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    /// assert_eq!(format!("{}", value), r#"{"a":[1,2]}"#);
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        json::to_fmt(self, f)
+    }
+}
+
+impl<'a> fmt::Debug for Value<'a> {
+    /// Same as `Display`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
 impl<'a> Default for Value<'a> {
     /// The default value is null.
     fn default() -> Self {
@@ -47,6 +75,483 @@ impl<'a> Default for Value<'a> {
     }
 }
 
+impl<'a> Value<'a> {
+    /// Gets the inner number if the value is a number.
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Gets the number as `u64`, `None` for negative or floating point numbers.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.as_number()? {
+            Number::U64(n) => Some(*n),
+            Number::I64(n) if *n >= 0 => Some(*n as u64),
+            _ => None,
+        }
+    }
+
+    /// Gets the number as `i64`, `None` for floating point numbers or
+    /// numbers greater than `i64::MAX`.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    /// Gets any kind of number as `f64`, may lose precision.
+    pub fn as_f64_lossy(&self) -> Option<f64> {
+        self.as_number().map(Number::as_f64)
+    }
+
+    /// Looks up a value by a [JSON Pointer](https://tools.ietf.org/html/rfc6901),
+    /// an empty `ptr` points to the value itself.
+    ///
+    /// ```rust
+    /// use toctoc::json::{self, Value};
+    ///
+    /// let mut j = r#"{"a":{"b":[10,20]}}"#.to_string();
+    /// let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    /// assert_eq!(value.pointer("/a/b/1").and_then(Value::as_u64), Some(20));
+    /// assert_eq!(value.pointer("/a/c"), None);
+    /// ```
+    pub fn pointer(&self, ptr: &str) -> Option<&Value<'a>> {
+        if ptr.is_empty() {
+            return Some(self);
+        }
+        if !ptr.starts_with('/') {
+            return None;
+        }
+        let mut target = self;
+        for token in ptr[1..].split('/') {
+            target = match target {
+                Value::Object(object) => object.get(&unescape_token(token)[..])?,
+                Value::Array(array) => array.get(parse_index(token)?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Mutable version of `pointer`
+    pub fn pointer_mut(&mut self, ptr: &str) -> Option<&mut Value<'a>> {
+        if ptr.is_empty() {
+            return Some(self);
+        }
+        if !ptr.starts_with('/') {
+            return None;
+        }
+        let mut target = self;
+        for token in ptr[1..].split('/') {
+            target = match target {
+                Value::Object(object) => object.get_mut(&unescape_token(token)[..])?,
+                Value::Array(array) => array.get_mut(parse_index(token)?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Same as `pointer` but returns `default` when nothing was found
+    pub fn pointer_or<'b>(&'b self, ptr: &str, default: &'b Value<'a>) -> &'b Value<'a> {
+        self.pointer(ptr).unwrap_or(default)
+    }
+
+    /// Gets an element of an array by `usize` or a field of an object by `&str`,
+    /// `None` if the value has other type or the element doesn't exist.
+    ///
+    /// ```rust
+    /// use toctoc::json::{self, Value};
+    ///
+    /// let mut j = r#"{"a":[true]}"#.to_string();
+    /// let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    /// assert_eq!(value.get("a").and_then(|a| a.get(0)), Some(&Value::Bool(true)));
+    /// assert_eq!(value.get(0), None);
+    /// ```
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&Value<'a>> {
+        index.index_into(self)
+    }
+
+    /// Mutable version of `get`
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut Value<'a>> {
+        index.index_into_mut(self)
+    }
+
+    /// Applies a [JSON Merge Patch](https://tools.ietf.org/html/rfc7396) in place,
+    /// null values in `patch` remove the keys, objects are merged recursively
+    /// and anything else replaces the previous value.
+    ///
+    /// ```rust
+    /// use toctoc::json::{self, Value};
+    ///
+    /// // Values taken from `patch` may borrow from it so it must outlive `value`
+    /// let mut p = r#"{"a":"z","c":{"f":null}}"#.to_string();
+    /// let patch: Value = json::from_str(&mut p, &mut ()).unwrap();
+    ///
+    /// let mut j = r#"{"a":"b","c":{"d":"e","f":"g"}}"#.to_string();
+    /// let mut value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    ///
+    /// value.merge_patch(&patch);
+    /// assert_eq!(json::to_string(&value, &mut ()), r#"{"a":"z","c":{"d":"e"}}"#);
+    /// ```
+    pub fn merge_patch(&mut self, patch: &Value<'a>) {
+        let patch = match patch {
+            Value::Object(patch) => patch,
+            _ => {
+                *self = patch.clone();
+                return;
+            }
+        };
+
+        let object = match self {
+            Value::Object(object) => object,
+            other => {
+                *other = Value::Object(Object::new());
+                match other {
+                    Value::Object(object) => object,
+                    _ => unreachable!(),
+                }
+            }
+        };
+
+        for (k, v) in patch.iter() {
+            if let Value::Null = v {
+                object.remove(k);
+            } else {
+                object
+                    .entry(k.clone())
+                    .or_insert(Value::Null)
+                    .merge_patch(v);
+            }
+        }
+    }
+}
+
+impl<'a> Value<'a> {
+    /// Copies every borrowed string and byte slice, so the value no longer
+    /// depends on the input it was deserialized from.
+    ///
+    /// ```rust
+    /// use toctoc::json::{self, Value};
+    ///
+    /// let value: Value<'static> = {
+    ///     let mut j = r#"{"a":["b","c"]}"#.to_string();
+    ///     let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    ///     value.into_owned()
+    /// };
+    /// assert_eq!(json::to_string(&value, &mut ()), r#"{"a":["b","c"]}"#);
+    /// ```
+    ///
+    /// Like the drop impl it doesn't recurse, so it's safe to call on
+    /// arbitrarily deeply nested values.
+    pub fn into_owned(self) -> Value<'static> {
+        let mut stack = Vec::new();
+        let mut value = self;
+        loop {
+            let mut owned = match value {
+                Value::Null => Some(Value::Null),
+                Value::Bool(b) => Some(Value::Bool(b)),
+                Value::Number(n) => Some(Value::Number(n)),
+                Value::String(s) => Some(Value::String(Cow::Owned(s.into_owned()))),
+                Value::Binary { bytes, align } => Some(Value::Binary {
+                    bytes: Cow::Owned(bytes.into_owned()),
+                    align,
+                }),
+                Value::Array(array) => {
+                    stack.push(Owning::Array(array.into_iter(), Array::new()));
+                    None
+                }
+                Value::Object(object) => {
+                    stack.push(Owning::Object(
+                        object.into_iter(),
+                        Object::new(),
+                        String::new(),
+                    ));
+                    None
+                }
+            };
+
+            // Moves the converted value into its parent and climbs up until
+            // a parent with elements left to convert is found
+            loop {
+                match stack.last_mut() {
+                    None => return owned.unwrap(),
+                    Some(Owning::Array(iter, out)) => {
+                        if let Some(v) = owned.take() {
+                            out.push(v);
+                        }
+                        if let Some(child) = iter.next() {
+                            value = child;
+                            break;
+                        }
+                    }
+                    Some(Owning::Object(iter, out, key)) => {
+                        if let Some(v) = owned.take() {
+                            out.insert(mem::take(key), v);
+                        }
+                        if let Some((k, child)) = iter.next() {
+                            *key = k;
+                            value = child;
+                            break;
+                        }
+                    }
+                }
+                owned = Some(match stack.pop().unwrap() {
+                    Owning::Array(_, out) => Value::Array(out),
+                    Owning::Object(_, out, _) => Value::Object(out),
+                });
+            }
+        }
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: ?Sized + Sealed> Sealed for &T {}
+}
+
+/// Types that can index a `Value`, `usize` for arrays and strings for objects.
+///
+/// This trait is sealed and can't be implemented outside of this crate.
+pub trait ValueIndex: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v, 'a>(&self, v: &'v Value<'a>) -> Option<&'v Value<'a>>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'v, 'a>(&self, v: &'v mut Value<'a>) -> Option<&'v mut Value<'a>>;
+
+    /// Panic message when indexing fails
+    #[doc(hidden)]
+    fn index_panic(&self, v: &Value) -> !;
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v, 'a>(&self, v: &'v Value<'a>) -> Option<&'v Value<'a>> {
+        match v {
+            Value::Array(array) => array.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v, 'a>(&self, v: &'v mut Value<'a>) -> Option<&'v mut Value<'a>> {
+        match v {
+            Value::Array(array) => array.get_mut(*self),
+            _ => None,
+        }
+    }
+
+    fn index_panic(&self, v: &Value) -> ! {
+        match v {
+            Value::Array(array) => panic!(
+                "index {} out of bounds of array with length {}",
+                self,
+                array.len()
+            ),
+            _ => panic!("cannot index into a non array value with {}", self),
+        }
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'v, 'a>(&self, v: &'v Value<'a>) -> Option<&'v Value<'a>> {
+        match v {
+            Value::Object(object) => object.get(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v, 'a>(&self, v: &'v mut Value<'a>) -> Option<&'v mut Value<'a>> {
+        match v {
+            Value::Object(object) => object.get_mut(self),
+            _ => None,
+        }
+    }
+
+    fn index_panic(&self, v: &Value) -> ! {
+        match v {
+            Value::Object(_) => panic!("missing field `{}`", self),
+            _ => panic!("cannot index into a non object value with `{}`", self),
+        }
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v, 'a>(&self, v: &'v Value<'a>) -> Option<&'v Value<'a>> {
+        self[..].index_into(v)
+    }
+
+    fn index_into_mut<'v, 'a>(&self, v: &'v mut Value<'a>) -> Option<&'v mut Value<'a>> {
+        self[..].index_into_mut(v)
+    }
+
+    fn index_panic(&self, v: &Value) -> ! {
+        self[..].index_panic(v)
+    }
+}
+
+impl<T: ?Sized + ValueIndex> ValueIndex for &T {
+    fn index_into<'v, 'a>(&self, v: &'v Value<'a>) -> Option<&'v Value<'a>> {
+        (**self).index_into(v)
+    }
+
+    fn index_into_mut<'v, 'a>(&self, v: &'v mut Value<'a>) -> Option<&'v mut Value<'a>> {
+        (**self).index_into_mut(v)
+    }
+
+    fn index_panic(&self, v: &Value) -> ! {
+        (**self).index_panic(v)
+    }
+}
+
+impl<'a, I: ValueIndex> Index<I> for Value<'a> {
+    type Output = Value<'a>;
+
+    /// Panics if the value isn't an array (for `usize`) or an object (for `&str`),
+    /// or if the element doesn't exist, use `Value::get` to avoid panicking.
+    fn index(&self, index: I) -> &Value<'a> {
+        match index.index_into(self) {
+            Some(v) => v,
+            None => index.index_panic(self),
+        }
+    }
+}
+
+impl<'a, I: ValueIndex> IndexMut<I> for Value<'a> {
+    /// Panics under the same conditions of `Index`, missing fields aren't inserted.
+    fn index_mut(&mut self, index: I) -> &mut Value<'a> {
+        if index.index_into(self).is_none() {
+            index.index_panic(self)
+        }
+        index.index_into_mut(self).unwrap()
+    }
+}
+
+/// Array or object being converted by `Value::into_owned`, holds the remaining
+/// elements and the already converted ones, objects also keep the key of
+/// the element being converted
+enum Owning<'a> {
+    Array(vec::IntoIter<Value<'a>>, Array<'static>),
+    Object(
+        <Object<'a> as IntoIterator>::IntoIter,
+        Object<'static>,
+        String,
+    ),
+}
+
+/// Applies the merge `patch` to `target`, same as `Value::merge_patch`
+pub fn merge_patch<'a>(target: &mut Value<'a>, patch: &Value<'a>) {
+    target.merge_patch(patch)
+}
+
+/// Decodes the `~1` and `~0` escapes of a JSON Pointer token, in that order
+/// so `~01` becomes `~1` and not `/`
+fn unescape_token(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Array indexes are plain decimal numbers without leading zeros
+fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if token.len() > 1 && token.starts_with('0') {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl<'a> From<()> for Value<'a> {
+    /// Unit converts to null.
+    fn from(_: ()) -> Self {
+        Value::Null
+    }
+}
+
+impl<'a> From<bool> for Value<'a> {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+macro_rules! from_number {
+    ($($ty:ty),*) => {
+        $(impl<'a> From<$ty> for Value<'a> {
+            fn from(n: $ty) -> Self {
+                Value::Number(n.into())
+            }
+        })*
+    };
+}
+
+from_number!(u8, u32, u64, i32, i64, f32, f64);
+
+impl<'a> From<String> for Value<'a> {
+    fn from(s: String) -> Self {
+        Value::String(Cow::Owned(s))
+    }
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(s: &'a str) -> Self {
+        Value::String(Cow::Borrowed(s))
+    }
+}
+
+impl<'a> From<Vec<Value<'a>>> for Value<'a> {
+    fn from(vec: Vec<Value<'a>>) -> Self {
+        Value::Array(vec.into_iter().collect())
+    }
+}
+
+impl<'a> From<Array<'a>> for Value<'a> {
+    fn from(array: Array<'a>) -> Self {
+        Value::Array(array)
+    }
+}
+
+impl<'a> From<Object<'a>> for Value<'a> {
+    fn from(object: Object<'a>) -> Self {
+        Value::Object(object)
+    }
+}
+
+impl FromStr for Value<'static> {
+    type Err = Error;
+
+    /// Parses a JSON string into an owned value, the input is copied
+    /// because the deserializer needs to modify it.
+    ///
+    /// ```rust
+    /// use toctoc::json::Value;
+    ///
+    /// let value: Value = r#""hello""#.parse().unwrap();
+    /// assert_eq!(value, Value::from("hello"));
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        let mut copy = s.to_owned();
+        let value: Value = json::from_str(&mut copy, &mut ())?;
+        Ok(value.into_owned())
+    }
+}
+
+/// Zero-copy parsing, `&str` can't be used instead because the
+/// strings are unescaped in place (and `Value` already converts
+/// from `&str` into a string value)
+impl<'a> TryFrom<&'a mut str> for Value<'a> {
+    type Error = Error;
+
+    fn try_from(s: &'a mut str) -> Result<Self> {
+        json::from_str(s, &mut ())
+    }
+}
+
 impl<'a> Serialize for Value<'a> {
     fn begin(&self, v: ser::Visitor, c: &mut dyn ser::Context) -> ser::Done {
         match self {
@@ -110,7 +615,7 @@ impl<'a, 'de: 'a> Deserialize<'de> for Value<'a> {
             }
 
             fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn de::Context) -> Result<()> {
-                let mut array = Array::new();
+                let mut array = Array::with_capacity(de::cautious_capacity::<Value>(s.size_hint()));
                 let mut element: Option<Value> = None;
                 while s.visit(Place::new(&mut element), c)? {
                     element.take().map(|e| array.push(e));
@@ -120,7 +625,8 @@ impl<'a, 'de: 'a> Deserialize<'de> for Value<'a> {
             }
 
             fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn de::Context) -> Result<()> {
-                let mut object = Object::new();
+                let capacity = de::cautious_capacity::<(String, Value)>(m.size_hint());
+                let mut object = Object::with_capacity(capacity);
                 let mut value: Option<Value> = None;
                 while let Some(key) = m.next()? {
                     m.visit(Place::new(&mut value), c)?;
@@ -173,10 +679,390 @@ impl<'de> PartialEq<Value<'de>> for Value<'de> {
     }
 }
 
+/// Deserializes `T` directly from a `Value` tree, without writing it as
+/// JSON and parsing it back.
+///
+/// The `value` is dropped before returning, so `T` can't borrow from it,
+/// use [`ValueDeserializer`] for types with borrowed strings.
+///
+/// ```rust
+/// use toctoc::{json, Deserialize};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Example {
+///     code: u32,
+///     message: String,
+/// }
+///
+/// let value = toctoc::json!({ "code": 200, "message": "Ok" });
+/// let out: Example = json::from_value(value, &mut ()).unwrap();
+/// assert_eq!(out, Example { code: 200, message: "Ok".to_owned() });
+/// ```
+pub fn from_value<T>(value: Value, context: &mut dyn de::Context) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    from_value_with_options(value, context, JsonDeOptions::default())
+}
+
+/// Same as `from_value` but with custom `options`, `from_value` uses
+/// `JsonDeOptions::default()`.
+///
+/// ```rust
+/// use toctoc::json::{self, JsonDeOptions};
+///
+/// let value = toctoc::json!([[1]]);
+///
+/// let options = JsonDeOptions { max_depth: 2 };
+/// let out: toctoc::Result<Vec<Vec<u32>>> =
+///     json::from_value_with_options(value.clone(), &mut (), options);
+/// assert!(out.is_ok());
+///
+/// let options = JsonDeOptions { max_depth: 1 };
+/// let out: toctoc::Result<Vec<Vec<u32>>> = json::from_value_with_options(value, &mut (), options);
+/// assert!(out.is_err());
+/// ```
+pub fn from_value_with_options<T>(
+    value: Value,
+    context: &mut dyn de::Context,
+    options: JsonDeOptions,
+) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let mut out = None;
+    ValueDeserializer::with_options(&value, options).deserialize(T::begin(&mut out), context)?;
+    out.ok_or_else(Error::unknown)
+}
+
+/// Serializes `value` into a `Value` tree, every string and byte slice
+/// is copied.
+///
+/// ```rust
+/// use toctoc::{json, Serialize};
+///
+/// #[derive(Serialize)]
+/// struct Example {
+///     code: u32,
+///     message: String,
+/// }
+///
+/// let example = Example { code: 200, message: "Ok".to_owned() };
+/// let value = json::to_value(&example, &mut ());
+/// assert_eq!(value["code"].as_u64(), Some(200));
+/// assert_eq!(value["message"], json::Value::from("Ok"));
+/// ```
+pub fn to_value<T: Serialize>(value: &T, context: &mut dyn ser::Context) -> Value<'static> {
+    let mut s = ValueSerializer::new();
+    value.begin((&mut s).into(), context);
+    s.into_value()
+}
+
+/// Walks a `Value` tree calling the visitor methods, strings and byte
+/// slices are borrowed from the tree.
+///
+/// ```rust
+/// use toctoc::de::{Deserialize, DeserializerTrait};
+/// use toctoc::json::{Value, ValueDeserializer};
+///
+/// let value = Value::from(vec![Value::from("a"), Value::from("b")]);
+/// let mut out: Option<Vec<&str>> = None;
+/// ValueDeserializer::new(&value)
+///     .deserialize(Deserialize::begin(&mut out), &mut ())
+///     .unwrap();
+/// assert_eq!(out, Some(vec!["a", "b"]));
+/// ```
+pub struct ValueDeserializer<'de> {
+    value: &'de Value<'de>,
+    max_depth: usize,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    pub fn new(value: &'de Value<'de>) -> Self {
+        Self::with_options(value, JsonDeOptions::default())
+    }
+
+    pub fn with_options(value: &'de Value<'de>, options: JsonDeOptions) -> Self {
+        Self {
+            value,
+            max_depth: options.max_depth,
+        }
+    }
+}
+
+impl<'de> DeserializerTrait<'de> for ValueDeserializer<'de> {
+    fn deserialize(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn de::Context) -> Result<()> {
+        let depth = Depth {
+            depth: 0,
+            max_depth: self.max_depth,
+        };
+        visit_value(self.value, v, c, depth)
+    }
+}
+
+/// Nesting depth of the value being visited, to avoid overflowing the stack
+#[derive(Clone, Copy)]
+struct Depth {
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Depth {
+    fn enter_nested(self) -> Result<Self> {
+        if self.depth >= self.max_depth {
+            Err(Error::depth_exceeded(self.max_depth))?
+        }
+        Ok(Self {
+            depth: self.depth + 1,
+            ..self
+        })
+    }
+}
+
+fn visit_value<'de>(
+    value: &'de Value<'de>,
+    v: &mut dyn Visitor<'de>,
+    c: &mut dyn de::Context,
+    depth: Depth,
+) -> Result<()> {
+    match value {
+        Value::Null => v.null(c),
+        Value::Bool(b) => v.boolean(*b),
+        // Nonnegative numbers may also be built as `I64`
+        Value::Number(Number::I64(n)) if *n >= 0 => v.nonnegative(*n as u64, c),
+        Value::Number(Number::I64(n)) => v.negative(*n, c),
+        Value::Number(Number::U64(n)) => v.nonnegative(*n, c),
+        Value::Number(Number::F32(n)) => v.single(*n),
+        Value::Number(Number::F64(n)) => v.double(*n),
+        Value::String(s) => v.string(s, c),
+        Value::Binary { bytes, .. } => v.bytes(bytes, c),
+        Value::Array(array) => v.seq(
+            &mut ValueSeq {
+                iter: array.iter(),
+                depth: depth.enter_nested()?,
+            },
+            c,
+        ),
+        Value::Object(object) => v.map(
+            &mut ValueMap {
+                iter: object.into_iter(),
+                value: None,
+                depth: depth.enter_nested()?,
+            },
+            c,
+        ),
+    }
+}
+
+struct ValueSeq<'de> {
+    iter: std::slice::Iter<'de, Value<'de>>,
+    depth: Depth,
+}
+
+impl<'de> Seq<'de> for ValueSeq<'de> {
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn de::Context) -> Result<bool> {
+        match self.iter.next() {
+            Some(e) => {
+                visit_value(e, v, c, self.depth)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn de::Context) -> Result<()> {
+        self.iter.by_ref().for_each(drop);
+        Ok(())
+    }
+}
+
+struct ValueMap<'de> {
+    iter: <&'de Object<'de> as IntoIterator>::IntoIter,
+    value: Option<&'de Value<'de>>,
+    depth: Depth,
+}
+
+impl<'de> Map<'de> for ValueMap<'de> {
+    fn next(&mut self) -> Result<Option<&'de str>> {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.value = Some(v);
+                Ok(Some(k.as_str()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn de::Context) -> Result<()> {
+        match self.value.take() {
+            Some(value) => visit_value(value, v, c, self.depth),
+            None => Err(Error::expecting("map key"))?,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn de::Context) -> Result<()> {
+        self.iter.by_ref().for_each(drop);
+        self.value = None;
+        Ok(())
+    }
+}
+
+/// Builds a `Value` tree from the serialized fragments.
+///
+/// ```rust
+/// use toctoc::ser::Serialize;
+/// use toctoc::json::{Value, ValueSerializer};
+///
+/// let mut s = ValueSerializer::new();
+/// vec![1u32, 2].begin((&mut s).into(), &mut ());
+/// assert_eq!(s.into_value(), Value::from(vec![Value::from(1u32), Value::from(2u32)]));
+/// ```
+pub struct ValueSerializer {
+    value: Value<'static>,
+    /// Arrays and objects not done yet
+    stack: Vec<Building>,
+}
+
+/// Array or object being built by `ValueSerializer`, objects also keep
+/// the key of the field being written
+enum Building {
+    Array(Array<'static>),
+    Object(Object<'static>, String),
+}
+
+impl ValueSerializer {
+    pub fn new() -> Self {
+        Self {
+            value: Value::Null,
+            stack: vec![],
+        }
+    }
+
+    /// Returns the serialized value
+    pub fn into_value(self) -> Value<'static> {
+        self.value
+    }
+
+    /// Puts `value` in the array or object being built, or as the
+    /// result when there's none
+    fn put(&mut self, value: Value<'static>) {
+        match self.stack.last_mut() {
+            Some(Building::Array(array)) => array.push(value),
+            Some(Building::Object(object, key)) => {
+                object.insert(mem::take(key), value);
+            }
+            None => self.value = value,
+        }
+    }
+}
+
+impl Default for ValueSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ser::VisitorTrait for ValueSerializer {
+    fn null(&mut self) {
+        self.put(Value::Null)
+    }
+
+    fn boolean(&mut self, b: bool) {
+        self.put(Value::Bool(b))
+    }
+
+    fn string(&mut self, s: &str) {
+        self.put(Value::String(Cow::Owned(s.to_owned())))
+    }
+
+    fn long(&mut self, n: i64) {
+        if n >= 0 {
+            // Same as the deserializers, nonnegative numbers are always `U64`
+            self.ulong(n as u64)
+        } else {
+            self.put(Value::Number(Number::I64(n)))
+        }
+    }
+
+    fn ulong(&mut self, n: u64) {
+        self.put(Value::Number(Number::U64(n)))
+    }
+
+    fn single(&mut self, n: f32) {
+        self.put(Value::Number(Number::F32(n)))
+    }
+
+    fn double(&mut self, n: f64) {
+        self.put(Value::Number(Number::F64(n)))
+    }
+
+    fn bytes(&mut self, b: &[u8], align: usize) {
+        self.put(Value::Binary {
+            bytes: Cow::Owned(b.to_vec()),
+            align,
+        })
+    }
+
+    #[cfg(feature = "base64")]
+    fn base64(&mut self, b: &[u8], _: usize, config: base64::Config) {
+        self.string(&base64::encode_config(b, config))
+    }
+
+    fn seq(&mut self) -> &mut dyn ser::SeqTrait {
+        self.stack.push(Building::Array(Array::new()));
+        self
+    }
+
+    fn map(&mut self) -> &mut dyn ser::MapTrait {
+        self.stack
+            .push(Building::Object(Object::new(), String::new()));
+        self
+    }
+}
+
+impl ser::SeqTrait for ValueSerializer {
+    fn element(&mut self, s: &dyn Serialize, c: &mut dyn ser::Context) {
+        s.begin(self.into(), c);
+    }
+
+    fn done(&mut self) {
+        match self.stack.pop() {
+            Some(Building::Array(array)) => self.put(Value::Array(array)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl ser::MapTrait for ValueSerializer {
+    fn field(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn ser::Context) {
+        if let Some(Building::Object(_, key)) = self.stack.last_mut() {
+            key.push_str(k);
+        }
+        s.begin(self.into(), c);
+    }
+
+    fn done(&mut self) {
+        match self.stack.pop() {
+            Some(Building::Object(object, _)) => self.put(Value::Object(object)),
+            _ => unreachable!(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ErrorKind;
     use crate::json;
+    use std::collections::BTreeMap;
 
     #[test]
     fn many_cases() {
@@ -214,4 +1100,513 @@ mod tests {
             assert_eq!(json, &actual);
         }
     }
+
+    #[test]
+    fn from_primitives() {
+        let explicit = Value::Array({
+            let mut array = Array::new();
+            array.push(Value::Null);
+            array.push(Value::Bool(true));
+            array.push(Value::Number(Number::U64(1)));
+            array.push(Value::Number(Number::I64(-1)));
+            array.push(Value::Number(Number::F64(0.5)));
+            array.push(Value::Object({
+                let mut object = Object::new();
+                object.insert("a".to_string(), Value::String(Cow::Borrowed("b")));
+                object.insert("c".to_string(), Value::String(Cow::Owned("d".to_string())));
+                object
+            }));
+            array
+        });
+
+        let converted = Value::Array({
+            let mut array = Array::new();
+            array.push(().into());
+            array.push(true.into());
+            array.push(1u64.into());
+            array.push((-1i64).into());
+            array.push(0.5f64.into());
+            array.push(Value::Object({
+                let mut object = Object::new();
+                object.insert("a".to_string(), "b".into());
+                object.insert("c".to_string(), "d".to_string().into());
+                object
+            }));
+            array
+        });
+
+        assert_eq!(explicit, converted);
+    }
+
+    #[test]
+    fn from_conversions() {
+        assert_eq!(Value::from(()), Value::Null);
+        assert_eq!(Value::from(true), Value::Bool(true));
+        assert_eq!(Value::from(7u8), Value::Number(Number::U64(7)));
+        assert_eq!(Value::from(7u32), Value::Number(Number::U64(7)));
+        assert_eq!(Value::from(7u64), Value::Number(Number::U64(7)));
+        assert_eq!(Value::from(-7i32), Value::Number(Number::I64(-7)));
+        assert_eq!(Value::from(-7i64), Value::Number(Number::I64(-7)));
+        assert_eq!(Value::from(0.5f32), Value::Number(Number::F32(0.5)));
+        assert_eq!(Value::from(0.5f64), Value::Number(Number::F64(0.5)));
+
+        assert!(matches!(
+            Value::from("a"),
+            Value::String(Cow::Borrowed("a"))
+        ));
+        assert!(matches!(
+            Value::from("a".to_string()),
+            Value::String(Cow::Owned(ref s)) if s == "a"
+        ));
+
+        let array = Value::from(vec![Value::from(1u64), Value::Null]);
+        assert_eq!(json::to_string(&array, &mut ()), "[1,null]");
+        assert_eq!(
+            Value::from(vec![Value::Null].into_iter().collect::<Array>()),
+            Value::from(vec![Value::Null])
+        );
+
+        let mut object = Object::new();
+        object.insert("a".to_string(), Value::from(false));
+        assert_eq!(
+            json::to_string(&Value::from(object), &mut ()),
+            r#"{"a":false}"#
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut object = Object::new();
+        object.insert("k\"ey".to_string(), "ünicode ✓".into());
+        object.insert(
+            "a".to_string(),
+            vec![Value::from(-1i64), Value::from(0.5f64), Value::Null].into(),
+        );
+        let cases = vec![
+            Value::Null,
+            Value::Bool(false),
+            Value::from(u64::max_value()),
+            Value::from("line\nbreak"),
+            Value::from(vec![]),
+            Value::from(object),
+        ];
+        for value in &cases {
+            assert_eq!(format!("{}", value), json::to_string(value, &mut ()));
+            assert_eq!(format!("{:?}", value), json::to_string(value, &mut ()));
+        }
+
+        // Big enough to be written in many chunks
+        let big = Value::from((0..10_000).map(|_| "ünicode ✓".into()).collect::<Vec<_>>());
+        assert_eq!(format!("{}", big), json::to_string(&big, &mut ()));
+    }
+
+    #[test]
+    fn parse() {
+        let value: Value<'static> = r#"{"a":["b\n",1]}"#.parse().unwrap();
+        assert!(matches!(value["a"][0], Value::String(Cow::Owned(ref s)) if s == "b\n"));
+        assert_eq!(value["a"][1], Value::from(1u64));
+
+        let err = "[1,".parse::<Value>().unwrap_err();
+        let mut copy = "[1,".to_string();
+        let expected = json::from_str::<Value>(&mut copy, &mut ()).unwrap_err();
+        assert_eq!(err.to_string(), expected.to_string());
+
+        let mut json = r#"["a"]"#.to_string();
+        let value = Value::try_from(&mut json[..]).unwrap();
+        assert!(matches!(value[0], Value::String(Cow::Borrowed("a"))));
+
+        let mut json = "nul".to_string();
+        assert!(Value::try_from(&mut json[..]).is_err());
+    }
+
+    #[test]
+    fn object_into_iter() {
+        let mut json = r#"{"a":1,"b":[true],"c":{"d":null}}"#.to_string();
+        let value: Value = json::from_str(&mut json, &mut ()).unwrap();
+        let object = match value {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        };
+
+        let map: BTreeMap<String, Value> = object.into_iter().collect();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["a"], Value::Number(Number::U64(1)));
+        assert_eq!(
+            map["b"],
+            Value::Array(vec![Value::Bool(true)].into_iter().collect())
+        );
+        assert_eq!(
+            map["c"],
+            Value::Object(vec![("d".to_string(), Value::Null)].into_iter().collect())
+        );
+    }
+
+    #[test]
+    fn object_order() {
+        let mut json = r#"{"c":3,"a":2,"b":1}"#.to_string();
+        let mut value: Value = json::from_str(&mut json, &mut ()).unwrap();
+        if let Value::Object(object) = &mut value {
+            object.remove("a");
+        }
+
+        #[cfg(feature = "preserve-order")]
+        assert_eq!(json::to_string(&value, &mut ()), r#"{"c":3,"b":1}"#);
+        #[cfg(not(feature = "preserve-order"))]
+        assert_eq!(json::to_string(&value, &mut ()), r#"{"b":1,"c":3}"#);
+
+        // Equality never depends on the order of the keys
+        let mut json = r#"{"b":1,"c":3}"#.to_string();
+        let other: Value = json::from_str(&mut json, &mut ()).unwrap();
+        assert_eq!(value, other);
+    }
+
+    #[test]
+    fn array_index() {
+        let mut array = Array::new();
+        array.push(Value::Bool(true));
+        array.push(Value::Null);
+        assert_eq!(array[0], Value::Bool(true));
+
+        array[1] = Value::Number(Number::U64(2));
+        assert_eq!(array.pop(), Some(Value::Number(Number::U64(2))));
+        assert_eq!(array.pop(), Some(Value::Bool(true)));
+        assert_eq!(array.pop(), None);
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut array = Array::with_capacity(10);
+        assert!(array.capacity() >= 10);
+        assert!(array.is_empty());
+
+        array.push(Value::Null);
+        array.reserve(100);
+        assert!(array.capacity() >= 101);
+
+        let object = Object::with_capacity(10);
+        assert!(object.is_empty());
+    }
+
+    #[test]
+    fn number_accessors() {
+        let u = Value::Number(Number::U64(u64::max_value()));
+        assert_eq!(u.as_number(), Some(&Number::U64(u64::max_value())));
+        assert_eq!(u.as_u64(), Some(u64::max_value()));
+        assert_eq!(u.as_i64(), None);
+        assert_eq!(u.as_f64_lossy(), Some(u64::max_value() as f64));
+
+        let i = Value::Number(Number::I64(-2));
+        assert_eq!(i.as_u64(), None);
+        assert_eq!(i.as_i64(), Some(-2));
+        assert_eq!(i.as_f64_lossy(), Some(-2.0));
+
+        let p = Value::Number(Number::I64(2));
+        assert_eq!(p.as_u64(), Some(2));
+        assert_eq!(p.as_i64(), Some(2));
+
+        let s = Value::Number(Number::F32(0.5));
+        assert_eq!(s.as_u64(), None);
+        assert_eq!(s.as_i64(), None);
+        assert_eq!(s.as_f64_lossy(), Some(0.5));
+
+        let d = Value::Number(Number::F64(-1.5));
+        assert_eq!(d.as_u64(), None);
+        assert_eq!(d.as_i64(), None);
+        assert_eq!(d.as_f64_lossy(), Some(-1.5));
+
+        let n = Value::Null;
+        assert_eq!(n.as_number(), None);
+        assert_eq!(n.as_u64(), None);
+        assert_eq!(n.as_i64(), None);
+        assert_eq!(n.as_f64_lossy(), None);
+    }
+
+    #[test]
+    fn pointer() {
+        // Examples from RFC 6901, section 5
+        let mut json = r#"{
+            "foo": ["bar", "baz"],
+            "": 0,
+            "a/b": 1,
+            "c%d": 2,
+            "e^f": 3,
+            "g|h": 4,
+            "i\\j": 5,
+            "k\"l": 6,
+            " ": 7,
+            "m~n": 8
+        }"#
+        .to_string();
+        let value: Value = json::from_str(&mut json, &mut ()).unwrap();
+
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(
+            value.pointer("/foo"),
+            Some(&Value::Array(
+                vec!["bar".into(), "baz".into()].into_iter().collect()
+            ))
+        );
+        assert_eq!(value.pointer("/foo/0"), Some(&"bar".into()));
+        let cases = &[
+            ("/", 0),
+            ("/a~1b", 1),
+            ("/c%d", 2),
+            ("/e^f", 3),
+            ("/g|h", 4),
+            ("/i\\j", 5),
+            ("/k\"l", 6),
+            ("/ ", 7),
+            ("/m~0n", 8),
+        ];
+        for (ptr, n) in cases {
+            assert_eq!(
+                value.pointer(ptr).and_then(Value::as_u64),
+                Some(*n),
+                "{}",
+                ptr
+            );
+        }
+
+        assert_eq!(value.pointer("foo"), None);
+        assert_eq!(value.pointer("/foo/2"), None);
+        assert_eq!(value.pointer("/foo/01"), None);
+        assert_eq!(value.pointer("/foo/-"), None);
+        assert_eq!(value.pointer("/foo/0/bar"), None);
+        assert_eq!(value.pointer("/m~01n"), None);
+
+        let default = Value::Null;
+        assert_eq!(value.pointer_or("/missing", &default), &Value::Null);
+        assert_eq!(value.pointer_or("/foo/1", &default), &"baz".into());
+
+        let mut value = value;
+        *value.pointer_mut("/foo/1").unwrap() = Value::Bool(true);
+        assert_eq!(value.pointer("/foo/1"), Some(&Value::Bool(true)));
+        assert_eq!(value.pointer_mut("/foo/3"), None);
+    }
+
+    #[test]
+    fn merge_patch() {
+        // Examples from RFC 7396, section 3 and appendix A
+        let cases = &[
+            (
+                r#"{
+                    "title": "Goodbye!",
+                    "author": { "givenName": "John", "familyName": "Doe" },
+                    "tags": ["example", "sample"],
+                    "content": "This will be unchanged"
+                }"#,
+                r#"{
+                    "title": "Hello!",
+                    "phoneNumber": "+01-123-456-7890",
+                    "author": { "familyName": null },
+                    "tags": ["example"]
+                }"#,
+                r#"{
+                    "title": "Hello!",
+                    "author": { "givenName": "John" },
+                    "tags": ["example"],
+                    "content": "This will be unchanged",
+                    "phoneNumber": "+01-123-456-7890"
+                }"#,
+            ),
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (
+                r#"{}"#,
+                r#"{"a":{"bb":{"ccc":null}}}"#,
+                r#"{"a":{"bb":{}}}"#,
+            ),
+        ];
+
+        for (target, patch, expected) in cases {
+            let mut patch = patch.to_string();
+            let patch: Value = json::from_str(&mut patch, &mut ()).unwrap();
+            let mut target = target.to_string();
+            let mut target: Value = json::from_str(&mut target, &mut ()).unwrap();
+            let mut expected = expected.to_string();
+            let expected: Value = json::from_str(&mut expected, &mut ()).unwrap();
+
+            json::merge_patch(&mut target, &patch);
+            assert_eq!(target, expected);
+        }
+    }
+
+    #[test]
+    fn index() {
+        let mut json = r#"{"a":[1,{"b":null}],"c":"d"}"#.to_string();
+        let mut value: Value = json::from_str(&mut json, &mut ()).unwrap();
+
+        assert_eq!(value["a"][0], Value::Number(Number::U64(1)));
+        assert_eq!(value["a"][1]["b"], Value::Null);
+        assert_eq!(value[&"c".to_string()], "d".into());
+        assert_eq!(value.get("a").and_then(|a| a.get(1)), value.pointer("/a/1"));
+        assert_eq!(value.get("x"), None);
+        assert_eq!(value.get(0), None);
+        assert_eq!(value["a"].get(2), None);
+        assert_eq!(value["a"].get("b"), None);
+
+        value["a"][1]["b"] = Value::Bool(true);
+        *value.get_mut("c").unwrap() = Value::Null;
+        assert_eq!(
+            json::to_string(&value, &mut ()),
+            r#"{"a":[1,{"b":true}],"c":null}"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "missing field `x`")]
+    fn index_missing_field() {
+        let mut object = Object::new();
+        object.insert("a".to_string(), Value::Null);
+        let _ = &Value::Object(object)["x"];
+    }
+
+    #[test]
+    #[should_panic(expected = "index 1 out of bounds of array with length 1")]
+    fn index_out_of_bounds() {
+        let mut array = Array::new();
+        array.push(Value::Null);
+        let _ = &Value::Array(array)[1];
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index into a non array value with 0")]
+    fn index_wrong_type() {
+        let _ = &Value::Null[0];
+    }
+
+    #[test]
+    fn to_from_value() {
+        let mut json = r##"{"a":[1,-2,0.5,null,true],"b":{"c":"d"},"e":"#--0102"}"##.to_string();
+        let value: Value = json::from_str(&mut json, &mut ()).unwrap();
+        assert_eq!(json::to_value(&value, &mut ()), value);
+
+        // Strings are borrowed from the tree
+        let mut out: Option<Value> = None;
+        ValueDeserializer::new(&value)
+            .deserialize(Deserialize::begin(&mut out), &mut ())
+            .unwrap();
+        let out = out.unwrap();
+        assert_eq!(out, value);
+        assert!(matches!(
+            out.pointer("/b/c"),
+            Some(Value::String(Cow::Borrowed("d")))
+        ));
+
+        let map: BTreeMap<String, Vec<i64>> =
+            vec![("a".to_string(), vec![1, -2])].into_iter().collect();
+        let value = json::to_value(&map, &mut ());
+        assert_eq!(json::to_string(&value, &mut ()), r#"{"a":[1,-2]}"#);
+        let actual: BTreeMap<String, Vec<i64>> = json::from_value(value, &mut ()).unwrap();
+        assert_eq!(actual, map);
+
+        // Nonnegative numbers are always `U64`, but `I64` ones are accepted too
+        assert_eq!(json::to_value(&5i64, &mut ()), Value::from(5u64));
+        let n: u32 = json::from_value(Value::Number(Number::I64(7)), &mut ()).unwrap();
+        assert_eq!(n, 7);
+        assert!(json::from_value::<u32>(Value::from("7"), &mut ()).is_err());
+        let none: Option<String> = json::from_value(Value::Null, &mut ()).unwrap();
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn from_value_depth() {
+        let nested = |depth| {
+            let mut value = Value::Null;
+            for _ in 0..depth {
+                value = Value::from(vec![value]);
+            }
+            value
+        };
+        let visit = |value: &Value, options| {
+            let mut out: Option<Value> = None;
+            ValueDeserializer::with_options(value, options)
+                .deserialize(Deserialize::begin(&mut out), &mut ())
+                .map(|_| ())
+        };
+
+        let options = JsonDeOptions::default();
+        assert!(visit(&nested(128), options).is_ok());
+        let err = visit(&nested(200), options).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::DepthExceeded(128)));
+
+        let options = JsonDeOptions { max_depth: 10 };
+        assert!(visit(&nested(10), options).is_ok());
+        let err = visit(&nested(11), options).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::DepthExceeded(10)));
+    }
+
+    #[test]
+    fn into_owned() {
+        let owned: Value<'static> = {
+            let mut json = r##"{"a":["b",{"c":"d"}],"e":"#--0102"}"##.to_string();
+            let value: Value = json::from_str(&mut json, &mut ()).unwrap();
+            assert!(matches!(
+                value.pointer("/a/0"),
+                Some(Value::String(Cow::Borrowed(_)))
+            ));
+            value.into_owned()
+        };
+        assert!(matches!(
+            owned.pointer("/a/0"),
+            Some(Value::String(Cow::Owned(_)))
+        ));
+        assert!(matches!(
+            owned.pointer("/a/1/c"),
+            Some(Value::String(Cow::Owned(_)))
+        ));
+        assert!(matches!(
+            owned.pointer("/e"),
+            Some(Value::Binary {
+                bytes: Cow::Owned(_),
+                ..
+            })
+        ));
+        assert_eq!(
+            json::to_string(&owned, &mut ()),
+            r##"{"a":["b",{"c":"d"}],"e":"#--0102"}"##
+        );
+
+        let mut value = Value::String(Cow::Borrowed("a"));
+        for i in 0..100_000 {
+            if i % 2 == 0 {
+                let mut array = Array::new();
+                array.push(value);
+                value = Value::Array(array);
+            } else {
+                let mut object = Object::new();
+                object.insert("k".to_string(), value);
+                value = Value::Object(object);
+            }
+        }
+        let owned = value.into_owned();
+        let mut depth = 0;
+        let mut node = &owned;
+        loop {
+            node = match node {
+                Value::Array(array) => &array[0],
+                Value::Object(object) => &object["k"],
+                _ => break,
+            };
+            depth += 1;
+        }
+        assert_eq!(depth, 100_000);
+        assert!(matches!(node, Value::String(Cow::Owned(_))));
+    }
 }
@@ -81,15 +81,19 @@ mod ignore;
 mod owned;
 
 pub mod bson;
+#[cfg(feature = "cbor")]
+pub mod cbor;
 pub mod de;
 pub mod json;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
 pub mod ser;
 
 #[doc(inline)]
 pub use crate::de::Deserialize;
 #[doc(inline)]
 pub use crate::de::Deserializer;
-pub use crate::error::{Error, Result};
+pub use crate::error::{Error, ErrorKind, Result};
 #[doc(inline)]
 pub use crate::ser::Serialize;
 #[doc(inline)]
@@ -0,0 +1,412 @@
+use paste::paste;
+use std::str;
+
+use crate::de::{Context, Deserialize, DeserializerTrait, Map, Seq, Visitor};
+use crate::error::{Error, Result};
+use crate::msgpack::MsgpackDeOptions;
+
+/// Deserialize a MessagePack byte slice into any deserializable type.
+///
+/// ```rust
+/// use toctoc::{msgpack, Deserialize};
+///
+/// #[derive(Deserialize, Debug)]
+/// struct Example {
+///     code: u32,
+///     message: String,
+/// }
+///
+/// fn main() -> toctoc::Result<()> {
+///     let b = b"\x82\xa4code\xcc\xc8\xa7message\xa2Ok";
+///     let out: Example = msgpack::from_bin(b, &mut ())?;
+///     println!("{:?}", out);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn from_bin<'de, T: Deserialize<'de>>(b: &'de [u8], ctx: &mut dyn Context) -> Result<T> {
+    let mut out = None;
+    MsgpackDe::new(b).deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
+/// Deserialize a MessagePack byte slice with custom `options`.
+///
+/// ```rust
+/// use toctoc::msgpack::{self, MsgpackDeOptions};
+///
+/// let options = MsgpackDeOptions { max_depth: 2 };
+///
+/// let b = msgpack::to_bin(&vec![vec![1u32]], &mut ());
+/// let out: toctoc::Result<Vec<Vec<u32>>> = msgpack::from_bin_with_options(&b, &mut (), options);
+/// assert!(out.is_ok());
+///
+/// let b = msgpack::to_bin(&vec![vec![vec![1u32]]], &mut ());
+/// let out: toctoc::Result<Vec<Vec<Vec<u32>>>> =
+///     msgpack::from_bin_with_options(&b, &mut (), options);
+/// assert!(out.is_err());
+/// ```
+pub fn from_bin_with_options<'de, T: Deserialize<'de>>(
+    b: &'de [u8],
+    ctx: &mut dyn Context,
+    options: MsgpackDeOptions,
+) -> Result<T> {
+    let mut out = None;
+    let mut de = MsgpackDe::new(b);
+    de.max_depth = options.max_depth;
+    de.deserialize(T::begin(&mut out), ctx)?;
+    out.ok_or_else(Error::unknown)
+}
+
+pub struct MsgpackDe<'de> {
+    /// Remaining buffer slice
+    buffer: &'de [u8],
+    /// Byte index, used to report errors
+    index: usize,
+    /// Maximum number of nested arrays and maps allowed
+    max_depth: usize,
+    /// Current number of nested arrays and maps
+    depth: usize,
+}
+
+macro_rules! read_byte_impl {
+    ($($t:ty),*) => {
+        $(paste! {
+            fn [<read_ $t>] (&mut self) -> Result<$t> {
+                let mut a = [0; std::mem::size_of::<$t>()];
+                a.copy_from_slice(self.read_bytes(std::mem::size_of::<$t>())?);
+                Ok($t::from_be_bytes(a))
+            }
+        })*
+    };
+}
+
+/// Provides various functions to read bytes from the inner buffer
+/// and interpreting as big endian bytes many primitive types
+impl<'de> MsgpackDe<'de> {
+    pub fn new(buffer: &'de [u8]) -> Self {
+        Self {
+            buffer,
+            index: 0,
+            max_depth: MsgpackDeOptions::default().max_depth,
+            depth: 0,
+        }
+    }
+
+    /// Begin deserialization
+    fn begin(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        self.visit(v, c)?;
+
+        // All input was consumed
+        if !self.buffer.is_empty() {
+            Err(err!("buffer has {} bytes left", self.buffer.len()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        let marker = self.read_u8()?;
+        match marker {
+            0x00..=0x7F => v.nonnegative(marker as u64, c)?,
+            0x80..=0x8F => self.visit_map((marker & 0x0F) as usize, v, c)?,
+            0x90..=0x9F => self.visit_seq((marker & 0x0F) as usize, v, c)?,
+            0xA0..=0xBF => {
+                let s = self.read_str((marker & 0x1F) as usize)?;
+                v.string(s, c)?;
+            }
+            0xC0 => v.null(c)?,
+            0xC2 => v.boolean(false)?,
+            0xC3 => v.boolean(true)?,
+            0xC4..=0xC6 => {
+                // Binary
+                let size = self.read_len(marker - 0xC4)?;
+                let b = self.read_bytes(size)?;
+                v.bytes(b, c)?;
+            }
+            0xCA => {
+                let n = self.read_f32()?;
+                v.single(n)?;
+            }
+            0xCB => {
+                let n = self.read_f64()?;
+                v.double(n)?;
+            }
+            0xCC => {
+                let n = self.read_u8()?;
+                v.nonnegative(n as u64, c)?;
+            }
+            0xCD => {
+                let n = self.read_u16()?;
+                v.nonnegative(n as u64, c)?;
+            }
+            0xCE => {
+                let n = self.read_u32()?;
+                v.nonnegative_u32(n, c)?;
+            }
+            0xCF => {
+                let n = self.read_u64()?;
+                v.nonnegative(n, c)?;
+            }
+            // Other encoders may write non negative numbers with signed markers
+            0xD0 => {
+                let n = self.read_i8()?;
+                signed(n as i64, v, c)?;
+            }
+            0xD1 => {
+                let n = self.read_i16()?;
+                signed(n as i64, v, c)?;
+            }
+            0xD2 => {
+                let n = self.read_i32()?;
+                if n < 0 {
+                    v.negative_i32(n, c)?;
+                } else {
+                    v.nonnegative_u32(n as u32, c)?;
+                }
+            }
+            0xD3 => {
+                let n = self.read_i64()?;
+                signed(n, v, c)?;
+            }
+            0xD9..=0xDB => {
+                // Utf8 String
+                let size = self.read_len(marker - 0xD9)?;
+                let s = self.read_str(size)?;
+                v.string(s, c)?;
+            }
+            0xDC => {
+                let len = self.read_u16()? as usize;
+                self.visit_seq(len, v, c)?;
+            }
+            0xDD => {
+                let len = self.read_u32()? as usize;
+                self.visit_seq(len, v, c)?;
+            }
+            0xDE => {
+                let len = self.read_u16()? as usize;
+                self.visit_map(len, v, c)?;
+            }
+            0xDF => {
+                let len = self.read_u32()? as usize;
+                self.visit_map(len, v, c)?;
+            }
+            0xE0..=0xFF => v.negative(marker as i8 as i64, c)?,
+            0xC7..=0xC9 | 0xD4..=0xD8 => Err(err!("extension types aren't supported"))?,
+            marker => Err(err!("unknown marker ({})", marker))?,
+        }
+        Ok(())
+    }
+
+    fn visit_seq(
+        &mut self,
+        len: usize,
+        v: &mut dyn Visitor<'de>,
+        c: &mut dyn Context,
+    ) -> Result<()> {
+        self.enter()?;
+        let mut stack = Stack::new(len, self);
+        v.seq(&mut stack, c)?;
+        let remaining = stack.remaining;
+        self.depth -= 1;
+        // No matter what skip the entire array
+        self.skip_values(remaining)
+    }
+
+    fn visit_map(
+        &mut self,
+        len: usize,
+        v: &mut dyn Visitor<'de>,
+        c: &mut dyn Context,
+    ) -> Result<()> {
+        self.enter()?;
+        // Keys and values are counted apart
+        let mut stack = Stack::new(len.saturating_mul(2), self);
+        v.map(&mut stack, c)?;
+        let remaining = stack.remaining;
+        self.depth -= 1;
+        // No matter what skip the entire map
+        self.skip_values(remaining)
+    }
+
+    /// Keeps track of the nesting depth to avoid overflowing the stack
+    #[inline(always)]
+    fn enter(&mut self) -> Result<()> {
+        if self.depth >= self.max_depth {
+            Err(Error::depth_exceeded(self.max_depth))?
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Skips `n` values without recursion, nested arrays and maps just
+    /// add their elements to the number of values left to skip
+    fn skip_values(&mut self, mut n: usize) -> Result<()> {
+        while n > 0 {
+            n -= 1;
+            let marker = self.read_u8()?;
+            let (size, values) = match marker {
+                0x00..=0x7F | 0xC0 | 0xC2 | 0xC3 | 0xE0..=0xFF => (0, 0),
+                0x80..=0x8F => (0, (marker & 0x0F) as usize * 2),
+                0x90..=0x9F => (0, (marker & 0x0F) as usize),
+                0xA0..=0xBF => ((marker & 0x1F) as usize, 0),
+                0xC4..=0xC6 => (self.read_len(marker - 0xC4)?, 0),
+                0xD9..=0xDB => (self.read_len(marker - 0xD9)?, 0),
+                0xCC | 0xD0 => (1, 0),
+                0xCD | 0xD1 => (2, 0),
+                0xCA | 0xCE | 0xD2 => (4, 0),
+                0xCB | 0xCF | 0xD3 => (8, 0),
+                0xDC => (0, self.read_u16()? as usize),
+                0xDD => (0, self.read_u32()? as usize),
+                0xDE => (0, (self.read_u16()? as usize) * 2),
+                0xDF => (0, (self.read_u32()? as usize).saturating_mul(2)),
+                0xC7..=0xC9 | 0xD4..=0xD8 => Err(err!("extension types aren't supported"))?,
+                marker => Err(err!("unknown marker ({})", marker))?,
+            };
+            self.read_bytes(size)?;
+            n = n.saturating_add(values);
+        }
+        Ok(())
+    }
+
+    read_byte_impl!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+    /// Reads a 8, 16 or 32 bits length, the `width` is given by the marker offset
+    fn read_len(&mut self, width: u8) -> Result<usize> {
+        Ok(match width {
+            0 => self.read_u8()? as usize,
+            1 => self.read_u16()? as usize,
+            _ => self.read_u32()? as usize,
+        })
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'de [u8]> {
+        if length <= self.buffer.len() {
+            let (buf, rem) = self.buffer.split_at(length);
+            self.buffer = rem;
+            self.index += length;
+            Ok(buf)
+        } else {
+            Err(err!(
+                "expected to read {} bytes but buffer only have {} left",
+                length,
+                self.buffer.len()
+            ))
+        }
+    }
+
+    fn read_str(&mut self, length: usize) -> Result<&'de str> {
+        let bytes = self.read_bytes(length)?;
+        if !faster_utf8_validator::validate(bytes) {
+            Err(Error::invalid_utf8())?
+        }
+        Ok(unsafe { str::from_utf8_unchecked(bytes) })
+    }
+
+    /// Reads a map key, only strings are supported
+    fn read_key(&mut self) -> Result<&'de str> {
+        let marker = self.read_u8()?;
+        let size = match marker {
+            0xA0..=0xBF => (marker & 0x1F) as usize,
+            0xD9..=0xDB => self.read_len(marker - 0xD9)?,
+            _ => Err(err!("map keys must be strings"))?,
+        };
+        self.read_str(size)
+    }
+}
+
+fn signed<'de>(n: i64, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+    if n < 0 {
+        v.negative(n, c)
+    } else {
+        v.nonnegative(n as u64, c)
+    }
+}
+
+struct Stack<'a, 'de: 'de> {
+    /// Number of values left, keys count as values
+    remaining: usize,
+    de: &'a mut MsgpackDe<'de>,
+    /// Last key or element index visited, used to annotate errors
+    key: &'de str,
+    count: usize,
+}
+
+impl<'a, 'de: 'de> Stack<'a, 'de> {
+    fn new(remaining: usize, de: &'a mut MsgpackDe<'de>) -> Self {
+        Self {
+            remaining,
+            de,
+            key: "",
+            count: 0,
+        }
+    }
+
+    /// Every value takes at least one byte, which bounds the hint of corrupted inputs
+    fn hint(&self, values: usize) -> (usize, Option<usize>) {
+        let n = values.min(self.de.buffer.len());
+        (n, Some(n))
+    }
+}
+
+impl<'a, 'de: 'de> Seq<'de> for Stack<'a, 'de> {
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<bool> {
+        if self.remaining > 0 {
+            let index = self.count;
+            self.count += 1;
+            self.remaining -= 1;
+            self.de
+                .visit(v, c)
+                .map_err(|e| e.append_path_index(index))?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.hint(self.remaining)
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        let remaining = std::mem::take(&mut self.remaining);
+        self.de.skip_values(remaining)
+    }
+}
+
+impl<'a, 'de: 'de> Map<'de> for Stack<'a, 'de> {
+    fn next(&mut self) -> Result<Option<&'de str>> {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.key = self.de.read_key()?;
+            Ok(Some(self.key))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn visit(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        if self.remaining == 0 {
+            Err(err!("map value visited without its key"))?
+        }
+        self.remaining -= 1;
+        let key = self.key;
+        self.de.visit(v, c).map_err(|e| e.append_path_segment(key))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.hint(self.remaining / 2)
+    }
+
+    fn ignore_remaining(&mut self, _: &mut dyn Context) -> Result<()> {
+        let remaining = std::mem::take(&mut self.remaining);
+        self.de.skip_values(remaining)
+    }
+}
+
+impl<'de> DeserializerTrait<'de> for MsgpackDe<'de> {
+    fn deserialize(&mut self, v: &mut dyn Visitor<'de>, c: &mut dyn Context) -> Result<()> {
+        self.begin(v, c)
+            .map_err(|e| e.append_line_and_column(0, self.index))
+    }
+}
@@ -0,0 +1,21 @@
+//! [MessagePack](https://msgpack.org/) data format.
+//!
+//! Values are written in their most compact form, like `rmp` does, so
+//! `{"a":[1,-1]}` is serialized as:
+//!
+//! ```text
+//! \x81 \xa1 a \x92 \x01 \xff
+//! ```
+//!
+//! Map keys are always strings and extension types aren't supported.
+//!
+
+mod ser;
+pub use self::ser::{to_bin, MsgpackSer};
+
+mod de;
+pub use self::de::MsgpackDe;
+pub use self::de::{from_bin, from_bin_with_options};
+
+mod options;
+pub use self::options::MsgpackDeOptions;
@@ -0,0 +1,13 @@
+/// Options for `from_bin_with_options`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MsgpackDeOptions {
+    /// Maximum number of nested arrays and maps, deeper inputs fail
+    /// with `Error::depth_exceeded` instead of overflowing the stack
+    pub max_depth: usize,
+}
+
+impl Default for MsgpackDeOptions {
+    fn default() -> Self {
+        Self { max_depth: 128 }
+    }
+}
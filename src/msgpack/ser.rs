@@ -0,0 +1,216 @@
+use crate::ser::Headers;
+use crate::ser::{Context, MapTrait, Return, SeqTrait, Serialize, SerializerTrait, VisitorTrait};
+
+/// Serialize any serializable type into a MessagePack byte vec.
+///
+/// ```rust
+/// use toctoc::{msgpack, Serialize};
+///
+/// #[derive(Serialize, Debug)]
+/// struct Example {
+///     code: u32,
+///     message: String,
+/// }
+///
+/// fn main() {
+///     let example = Example {
+///         code: 200,
+///         message: "Ok".to_owned(),
+///     };
+///
+///     let b = msgpack::to_bin(&example, &mut ());
+///     assert_eq!(b, b"\x82\xa4code\xcc\xc8\xa7message\xa2Ok");
+/// }
+/// ```
+pub fn to_bin<T: Serialize>(value: &T, context: &mut dyn Context) -> Vec<u8> {
+    let mut msgpack = MsgpackSer::new();
+    match msgpack.serialize(value, context) {
+        Return::Text(_) => unreachable!(),
+        Return::Binary(b) => b,
+    }
+}
+
+/// **NOTE** The number of elements of arrays and maps is only known once
+/// they are done, so a 5 bytes header is reserved and then replaced by the
+/// compact one, the output is moved back to remove the unused bytes once
+/// the outermost array or map is done.
+///
+/// Binary data is written without any padding, so the alignment of
+/// `Bytes` isn't kept.
+pub struct MsgpackSer {
+    buffer: Vec<u8>,
+    headers: Headers,
+}
+
+impl MsgpackSer {
+    pub fn new() -> Self {
+        Self {
+            buffer: vec![],
+            headers: Headers::new(5),
+        }
+    }
+
+    /// Returns the serialized bytes
+    pub fn into_vec(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    /// Writes the `marker` for 8 bits lengths followed by `len`, the
+    /// markers for 16 and 32 bits lengths must be the next ones
+    fn write_len(&mut self, marker: u8, len: usize) {
+        if len <= u8::max_value() as usize {
+            self.buffer.push(marker);
+            self.buffer.push(len as u8);
+        } else if len <= u16::max_value() as usize {
+            self.buffer.push(marker + 1);
+            self.buffer.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            self.buffer.push(marker + 2);
+            self.buffer.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+
+    fn begin_collection(&mut self) {
+        self.headers.begin(&mut self.buffer);
+    }
+
+    /// Writes the compact header in the reserved one, `fix` is the marker
+    /// of the fixed size form and `marker` the one for 16 bits lengths
+    fn end_collection(&mut self, fix: u8, marker: u8) {
+        self.headers.end(&mut self.buffer, |len, header| {
+            if len < 16 {
+                header[0] = fix | len as u8;
+                1
+            } else if len <= u16::max_value() as usize {
+                header[0] = marker;
+                header[1..3].copy_from_slice(&(len as u16).to_be_bytes());
+                3
+            } else {
+                header[0] = marker + 1;
+                header[1..5].copy_from_slice(&(len as u32).to_be_bytes());
+                5
+            }
+        });
+    }
+
+    fn count(&mut self) {
+        self.headers.count();
+    }
+}
+
+impl SerializerTrait for MsgpackSer {
+    fn serialize(&mut self, s: &dyn Serialize, c: &mut dyn Context) -> Return {
+        self.buffer.clear();
+        self.headers.clear();
+
+        s.begin(self.into(), c);
+
+        Return::Binary(std::mem::take(&mut self.buffer))
+    }
+}
+
+impl VisitorTrait for MsgpackSer {
+    fn null(&mut self) {
+        self.buffer.push(0xC0);
+    }
+
+    fn boolean(&mut self, b: bool) {
+        self.buffer.push(if b { 0xC3 } else { 0xC2 });
+    }
+
+    fn string(&mut self, s: &str) {
+        if s.len() < 32 {
+            self.buffer.push(0xA0 | s.len() as u8);
+        } else {
+            self.write_len(0xD9, s.len());
+        }
+        self.buffer.extend_from_slice(s.as_bytes());
+    }
+
+    fn long(&mut self, n: i64) {
+        if n >= 0 {
+            // Same as `rmp`, non negative numbers are always written as unsigned
+            self.ulong(n as u64)
+        } else if n >= -32 {
+            self.buffer.push(n as u8); // Negative fixint
+        } else if n >= i8::min_value() as i64 {
+            self.buffer.push(0xD0);
+            self.buffer.push(n as u8);
+        } else if n >= i16::min_value() as i64 {
+            self.buffer.push(0xD1);
+            self.buffer.extend_from_slice(&(n as i16).to_be_bytes());
+        } else if n >= i32::min_value() as i64 {
+            self.buffer.push(0xD2);
+            self.buffer.extend_from_slice(&(n as i32).to_be_bytes());
+        } else {
+            self.buffer.push(0xD3);
+            self.buffer.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+
+    fn ulong(&mut self, n: u64) {
+        if n < 128 {
+            self.buffer.push(n as u8); // Positive fixint
+        } else if n <= u8::max_value() as u64 {
+            self.buffer.push(0xCC);
+            self.buffer.push(n as u8);
+        } else if n <= u16::max_value() as u64 {
+            self.buffer.push(0xCD);
+            self.buffer.extend_from_slice(&(n as u16).to_be_bytes());
+        } else if n <= u32::max_value() as u64 {
+            self.buffer.push(0xCE);
+            self.buffer.extend_from_slice(&(n as u32).to_be_bytes());
+        } else {
+            self.buffer.push(0xCF);
+            self.buffer.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+
+    fn single(&mut self, n: f32) {
+        self.buffer.push(0xCA);
+        self.buffer.extend_from_slice(&n.to_be_bytes());
+    }
+
+    fn double(&mut self, n: f64) {
+        self.buffer.push(0xCB);
+        self.buffer.extend_from_slice(&n.to_be_bytes());
+    }
+
+    fn bytes(&mut self, b: &[u8], _: usize) {
+        self.write_len(0xC4, b.len());
+        self.buffer.extend_from_slice(b);
+    }
+
+    fn seq(&mut self) -> &mut dyn SeqTrait {
+        self.begin_collection();
+        self
+    }
+
+    fn map(&mut self) -> &mut dyn MapTrait {
+        self.begin_collection();
+        self
+    }
+}
+
+impl SeqTrait for MsgpackSer {
+    fn element(&mut self, s: &dyn Serialize, c: &mut dyn Context) {
+        self.count();
+        s.begin(self.into(), c);
+    }
+
+    fn done(&mut self) {
+        self.end_collection(0x90, 0xDC)
+    }
+}
+
+impl MapTrait for MsgpackSer {
+    fn field(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        self.count();
+        self.string(k);
+        s.begin(self.into(), c);
+    }
+
+    fn done(&mut self) {
+        self.end_collection(0x80, 0xDE)
+    }
+}
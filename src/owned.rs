@@ -1,3 +1,4 @@
+use std::mem::ManuallyDrop;
 use std::pin::Pin;
 
 /// Keeps a value `T` that contains borrows to some data `D`.
@@ -33,6 +34,42 @@ impl<D, T> OwnedRaw<D, T> {
     pub unsafe fn as_mut(&mut self) -> &mut T {
         self.value.as_mut().unwrap()
     }
+
+    /// Transforms the inner value while keeping the data.
+    ///
+    /// # Safety
+    ///
+    /// The borrows of `T` aren't tied to `self`, so the caller must guarantee
+    /// that nothing borrowed from `T` (like a `&str` field) is returned inside `U`
+    /// or escapes the closure, make owned copies instead.
+    pub unsafe fn map<U, F: FnOnce(&T) -> U>(self, f: F) -> OwnedRaw<D, U> {
+        let value = f(self.value.as_ref().unwrap());
+        self.replace(value)
+    }
+
+    /// Same as `map` but the transformation can fail, the data is dropped on error.
+    ///
+    /// # Safety
+    ///
+    /// Same as `map`, neither `U` nor `E` can hold anything borrowed from `T`.
+    pub unsafe fn try_map<U, E, F: FnOnce(&T) -> Result<U, E>>(
+        self,
+        f: F,
+    ) -> Result<OwnedRaw<D, U>, E> {
+        let value = f(self.value.as_ref().unwrap())?;
+        Ok(self.replace(value))
+    }
+
+    /// Drops `T` and moves the data into a new `OwnedRaw` holding `value`
+    fn replace<U>(mut self, value: U) -> OwnedRaw<D, U> {
+        self.value = None;
+        // `self` can't be destructured because it implements `Drop`
+        let this = ManuallyDrop::new(self);
+        OwnedRaw {
+            data: unsafe { std::ptr::read(&this.data) },
+            value: Some(value),
+        }
+    }
 }
 
 impl<D, T> std::ops::Drop for OwnedRaw<D, T> {
@@ -40,3 +77,33 @@ impl<D, T> std::ops::Drop for OwnedRaw<D, T> {
         self.value = None; // Drop the inner borrowed value frist
     }
 }
+
+/// Iterator over values `I::Item` that contain borrows to some data `D`.
+/// Meant for streaming zero copy items while keeping their data alive.
+///
+/// ***Warning*** Items returned by the iterator are still borrowing `D`,
+/// they must not outlive the `OwnedIterRaw` that yield them, that's why
+/// the functions creating it are `unsafe`.
+#[allow(dead_code)]
+pub struct OwnedIterRaw<D, I: Iterator> {
+    pub(crate) data: Pin<D>,
+    pub(crate) iter: Option<I>,
+}
+
+impl<D, I: Iterator> Iterator for OwnedIterRaw<D, I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.as_mut().and_then(Iterator::next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.as_ref().map_or((0, Some(0)), Iterator::size_hint)
+    }
+}
+
+impl<D, I: Iterator> std::ops::Drop for OwnedIterRaw<D, I> {
+    fn drop(&mut self) {
+        self.iter = None; // Drop the remaining borrowed items first
+    }
+}
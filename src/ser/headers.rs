@@ -0,0 +1,67 @@
+use std::ops::Range;
+
+/// Headers of the arrays and maps of the binary formats that start with their
+/// number of elements, it's only known once they are done so the biggest
+/// header is reserved and the bytes it didn't use are removed once the
+/// outermost one is done, moving every byte at most once.
+pub(crate) struct Headers {
+    /// Size of the biggest header
+    reserved: usize,
+    /// Header offset and number of elements of every array or map not done yet
+    open: Vec<(usize, usize)>,
+    /// Reserved bytes left unused by the headers written so far
+    gaps: Vec<Range<usize>>,
+}
+
+impl Headers {
+    pub fn new(reserved: usize) -> Self {
+        Self {
+            reserved,
+            open: vec![],
+            gaps: vec![],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.open.clear();
+        self.gaps.clear();
+    }
+
+    pub fn begin(&mut self, buffer: &mut Vec<u8>) {
+        self.open.push((buffer.len(), 0));
+        buffer.resize(buffer.len() + self.reserved, 0);
+    }
+
+    pub fn count(&mut self) {
+        self.open.last_mut().unwrap().1 += 1;
+    }
+
+    /// Writes the header with `write`, given the number of elements and the
+    /// reserved bytes it returns how many of them were used
+    pub fn end(&mut self, buffer: &mut Vec<u8>, write: impl FnOnce(usize, &mut [u8]) -> usize) {
+        let (i, len) = self.open.pop().unwrap();
+        let size = write(len, &mut buffer[i..i + self.reserved]);
+        if size < self.reserved {
+            self.gaps.push(i + size..i + self.reserved);
+        }
+        if self.open.is_empty() {
+            self.compact(buffer);
+        }
+    }
+
+    fn compact(&mut self, buffer: &mut Vec<u8>) {
+        // Inner arrays and maps are done first
+        self.gaps.sort_unstable_by_key(|gap| gap.start);
+        let mut write = match self.gaps.first() {
+            Some(gap) => gap.start,
+            None => return,
+        };
+        for (k, gap) in self.gaps.iter().enumerate() {
+            let end = self.gaps.get(k + 1).map_or(buffer.len(), |next| next.start);
+            buffer.copy_within(gap.end..end, write);
+            write += end - gap.end;
+        }
+        buffer.truncate(write);
+        self.gaps.clear();
+    }
+}
@@ -1,8 +1,18 @@
 use std::borrow::Cow;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque};
 use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{
+    NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32,
+    NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::ser::{Context, Done, Serialize, Visitor};
+use crate::ser::{Context, Done, Serialize, SerializeMap, Visitor};
 
 impl Serialize for () {
     fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
@@ -10,6 +20,13 @@ impl Serialize for () {
     }
 }
 
+/// Written as `null` without requiring `T: Serialize`
+impl<T: ?Sized> Serialize for PhantomData<T> {
+    fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
+        v.null()
+    }
+}
+
 impl Serialize for bool {
     fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
         v.boolean(*self)
@@ -22,12 +39,32 @@ impl Serialize for str {
     }
 }
 
+impl Serialize for char {
+    fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
+        v.string(self.encode_utf8(&mut [0; 4]))
+    }
+}
+
 impl Serialize for String {
     fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
         v.string(self)
     }
 }
 
+/// Serialization can't fail so non UTF-8 paths are written lossy,
+/// with `U+FFFD` replacing the invalid sequences
+impl Serialize for Path {
+    fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
+        v.string(&self.to_string_lossy())
+    }
+}
+
+impl Serialize for PathBuf {
+    fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+        self.as_path().begin(v, context)
+    }
+}
+
 macro_rules! primitive {
     ($ty:ident, $method:ident, $cast:ident) => {
         impl Serialize for $ty {
@@ -39,30 +76,65 @@ macro_rules! primitive {
 }
 
 primitive!(u8, byte, u8);
-primitive!(u16, uint, u32);
+primitive!(u16, ushort, u16);
 primitive!(u32, uint, u32);
 primitive!(u64, ulong, u64);
 primitive!(usize, ulong, u64);
 primitive!(i8, sbyte, i8);
-primitive!(i16, int, i32);
+primitive!(i16, short, i16);
 primitive!(i32, int, i32);
 primitive!(i64, long, i64);
 primitive!(isize, long, i64);
+primitive!(i128, int128, i128);
+primitive!(u128, uint128, u128);
 primitive!(f32, single, f32);
 primitive!(f64, double, f64);
 
+macro_rules! nonzero {
+    ($ty:ident) => {
+        impl Serialize for $ty {
+            fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+                self.get().begin(v, context)
+            }
+        }
+    };
+}
+
+nonzero!(NonZeroU8);
+nonzero!(NonZeroU16);
+nonzero!(NonZeroU32);
+nonzero!(NonZeroU64);
+nonzero!(NonZeroUsize);
+nonzero!(NonZeroI8);
+nonzero!(NonZeroI16);
+nonzero!(NonZeroI32);
+nonzero!(NonZeroI64);
+nonzero!(NonZeroIsize);
+
 impl<'a, T: ?Sized + Serialize> Serialize for &'a T {
     fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
         (**self).begin(v, context)
     }
 }
 
-impl<T: ?Sized + Serialize> Serialize for Box<T> {
-    fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
-        (**self).begin(v, context)
-    }
+impl<'a, T: ?Sized + SerializeMap> SerializeMap for &'a T {}
+
+macro_rules! smart_pointer {
+    ($ptr:ident) => {
+        impl<T: ?Sized + Serialize> Serialize for $ptr<T> {
+            fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+                (**self).begin(v, context)
+            }
+        }
+
+        impl<T: ?Sized + SerializeMap> SerializeMap for $ptr<T> {}
+    };
 }
 
+smart_pointer!(Box);
+smart_pointer!(Rc);
+smart_pointer!(Arc);
+
 impl<T: Serialize> Serialize for Option<T> {
     fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
         match self {
@@ -72,12 +144,16 @@ impl<T: Serialize> Serialize for Option<T> {
     }
 }
 
+impl<T: SerializeMap> SerializeMap for Option<T> {}
+
 impl<'a, T: ?Sized + ToOwned + Serialize> Serialize for Cow<'a, T> {
     fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
         (**self).begin(v, context)
     }
 }
 
+impl<'a, T: ?Sized + ToOwned + SerializeMap> SerializeMap for Cow<'a, T> {}
+
 macro_rules! arrays {
     ($($n:tt),*) => { $(
         impl<T: Serialize> Serialize for [T; $n] {
@@ -139,6 +215,46 @@ impl<T: Serialize> Serialize for Vec<T> {
     }
 }
 
+impl<T: Serialize> Serialize for VecDeque<T> {
+    fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+        let mut seq = v.seq();
+        for e in self {
+            seq = seq.element(e, context);
+        }
+        seq.done()
+    }
+}
+
+impl<T: Serialize> Serialize for LinkedList<T> {
+    fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+        let mut seq = v.seq();
+        for e in self {
+            seq = seq.element(e, context);
+        }
+        seq.done()
+    }
+}
+
+/// Written as a two elements sequence `[start, end]`
+impl<T: Serialize> Serialize for Range<T> {
+    fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+        v.seq()
+            .element(&self.start, context)
+            .element(&self.end, context)
+            .done()
+    }
+}
+
+/// Written as a two elements sequence `[start, end]`, with `end` included
+impl<T: Serialize> Serialize for RangeInclusive<T> {
+    fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+        v.seq()
+            .element(self.start(), context)
+            .element(self.end(), context)
+            .done()
+    }
+}
+
 impl<K, V, H> Serialize for HashMap<K, V, H>
 where
     K: Hash + Eq + ToString,
@@ -154,6 +270,14 @@ where
     }
 }
 
+impl<K, V, H> SerializeMap for HashMap<K, V, H>
+where
+    K: Hash + Eq + ToString,
+    V: Serialize,
+    H: BuildHasher,
+{
+}
+
 impl<K: ToString, V: Serialize> Serialize for BTreeMap<K, V> {
     fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
         let mut map = v.map();
@@ -163,3 +287,46 @@ impl<K: ToString, V: Serialize> Serialize for BTreeMap<K, V> {
         map.done()
     }
 }
+
+impl<K: ToString, V: Serialize> SerializeMap for BTreeMap<K, V> {}
+
+impl<T, H> Serialize for HashSet<T, H>
+where
+    T: Serialize + Hash + Eq,
+    H: BuildHasher,
+{
+    fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+        let mut seq = v.seq();
+        for e in self {
+            seq = seq.element(e, context);
+        }
+        seq.done()
+    }
+}
+
+impl<T: Serialize> Serialize for BTreeSet<T> {
+    fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done {
+        let mut seq = v.seq();
+        for e in self {
+            seq = seq.element(e, context);
+        }
+        seq.done()
+    }
+}
+
+macro_rules! net_addr {
+    ($ty:ident) => {
+        impl Serialize for $ty {
+            fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
+                v.string(&self.to_string())
+            }
+        }
+    };
+}
+
+net_addr!(IpAddr);
+net_addr!(Ipv4Addr);
+net_addr!(Ipv6Addr);
+net_addr!(SocketAddr);
+net_addr!(SocketAddrV4);
+net_addr!(SocketAddrV6);
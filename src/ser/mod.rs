@@ -61,7 +61,22 @@
 //! }
 //! ```
 
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+mod headers;
 mod impls;
+mod time;
+mod with;
+
+#[cfg(feature = "rfc3339")]
+pub use self::time::SystemTimeRfc3339;
+pub use self::time::SystemTimeUnix;
+#[cfg(feature = "base64")]
+pub use self::with::SerializeBase64;
+#[doc(hidden)]
+pub use self::with::SerializeWith;
+
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+pub(crate) use self::headers::Headers;
 
 use crate::export::{Asset, Entity};
 
@@ -72,6 +87,20 @@ pub trait Serialize {
     fn begin(&self, v: Visitor, context: &mut dyn Context) -> Done;
 }
 
+/// Marker for the types that always serialize as a map (or `null` to write
+/// nothing), the only ones that can be flattened with `Map::flatten`.
+///
+/// Implemented by `#[derive(Serialize)]` for structs with named fields.
+///
+/// ```rust,compile_fail
+/// #[derive(toctoc::Serialize)]
+/// struct Wrapper {
+///     #[toctoc(flatten)]
+///     id: u32,
+/// }
+/// ```
+pub trait SerializeMap: Serialize {}
+
 ///////////////////////////////////////////////////////////////////////////////
 
 pub struct Serializer<'a>(&'a mut dyn SerializerTrait);
@@ -139,6 +168,12 @@ impl<'a> Visitor<'a> {
         Done(())
     }
 
+    #[inline(always)]
+    pub fn short(self, n: i16) -> Done {
+        self.s.short(n);
+        Done(())
+    }
+
     #[inline(always)]
     pub fn int(self, n: i32) -> Done {
         self.s.int(n);
@@ -157,6 +192,12 @@ impl<'a> Visitor<'a> {
         Done(())
     }
 
+    #[inline(always)]
+    pub fn ushort(self, n: u16) -> Done {
+        self.s.ushort(n);
+        Done(())
+    }
+
     #[inline(always)]
     pub fn uint(self, n: u32) -> Done {
         self.s.uint(n);
@@ -169,6 +210,18 @@ impl<'a> Visitor<'a> {
         Done(())
     }
 
+    #[inline(always)]
+    pub fn int128(self, n: i128) -> Done {
+        self.s.int128(n);
+        Done(())
+    }
+
+    #[inline(always)]
+    pub fn uint128(self, n: u128) -> Done {
+        self.s.uint128(n);
+        Done(())
+    }
+
     #[inline(always)]
     pub fn single(self, n: f32) -> Done {
         self.s.single(n);
@@ -187,6 +240,13 @@ impl<'a> Visitor<'a> {
         Done(())
     }
 
+    #[cfg(feature = "base64")]
+    #[inline(always)]
+    pub fn base64(self, b: &[u8], align: usize, config: base64::Config) -> Done {
+        self.s.base64(b, align, config);
+        Done(())
+    }
+
     #[inline(always)]
     pub fn seq(self) -> Seq<'a> {
         Seq { s: self.s.seq() }
@@ -229,6 +289,44 @@ impl<'a> Map<'a> {
         self
     }
 
+    /// Writes a field without escaping its key `k`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that `k` doesn't contain any `"`, `\`
+    /// or control characters, otherwise the output may be malformed.
+    #[inline(always)]
+    pub unsafe fn field_raw_key(self, k: &str, s: &dyn Serialize, c: &mut dyn Context) -> Self {
+        self.m.field_raw_key(k, s, c);
+        self
+    }
+
+    /// Writes all the fields of `s` inline into this map, or nothing
+    /// when `s` serializes as `null`.
+    ///
+    /// Used by the `#[toctoc(flatten)]` derive attribute
+    #[inline(always)]
+    pub fn flatten<T: ?Sized + SerializeMap>(self, s: &T, c: &mut dyn Context) -> Self {
+        s.begin(
+            Visitor {
+                s: &mut Flatten { m: &mut *self.m },
+            },
+            c,
+        );
+        self
+    }
+
+    /// Checks that no key is written twice, used by the `#[toctoc(flatten)]`
+    /// derive attribute since the keys of flattened fields are only known at runtime
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn unique_keys(self) -> UniqueKeys<'a> {
+        UniqueKeys {
+            m: self.m,
+            keys: vec![],
+        }
+    }
+
     #[inline(always)]
     pub fn done(self) -> Done {
         self.m.done();
@@ -236,6 +334,47 @@ impl<'a> Map<'a> {
     }
 }
 
+/// Map that panics when a key is written twice, only in debug builds
+#[doc(hidden)]
+pub struct UniqueKeys<'a> {
+    m: &'a mut dyn MapTrait,
+    keys: Vec<String>,
+}
+
+impl<'a> UniqueKeys<'a> {
+    #[inline(always)]
+    pub fn map(&mut self) -> Map {
+        Map { m: self }
+    }
+
+    fn insert(&mut self, k: &str) {
+        if cfg!(debug_assertions) {
+            assert!(
+                !self.keys.iter().any(|key| key == k),
+                "key `{}` written twice, the keys of flattened fields overlap",
+                k
+            );
+            self.keys.push(k.to_owned());
+        }
+    }
+}
+
+impl<'a> MapTrait for UniqueKeys<'a> {
+    fn field(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        self.insert(k);
+        self.m.field(k, s, c)
+    }
+
+    unsafe fn field_raw_key(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        self.insert(k);
+        self.m.field_raw_key(k, s, c)
+    }
+
+    fn done(&mut self) {
+        self.m.done()
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 pub trait VisitorTrait {
@@ -249,6 +388,10 @@ pub trait VisitorTrait {
         self.int(n as i32)
     }
 
+    fn short(&mut self, n: i16) {
+        self.int(n as i32)
+    }
+
     fn int(&mut self, n: i32) {
         self.long(n as i64)
     }
@@ -259,18 +402,50 @@ pub trait VisitorTrait {
         self.uint(n as u32)
     }
 
+    fn ushort(&mut self, n: u16) {
+        self.uint(n as u32)
+    }
+
     fn uint(&mut self, n: u32) {
         self.ulong(n as u64)
     }
 
     fn ulong(&mut self, n: u64);
 
+    /// Values out of the 64 bits range are written as strings by default,
+    /// to avoid any precision loss
+    fn int128(&mut self, n: i128) {
+        if n >= i64::min_value() as i128 && n <= i64::max_value() as i128 {
+            self.long(n as i64)
+        } else {
+            self.string(itoa::Buffer::new().format(n))
+        }
+    }
+
+    /// Values out of the 64 bits range are written as strings by default,
+    /// to avoid any precision loss
+    fn uint128(&mut self, n: u128) {
+        if n <= u64::max_value() as u128 {
+            self.ulong(n as u64)
+        } else {
+            self.string(itoa::Buffer::new().format(n))
+        }
+    }
+
     fn single(&mut self, n: f32);
 
     fn double(&mut self, n: f64);
 
     fn bytes(&mut self, b: &[u8], align: usize);
 
+    /// Bytes of a `#[toctoc(base64)]` field, text formats write them as a base64
+    /// string while the binary ones keep their native bytes by default
+    #[cfg(feature = "base64")]
+    fn base64(&mut self, b: &[u8], align: usize, config: base64::Config) {
+        let _ = config;
+        self.bytes(b, align)
+    }
+
     fn seq(&mut self) -> &mut dyn SeqTrait;
 
     fn map(&mut self) -> &mut dyn MapTrait;
@@ -283,26 +458,160 @@ pub trait SeqTrait {
 
 pub trait MapTrait {
     fn field(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn Context);
+
+    /// Same as `field` but the key `k` is known to not need any escaping,
+    /// see `Map::field_raw_key` for the safety requirements
+    unsafe fn field_raw_key(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        self.field(k, s, c)
+    }
+
     fn done(&mut self);
 }
 
+/// Forwards the fields of a map to its parent map, used by `Map::flatten`
+struct Flatten<'a> {
+    m: &'a mut dyn MapTrait,
+}
+
+impl<'a> Flatten<'a> {
+    fn unsupported(&self) -> ! {
+        panic!("`SerializeMap` types must serialize as a map")
+    }
+}
+
+impl<'a> VisitorTrait for Flatten<'a> {
+    fn null(&mut self) {
+        // Nothing to flatten, like a `None`
+    }
+
+    fn boolean(&mut self, _: bool) {
+        self.unsupported()
+    }
+
+    fn string(&mut self, _: &str) {
+        self.unsupported()
+    }
+
+    fn long(&mut self, _: i64) {
+        self.unsupported()
+    }
+
+    fn ulong(&mut self, _: u64) {
+        self.unsupported()
+    }
+
+    fn single(&mut self, _: f32) {
+        self.unsupported()
+    }
+
+    fn double(&mut self, _: f64) {
+        self.unsupported()
+    }
+
+    fn bytes(&mut self, _: &[u8], _: usize) {
+        self.unsupported()
+    }
+
+    #[cfg(feature = "base64")]
+    fn base64(&mut self, _: &[u8], _: usize, _: base64::Config) {
+        self.unsupported()
+    }
+
+    fn seq(&mut self) -> &mut dyn SeqTrait {
+        self.unsupported()
+    }
+
+    fn map(&mut self) -> &mut dyn MapTrait {
+        self
+    }
+}
+
+impl<'a> MapTrait for Flatten<'a> {
+    fn field(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        self.m.field(k, s, c)
+    }
+
+    unsafe fn field_raw_key(&mut self, k: &str, s: &dyn Serialize, c: &mut dyn Context) {
+        self.m.field_raw_key(k, s, c)
+    }
+
+    fn done(&mut self) {
+        // The parent map is closed by its owner
+    }
+}
+
 /// Trait that can translate complex types based on some context
-/// into serializable fragments
+/// into serializable fragments.
+///
+/// The default methods serialize as `null`, tuples of contexts use them
+/// to know when to move on to the next context.
 #[cfg(not(feature = "any-context"))]
 pub trait Context {
     fn entity(&self, e: Entity) -> &dyn Serialize {
         let _ = e;
-        &()
+        &UNRESOLVED
     }
 
     fn asset(&self, a: Asset) -> &dyn Serialize {
         let _ = a;
-        &()
+        &UNRESOLVED
     }
 }
 
 #[cfg(not(feature = "any-context"))]
 impl Context for () {}
 
+/// Returned by the default `Context` methods, not zero sized so it has a unique address
+#[cfg(not(feature = "any-context"))]
+struct Unresolved {
+    _unique: u8,
+}
+
+#[cfg(not(feature = "any-context"))]
+static UNRESOLVED: Unresolved = Unresolved { _unique: 0 };
+
+#[cfg(not(feature = "any-context"))]
+impl Serialize for Unresolved {
+    fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
+        v.null()
+    }
+}
+
+#[cfg(not(feature = "any-context"))]
+fn is_unresolved(s: &dyn Serialize) -> bool {
+    std::ptr::eq(
+        s as *const dyn Serialize as *const u8,
+        &UNRESOLVED as *const Unresolved as *const u8,
+    )
+}
+
+#[cfg(not(feature = "any-context"))]
+macro_rules! context_tuple {
+    ($($n:tt $t:ident)+) => {
+        /// Composes many contexts, each one is tried in order until one of them
+        /// overrides the default method
+        impl<$($t: Context),+> Context for ($($t,)+) {
+            fn entity(&self, e: Entity) -> &dyn Serialize {
+                let s: &dyn Serialize = &UNRESOLVED;
+                $(let s = if is_unresolved(s) { self.$n.entity(e) } else { s };)+
+                s
+            }
+
+            fn asset(&self, a: Asset) -> &dyn Serialize {
+                let s: &dyn Serialize = &UNRESOLVED;
+                $(let s = if is_unresolved(s) { self.$n.asset(a.clone()) } else { s };)+
+                s
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "any-context"))]
+context_tuple!(0 C0 1 C1);
+#[cfg(not(feature = "any-context"))]
+context_tuple!(0 C0 1 C1 2 C2);
+#[cfg(not(feature = "any-context"))]
+context_tuple!(0 C0 1 C1 2 C2 3 C3);
+
 #[cfg(feature = "any-context")]
 pub trait Context = std::any::Any;
@@ -0,0 +1,67 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ser::{Context, Done, Serialize, Visitor};
+
+/// `SystemTime` written as the number of seconds since the Unix epoch,
+/// times before the epoch are negative and the sub second part is dropped.
+///
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use toctoc::{json, ser::SystemTimeUnix};
+///
+/// let t = SystemTimeUnix(UNIX_EPOCH + Duration::from_secs(1_705_314_600));
+/// assert_eq!(json::to_string(&t, &mut ()), "1705314600");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemTimeUnix(pub SystemTime);
+
+impl Serialize for SystemTimeUnix {
+    fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
+        match self.0.duration_since(UNIX_EPOCH) {
+            Ok(d) => v.ulong(d.as_secs()),
+            Err(err) => {
+                // Rounds down like the times after the epoch do
+                let d = err.duration();
+                let secs = d.as_secs() as i64 + (d.subsec_nanos() > 0) as i64;
+                v.long(-secs)
+            }
+        }
+    }
+}
+
+/// `SystemTime` written as a UTC [RFC 3339](https://tools.ietf.org/html/rfc3339)
+/// string, like `"2024-01-15T10:30:00Z"`, nanoseconds are kept when present.
+///
+/// Times that can't be written in the RFC 3339 format (past the year 9999)
+/// are written as `null`.
+///
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use toctoc::{json, ser::SystemTimeRfc3339};
+///
+/// let t = SystemTimeRfc3339(UNIX_EPOCH + Duration::from_secs(1_705_314_600));
+/// assert_eq!(json::to_string(&t, &mut ()), r#""2024-01-15T10:30:00Z""#);
+/// ```
+#[cfg(feature = "rfc3339")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemTimeRfc3339(pub SystemTime);
+
+#[cfg(feature = "rfc3339")]
+impl Serialize for SystemTimeRfc3339 {
+    fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
+        use ::time::format_description::well_known::Rfc3339;
+        use ::time::OffsetDateTime;
+
+        let nanos = match self.0.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_nanos() as i128,
+            Err(err) => -(err.duration().as_nanos() as i128),
+        };
+        match OffsetDateTime::from_unix_timestamp_nanos(nanos)
+            .ok()
+            .and_then(|t| t.format(&Rfc3339).ok())
+        {
+            Some(s) => v.string(&s),
+            None => v.null(),
+        }
+    }
+}
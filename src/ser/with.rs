@@ -0,0 +1,40 @@
+use crate::ser::{Context, Done, Serialize, Visitor};
+
+#[cfg(feature = "base64")]
+use crate::bytes::{Binary, Bytes};
+
+/// Serializes `value` with a custom function, used by the derive macros
+/// for `#[toctoc(serialize_with = "...")]` fields.
+///
+/// Not public API.
+#[doc(hidden)]
+pub struct SerializeWith<'a, T: ?Sized> {
+    pub value: &'a T,
+    pub with: fn(&T, Visitor, &mut dyn Context) -> Done,
+}
+
+impl<'a, T: ?Sized> Serialize for SerializeWith<'a, T> {
+    fn begin(&self, v: Visitor, c: &mut dyn Context) -> Done {
+        (self.with)(self.value, v, c)
+    }
+}
+
+/// Serializes the bytes of `value` as a base64 string, used by the derive macros
+/// for `#[toctoc(base64)]` fields. Binary formats (BSON, msgpack and CBOR) keep
+/// writing their native bytes.
+///
+/// Not public API.
+#[cfg(feature = "base64")]
+#[doc(hidden)]
+pub struct SerializeBase64<'a, T> {
+    pub value: &'a Bytes<T>,
+    pub config: base64::Config,
+}
+
+#[cfg(feature = "base64")]
+impl<'a, 'b, T: Binary<'b>> Serialize for SerializeBase64<'a, T> {
+    fn begin(&self, v: Visitor, _: &mut dyn Context) -> Done {
+        let (b, align) = self.value.0.as_bytes();
+        v.base64(b, align, self.config)
+    }
+}
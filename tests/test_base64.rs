@@ -0,0 +1,139 @@
+#![cfg(feature = "base64")]
+
+use toctoc::bytes::Bytes;
+use toctoc::{bson, json, Deserialize, Serialize};
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Blob {
+    #[toctoc(base64)]
+    data: Bytes<Vec<u8>>,
+    #[toctoc(base64 = "url_safe_no_pad")]
+    key: Bytes<Vec<u8>>,
+    #[toctoc(base64 = "standard_no_pad")]
+    points: Bytes<Vec<u32>>,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum Message {
+    Signed {
+        #[toctoc(base64 = "url_safe")]
+        signature: Bytes<Box<[u8]>>,
+    },
+}
+
+fn blob() -> Blob {
+    Blob {
+        data: Bytes(b"toctoc".to_vec()),
+        key: Bytes(vec![0xfb, 0xff, 0xfe, 0x01]),
+        points: Bytes(vec![1, 0xffff_ffff, 3]),
+    }
+}
+
+#[test]
+fn test_base64_ser() {
+    let v = blob();
+    let points: Vec<u8> = v.points.0.iter().flat_map(|p| p.to_ne_bytes()).collect();
+    let expected = format!(
+        r#"{{"data":"{}","key":"{}","points":"{}"}}"#,
+        base64::encode(&v.data.0),
+        base64::encode_config(&v.key.0, base64::URL_SAFE_NO_PAD),
+        base64::encode_config(&points, base64::STANDARD_NO_PAD),
+    );
+    assert_eq!(json::to_string(&v, &mut ()), expected);
+
+    let m = Message::Signed {
+        signature: Bytes(vec![0xfb, 0xff].into_boxed_slice()),
+    };
+    assert_eq!(
+        json::to_string(&m, &mut ()),
+        format!(
+            r#"{{"Signed":{{"signature":"{}"}}}}"#,
+            base64::encode_config([0xfbu8, 0xff], base64::URL_SAFE)
+        )
+    );
+}
+
+#[test]
+fn test_base64_de() {
+    let mut j = json::to_string(&blob(), &mut ());
+    let v: Blob = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(v, blob());
+
+    // Any variant is accepted, with or without padding
+    let data = base64::encode_config(b"toctoc!", base64::URL_SAFE);
+    let key = base64::encode_config([0xfbu8, 0xff, 0xfe], base64::STANDARD);
+    let mut j = format!(r#"{{"data":"{}","key":"{}","points":""}}"#, data, key);
+    let v: Blob = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(v.data, &base64::decode(b"dG9jdG9jIQ==").unwrap()[..]);
+    assert_eq!(v.key, &[0xfb, 0xff, 0xfe][..]);
+    assert_eq!(v.points, &[][..]);
+
+    let mut j = r#"{"Signed":{"signature":"-_8="}}"#.to_string();
+    let m: Message = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(
+        m,
+        Message::Signed {
+            signature: Bytes(vec![0xfb, 0xff].into_boxed_slice()),
+        }
+    );
+}
+
+/// Same fields of `Blob` without the `base64` attribute
+#[derive(Deserialize)]
+struct Raw {
+    data: Bytes<Vec<u8>>,
+    key: Bytes<Vec<u8>>,
+    points: Bytes<Vec<u8>>,
+}
+
+#[test]
+fn test_base64_binary_formats() {
+    let points: Vec<u8> = blob()
+        .points
+        .0
+        .iter()
+        .flat_map(|p| p.to_ne_bytes())
+        .collect();
+
+    // Written as native bytes by the binary formats
+    let b = bson::to_bin(&blob(), &mut ());
+    let r: Raw = bson::from_bin(&b, &mut ()).unwrap();
+    assert_eq!(r.data, &b"toctoc"[..]);
+    assert_eq!(r.key, &[0xfb, 0xff, 0xfe, 0x01][..]);
+    assert_eq!(r.points, &points[..]);
+    let v: Blob = bson::from_bin(&b, &mut ()).unwrap();
+    assert_eq!(v, blob());
+
+    #[cfg(feature = "msgpack")]
+    {
+        use toctoc::msgpack;
+
+        let b = msgpack::to_bin(&blob(), &mut ());
+        let r: Raw = msgpack::from_bin(&b, &mut ()).unwrap();
+        assert_eq!(r.data, &b"toctoc"[..]);
+        let v: Blob = msgpack::from_bin(&b, &mut ()).unwrap();
+        assert_eq!(v, blob());
+    }
+
+    #[cfg(feature = "cbor")]
+    {
+        use toctoc::cbor;
+
+        let b = cbor::to_bin(&blob(), &mut ());
+        let r: Raw = cbor::from_bin(&b, &mut ()).unwrap();
+        assert_eq!(r.data, &b"toctoc"[..]);
+        let v: Blob = cbor::from_bin(&b, &mut ()).unwrap();
+        assert_eq!(v, blob());
+    }
+}
+
+#[test]
+fn test_base64_errors() {
+    let mut j = r#"{"data":"dG9j*G9j","key":"","points":""}"#.to_string();
+    assert!(json::from_str::<Blob>(&mut j, &mut ()).is_err());
+    // Mixed alphabets
+    let mut j = r#"{"data":"+_","key":"","points":""}"#.to_string();
+    assert!(json::from_str::<Blob>(&mut j, &mut ()).is_err());
+    let mut j = r#"{"data":[1,2],"key":"","points":""}"#.to_string();
+    assert!(json::from_str::<Blob>(&mut j, &mut ()).is_err());
+}
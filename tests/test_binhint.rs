@@ -1,4 +1,4 @@
-use toctoc::bytes::Bytes;
+use toctoc::bytes::{ByValue, Bytes};
 use toctoc::json;
 
 macro_rules! bin {
@@ -27,3 +27,35 @@ fn test_binhint() {
         "\"#----04030002\""
     );
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, ByValue)]
+#[repr(C)]
+struct Pixel {
+    rgba: [u8; 4],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ByValue)]
+#[repr(C)]
+struct Pair<T> {
+    a: T,
+    b: T,
+}
+
+#[test]
+fn test_derive_by_value() {
+    bin!(
+        &[Pixel],
+        1,
+        Bytes::new(vec![Pixel { rgba: [1, 2, 3, 4] }]),
+        "\"#01020304\""
+    );
+    bin!(
+        &[Pair<u16>],
+        2,
+        Bytes::new(vec![Pair {
+            a: 0x0102_u16,
+            b: 0x0304
+        }]),
+        "\"#--02010403\""
+    );
+}
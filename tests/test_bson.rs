@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use toctoc::bytes::Bytes;
 use toctoc::{Deserialize as KDeserialize, Serialize as KSerialize};
 
@@ -90,4 +91,407 @@ fn bson_zero_copy() {
     assert_eq!(bson.as_ptr().align_offset(4), 0);
     let m1: MeshReadOnly = toctoc::bson::from_bin(&bson, &mut ()).unwrap();
     assert_eq!(m0, m1);
+    assert_eq!(m1.verts, &[[0xAA55AA55, 0], [0, 0], [0, 0], [0, 0]][..]);
+}
+
+#[test]
+fn bson_bytes_vec() {
+    let bin = toctoc::bson::to_bin(&Bytes::new(vec![1u32, 2, 3]), &mut ());
+    let v: Bytes<Vec<u32>> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(v, &[1, 2, 3][..]);
+    assert_ne!(v, &[1, 2][..]);
+}
+
+#[test]
+fn bson_bytes_boxed() {
+    let bin = toctoc::bson::to_bin(&Bytes::new(vec![1u32, 2, 3].into_boxed_slice()), &mut ());
+    let v: Bytes<Box<[u32]>> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(v, &[1, 2, 3][..]);
+
+    let v: Bytes<Arc<[u32]>> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    let shared = v.0.clone();
+    assert_eq!(v, &[1, 2, 3][..]);
+    assert_eq!(&shared[..], &[1, 2, 3][..]);
+}
+
+#[test]
+fn bson_size_mismatch() {
+    let mut bin = toctoc::bson::to_bin(&vec![1u32, 2, 3], &mut ());
+    let v: Vec<u32> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(v, vec![1, 2, 3]);
+
+    // Truncated buffer
+    let r: toctoc::Result<Vec<u32>> = toctoc::bson::from_bin(&bin[..bin.len() - 1], &mut ());
+    assert!(r.is_err());
+
+    // Root document declares more bytes than it has
+    bin[0] += 1;
+    let r: toctoc::Result<Vec<u32>> = toctoc::bson::from_bin(&bin, &mut ());
+    assert!(r.is_err());
+    bin[0] -= 1;
+
+    // Nested document declares more bytes than its parent
+    bin[6] += 0x20;
+    let r: toctoc::Result<Vec<u32>> = toctoc::bson::from_bin(&bin, &mut ());
+    assert!(r.is_err());
+}
+
+#[test]
+fn bson_reuse_buffer() {
+    use toctoc::bson::BsonSer;
+    use toctoc::Serialize;
+
+    let a = vec![1u32, 2, 3];
+    let b = "hello".to_string();
+
+    let mut bson = BsonSer::new();
+    a.begin((&mut bson).into(), &mut ());
+    let buffer = bson.into_buffer();
+    assert_eq!(buffer.as_slice(), &toctoc::bson::to_bin(&a, &mut ())[..]);
+
+    let mut bson = BsonSer::with_buffer(buffer);
+    b.begin((&mut bson).into(), &mut ());
+    let buffer = bson.into_buffer();
+    assert_eq!(buffer.as_slice(), &toctoc::bson::to_bin(&b, &mut ())[..]);
+
+    let mut bson = BsonSer::with_buffer(buffer);
+    a.begin((&mut bson).into(), &mut ());
+    assert_eq!(bson.into_vec(), toctoc::bson::to_bin(&a, &mut ()));
+}
+
+#[test]
+fn bson_to_bin_into_vec() {
+    let value = vec!["a".to_string(), "bc".to_string()];
+
+    let mut buf = vec![];
+    toctoc::bson::to_bin_into_vec(&value, &mut (), &mut buf);
+    assert_eq!(buf, toctoc::bson::to_bin(&value, &mut ()));
+
+    // No reallocation after the first call
+    let capacity = buf.capacity();
+    let ptr = buf.as_ptr();
+    for _ in 0..1000 {
+        toctoc::bson::to_bin_into_vec(&value, &mut (), &mut buf);
+        assert_eq!(buf.capacity(), capacity);
+        if ptr.align_offset(toctoc::buffer::Buffer::ALIGNMENT) == 0 {
+            // Same memory
+            assert_eq!(buf.as_ptr(), ptr);
+        }
+    }
+    assert_eq!(buf, toctoc::bson::to_bin(&value, &mut ()));
+}
+
+#[test]
+fn bson_to_writer() {
+    let value = vec!["a".to_string(), "bc".to_string()];
+    let mut out = vec![];
+    toctoc::bson::to_writer(&value, &mut (), &mut out).unwrap();
+    assert_eq!(out, toctoc::bson::to_bin(&value, &mut ()));
+
+    let r: Vec<String> = toctoc::bson::from_bin(&out, &mut ()).unwrap();
+    assert_eq!(r, value);
+}
+
+#[test]
+fn bson_from_reader() {
+    use std::io::Cursor;
+
+    let first = vec!["a".to_string(), "bc".to_string()];
+    let second = vec![vec![1u32], vec![2, 3]];
+    let mut bin = toctoc::bson::to_bin(&first, &mut ());
+    bin.extend(toctoc::bson::to_bin(&second, &mut ()));
+
+    // Every call reads one document
+    let mut reader = Cursor::new(&bin[..]);
+    let r: Vec<String> = toctoc::bson::from_reader(&mut reader, &mut ()).unwrap();
+    assert_eq!(r, first);
+    let r: Vec<Vec<u32>> = toctoc::bson::from_reader(&mut reader, &mut ()).unwrap();
+    assert_eq!(r, second);
+    assert_eq!(reader.position() as usize, bin.len());
+
+    let r: toctoc::Result<Vec<Vec<u32>>> = toctoc::bson::from_reader(&mut reader, &mut ());
+    assert!(r.is_err());
+
+    // Truncated document
+    let r: toctoc::Result<Vec<String>> =
+        toctoc::bson::from_reader(Cursor::new(&bin[..10]), &mut ());
+    assert!(r.is_err());
+
+    let r: toctoc::Result<Vec<String>> =
+        toctoc::bson::from_reader(Cursor::new(&[1, 0, 0, 0][..]), &mut ());
+    assert!(r.is_err());
+}
+
+#[test]
+fn bson_binary_writer() {
+    use std::io::{self, Cursor, Write};
+    use toctoc::bson::{BsonBinaryWriter, BsonSer};
+
+    let data: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+
+    let mut bson = BsonSer::new();
+    let mut writer = BsonBinaryWriter::new(&mut bson);
+    io::copy(&mut Cursor::new(&data[..]), &mut writer).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    let bin = bson.into_vec();
+
+    // Same as `v.bytes(data, 1)`
+    assert_eq!(
+        bin,
+        toctoc::bson::to_bin(&Bytes::new(data.clone()), &mut ())
+    );
+    let v: Bytes<Vec<u8>> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(v, &data[..]);
+}
+
+#[test]
+fn bson_binary_writer_field() {
+    use std::io::{self, Cursor, Write};
+    use toctoc::bson::{BsonBinaryWriter, BsonSer};
+    use toctoc::ser::{MapTrait, VisitorTrait};
+
+    #[derive(Debug, PartialEq, KDeserialize, KSerialize)]
+    struct Inner {
+        name: String,
+        data: Bytes<Vec<u32>>,
+        raw: Bytes<Vec<u8>>,
+    }
+
+    #[derive(Debug, PartialEq, KDeserialize, KSerialize)]
+    struct Outer {
+        id: u32,
+        inner: Inner,
+    }
+
+    let data: Vec<u32> = (0..1000).collect();
+    let expected = Outer {
+        id: 7,
+        inner: Inner {
+            name: "blob".to_string(),
+            data: Bytes::new(data.clone()),
+            raw: Bytes::new(vec![1, 2, 3]),
+        },
+    };
+
+    let mut bson = BsonSer::new();
+    VisitorTrait::map(&mut bson);
+    MapTrait::field(&mut bson, "id", &7u32, &mut ());
+    VisitorTrait::map(bson.key("inner"));
+    MapTrait::field(&mut bson, "name", &"blob", &mut ());
+
+    let bytes: Vec<u8> = data.iter().flat_map(|n| n.to_ne_bytes().to_vec()).collect();
+    let mut writer = BsonBinaryWriter::field(&mut bson, "data", 4);
+    io::copy(&mut Cursor::new(&bytes[..]), &mut writer).unwrap();
+    drop(writer);
+
+    let mut writer = BsonBinaryWriter::field(&mut bson, "raw", 1);
+    writer.write_all(&[1, 2, 3]).unwrap();
+    drop(writer);
+
+    MapTrait::done(&mut bson);
+    MapTrait::done(&mut bson);
+    let bin = bson.into_vec();
+
+    // Same as `v.bytes(data, align)` on each field
+    assert_eq!(bin, toctoc::bson::to_bin(&expected, &mut ()));
+    let actual: Outer = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn bson_fields_remaining() {
+    use std::collections::BTreeMap;
+    use toctoc::de::{Context, Map, Visitor};
+
+    // Records the estimate given before visiting any field
+    struct Remaining(Option<usize>);
+
+    impl<'de> Visitor<'de> for Remaining {
+        fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> toctoc::Result<()> {
+            self.0 = m.fields_remaining();
+            while m.next()?.is_some() {
+                m.visit(<dyn Visitor>::ignore(), c)?;
+            }
+            Ok(())
+        }
+    }
+
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), 1u32);
+    map.insert("b".to_string(), 2u32);
+    let bin = toctoc::bson::to_bin(&map, &mut ());
+
+    let mut remaining = Remaining(None);
+    toctoc::Deserializer::from(&mut toctoc::bson::BsonDe::new(&bin))
+        .deserialize(&mut remaining, &mut ())
+        .unwrap();
+    assert!(remaining.0.unwrap() >= map.len());
+}
+
+#[test]
+fn bson_depth_limit() {
+    let v = vec![vec![vec![1u32, 2], vec![3]], vec![vec![4]]];
+    let bin = toctoc::bson::to_bin(&v, &mut ());
+
+    let r: toctoc::Result<Vec<Vec<Vec<u32>>>> =
+        toctoc::bson::from_bin_with_depth_limit(&bin, &mut (), 2);
+    assert!(r.is_err());
+
+    let r: Vec<Vec<Vec<u32>>> = toctoc::bson::from_bin_with_depth_limit(&bin, &mut (), 3).unwrap();
+    assert_eq!(r, v);
+}
+
+#[test]
+fn bson_options_depth() {
+    use toctoc::bson::BsonDeOptions;
+    use toctoc::json::{JsonDeOptions, Value};
+
+    let nested = |depth: usize| {
+        let mut j = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let options = JsonDeOptions { max_depth: depth };
+        let value: Value = toctoc::json::from_str_with_options(&mut j, &mut (), options).unwrap();
+        toctoc::bson::to_bin(&value, &mut ())
+    };
+
+    assert_eq!(BsonDeOptions::default().max_depth, 128);
+
+    let bin = nested(10);
+    let r: toctoc::Result<Value> = toctoc::bson::from_bin(&bin, &mut ());
+    assert!(r.is_ok());
+
+    // Rejected instead of overflowing the stack
+    let options = BsonDeOptions { max_depth: 16 };
+    let bin = nested(200);
+    let r: toctoc::Result<Value> = toctoc::bson::from_bin_with_options(&bin, &mut (), options);
+    let err = r.unwrap_err();
+    assert!(matches!(err.kind(), toctoc::ErrorKind::DepthExceeded(16)));
+
+    let bin = nested(16);
+    let r: toctoc::Result<Value> = toctoc::bson::from_bin_with_options(&bin, &mut (), options);
+    assert!(r.is_ok());
+}
+
+#[test]
+fn bson_nonzero() {
+    use std::num::NonZeroU8;
+
+    // Both `0x81` (u8) and `0x83` (u32) representations
+    let bin = toctoc::bson::to_bin(&5u8, &mut ());
+    assert_eq!(bin[4], 0x81);
+    let n: NonZeroU8 = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(n.get(), 5);
+
+    let bin = toctoc::bson::to_bin(&5u32, &mut ());
+    assert_eq!(bin[4], 0x83);
+    let n: NonZeroU8 = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(n.get(), 5);
+
+    // Zero, negative and out of range values are rejected
+    for bin in &[
+        toctoc::bson::to_bin(&0u8, &mut ()),
+        toctoc::bson::to_bin(&0u32, &mut ()),
+        toctoc::bson::to_bin(&-5i8, &mut ()),
+        toctoc::bson::to_bin(&256u32, &mut ()),
+    ] {
+        let r: toctoc::Result<NonZeroU8> = toctoc::bson::from_bin(bin, &mut ());
+        assert!(r.is_err());
+    }
+}
+
+#[derive(KSerialize)]
+struct Status {
+    id: u64,
+    text: String,
+    truncated: bool,
+    in_reply_to: Option<u32>,
+    retweet_count: u32,
+    completed_in: f32,
+    indices: (u8, u8),
+    hashtags: Vec<String>,
+    user: V,
+    empty: Vec<u32>,
+}
+
+#[test]
+fn bson_stream_to_json() {
+    let status = Status {
+        id: 505874924095815681,
+        text: "\"Quoted\"\nand a tab\t\u{1F600}".to_string(),
+        truncated: false,
+        in_reply_to: None,
+        retweet_count: 3,
+        completed_in: 0.087,
+        indices: (3, 10),
+        hashtags: vec!["a".to_string(), "b\\c".to_string()],
+        user: V {
+            string: "Hi!".to_owned(),
+            b: true,
+            int: -5,
+        },
+        empty: vec![],
+    };
+
+    let bin = toctoc::bson::to_bin(&status, &mut ());
+    let mut j = String::new();
+    toctoc::bson::stream_to_json(&bin, &mut (), &mut j).unwrap();
+    assert_eq!(j, toctoc::json::to_string(&status, &mut ()));
+}
+
+#[test]
+fn bson_stream_to_json_twitter() {
+    use toctoc::json::Value;
+
+    let mut j = std::fs::read_to_string("benches/twitter.json").unwrap();
+    let value: Value = toctoc::json::from_str(&mut j, &mut ()).unwrap();
+
+    let bin = toctoc::bson::to_bin(&value, &mut ());
+    let mut j = String::new();
+    toctoc::bson::stream_to_json(&bin, &mut (), &mut j).unwrap();
+    assert_eq!(j, toctoc::json::to_string(&value, &mut ()));
+}
+
+#[test]
+fn bson_native_32() {
+    let v: Vec<u32> = vec![0, 1, u32::max_value()];
+    let bin = toctoc::bson::to_bin(&v, &mut ());
+    let r: Vec<u32> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(r, v);
+
+    let v: Vec<i32> = vec![i32::min_value(), -1, 0, i32::max_value()];
+    let bin = toctoc::bson::to_bin(&v, &mut ());
+    let r: Vec<i32> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(r, v);
+
+    // Other types still go through the widened default
+    let bin = toctoc::bson::to_bin(&u32::max_value(), &mut ());
+    let n: u64 = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(n, u32::max_value() as u64);
+    let r: toctoc::Result<u16> = toctoc::bson::from_bin(&bin, &mut ());
+    assert!(r.is_err());
+
+    let bin = toctoc::bson::to_bin(&-7i32, &mut ());
+    let n: i64 = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(n, -7);
+}
+
+#[test]
+fn bson_128() {
+    let v: Vec<i128> = vec![i128::min_value(), -1, 0, i128::max_value()];
+    let bin = toctoc::bson::to_bin(&v, &mut ());
+    let r: Vec<i128> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(r, v);
+
+    let bin = toctoc::bson::to_bin(&u128::max_value(), &mut ());
+    assert_eq!(bin[4], 0x87);
+    let n: u128 = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(n, u128::max_value());
+
+    // Narrower types accept the value as long it fits
+    let bin = toctoc::bson::to_bin(&7u128, &mut ());
+    let n: u8 = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(n, 7);
+    let r: toctoc::Result<u64> =
+        toctoc::bson::from_bin(&toctoc::bson::to_bin(&u128::max_value(), &mut ()), &mut ());
+    assert!(r.is_err());
 }
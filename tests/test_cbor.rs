@@ -0,0 +1,323 @@
+#![cfg(feature = "cbor")]
+
+use toctoc::bytes::Bytes;
+use toctoc::cbor::{self, CborDeOptions};
+use toctoc::de::{Context, Seq, Visitor};
+use toctoc::json::{self, Value};
+use toctoc::{make_place, Deserialize, Serialize};
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Decodes the CBOR `h` hex string, written back as JSON
+fn decode(h: &str) -> String {
+    let bin = hex(h);
+    let value: Value = cbor::from_bin(&bin, &mut ()).unwrap();
+    json::to_string(&value, &mut ())
+}
+
+make_place!(Place);
+
+/// Only visits the first element of an array
+struct First(u64);
+
+impl<'de> Visitor<'de> for Place<First> {
+    fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> toctoc::Result<()> {
+        let mut n = None;
+        s.visit(Deserialize::begin(&mut n), c)?;
+        self.out = n.map(First);
+        Ok(())
+    }
+}
+
+impl<'de> Deserialize<'de> for First {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        Place::new(out)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct V {
+    string: String,
+    b: bool,
+    int: i32,
+    list: Vec<i64>,
+}
+
+// Examples from RFC 8949, appendix A
+#[test]
+fn test_cbor_integers() {
+    let cases: &[(i128, &str)] = &[
+        (0, "00"),
+        (1, "01"),
+        (10, "0a"),
+        (23, "17"),
+        (24, "1818"),
+        (25, "1819"),
+        (100, "1864"),
+        (1000, "1903e8"),
+        (1000000, "1a000f4240"),
+        (1000000000000, "1b000000e8d4a51000"),
+        (18446744073709551615, "1bffffffffffffffff"),
+        (-18446744073709551616, "3bffffffffffffffff"),
+        (-1, "20"),
+        (-10, "29"),
+        (-100, "3863"),
+        (-1000, "3903e7"),
+    ];
+    for (n, h) in cases {
+        let bin = cbor::to_bin(n, &mut ());
+        assert_eq!(bin, hex(h), "{}", n);
+        let n1: i128 = cbor::from_bin(&bin, &mut ()).unwrap();
+        assert_eq!(n1, *n);
+    }
+
+    assert_eq!(cbor::to_bin(&-1000i64, &mut ()), hex("3903e7"));
+    assert_eq!(cbor::to_bin(&1000u64, &mut ()), hex("1903e8"));
+    let n: u8 = cbor::from_bin(&hex("1818"), &mut ()).unwrap();
+    assert_eq!(n, 24);
+}
+
+#[test]
+fn test_cbor_floats() {
+    assert_eq!(cbor::to_bin(&1.1f64, &mut ()), hex("fb3ff199999999999a"));
+    assert_eq!(cbor::to_bin(&100000.0f32, &mut ()), hex("fa47c35000"));
+    assert_eq!(cbor::to_bin(&f32::MAX, &mut ()), hex("fa7f7fffff"));
+    assert_eq!(
+        cbor::to_bin(&1.0e+300f64, &mut ()),
+        hex("fb7e37e43c8800759c")
+    );
+    assert_eq!(cbor::to_bin(&-4.1f64, &mut ()), hex("fbc010666666666666"));
+
+    let n: f64 = cbor::from_bin(&hex("fb3ff199999999999a"), &mut ()).unwrap();
+    assert_eq!(n, 1.1);
+
+    // Half precision floats are only read
+    let cases: &[(f32, &str)] = &[
+        (0.0, "f90000"),
+        (-0.0, "f98000"),
+        (1.0, "f93c00"),
+        (1.5, "f93e00"),
+        (65504.0, "f97bff"),
+        (2f32.powi(-24), "f90001"),
+        (2f32.powi(-14), "f90400"),
+        (-4.0, "f9c400"),
+        (f32::INFINITY, "f97c00"),
+        (f32::NEG_INFINITY, "f9fc00"),
+    ];
+    for (n, h) in cases {
+        let n1: f32 = cbor::from_bin(&hex(h), &mut ()).unwrap();
+        assert_eq!(n1.to_bits(), n.to_bits(), "{}", h);
+    }
+    let nan: f32 = cbor::from_bin(&hex("f97e00"), &mut ()).unwrap();
+    assert!(nan.is_nan());
+}
+
+#[test]
+fn test_cbor_simple() {
+    assert_eq!(cbor::to_bin(&false, &mut ()), hex("f4"));
+    assert_eq!(cbor::to_bin(&true, &mut ()), hex("f5"));
+    assert_eq!(cbor::to_bin(&Option::<u8>::None, &mut ()), hex("f6"));
+    assert_eq!(decode("f4"), "false");
+    assert_eq!(decode("f5"), "true");
+    assert_eq!(decode("f6"), "null");
+    // Undefined
+    assert_eq!(decode("f7"), "null");
+    // simple(16)
+    assert!(cbor::from_bin::<Value>(&hex("f0"), &mut ()).is_err());
+}
+
+#[test]
+fn test_cbor_strings() {
+    let cases = &[
+        ("", "60"),
+        ("a", "6161"),
+        ("IETF", "6449455446"),
+        ("\"\\", "62225c"),
+        ("\u{00fc}", "62c3bc"),
+        ("\u{6c34}", "63e6b0b4"),
+        ("\u{10151}", "64f0908591"),
+    ];
+    for (s, h) in cases {
+        let bin = cbor::to_bin(s, &mut ());
+        assert_eq!(bin, hex(h), "{}", s);
+        let s1: &str = cbor::from_bin(&bin, &mut ()).unwrap();
+        assert_eq!(s1, *s);
+    }
+
+    let bin = cbor::to_bin(&Bytes::new(Vec::<u8>::new()), &mut ());
+    assert_eq!(bin, hex("40"));
+    let bin = cbor::to_bin(&Bytes::new(vec![1u8, 2, 3, 4]), &mut ());
+    assert_eq!(bin, hex("4401020304"));
+    let b: Bytes<Vec<u8>> = cbor::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(b, &[1u8, 2, 3, 4][..]);
+
+    // Indefinite length strings can't be borrowed
+    assert!(cbor::from_bin::<Value>(&hex("5f42010243030405ff"), &mut ()).is_err());
+    assert!(cbor::from_bin::<Value>(&hex("7f657374726561646d696e67ff"), &mut ()).is_err());
+}
+
+#[test]
+fn test_cbor_collections() {
+    let empty: Vec<u8> = vec![];
+    assert_eq!(cbor::to_bin(&empty, &mut ()), hex("80"));
+    assert_eq!(cbor::to_bin(&vec![1, 2, 3], &mut ()), hex("83010203"));
+    assert_eq!(
+        cbor::to_bin(&(1, vec![2, 3], vec![4, 5]), &mut ()),
+        hex("8301820203820405")
+    );
+    let long: Vec<u32> = (1..=25).collect();
+    let h = "98190102030405060708090a0b0c0d0e0f101112131415161718181819";
+    assert_eq!(cbor::to_bin(&long, &mut ()), hex(h));
+    let long1: Vec<u32> = cbor::from_bin(&hex(h), &mut ()).unwrap();
+    assert_eq!(long1, long);
+
+    // Nested headers of every size
+    let nested = vec![vec![], vec![2u8; 24], vec![3u8; 70_000], vec![4u8]];
+    let bin = cbor::to_bin(&nested, &mut ());
+    let mut expected = hex("84809818");
+    expected.extend_from_slice(&[2; 24]);
+    expected.extend_from_slice(&hex("9a00011170"));
+    expected.extend_from_slice(&[3; 70_000]);
+    expected.extend_from_slice(&hex("8104"));
+    assert_eq!(bin, expected);
+    let actual: Vec<Vec<u8>> = cbor::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, nested);
+
+    let cases = &[
+        ("a0", "{}"),
+        ("a26161016162820203", r#"{"a":1,"b":[2,3]}"#),
+        ("826161a161626163", r#"["a",{"b":"c"}]"#),
+        (
+            "a56161614161626142616361436164614461656145",
+            r#"{"a":"A","b":"B","c":"C","d":"D","e":"E"}"#,
+        ),
+    ];
+    for (h, j) in cases {
+        assert_eq!(decode(h), *j);
+
+        // Keys are sorted just like in the RFC examples
+        let mut j = j.to_string();
+        let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(cbor::to_bin(&value, &mut ()), hex(h));
+    }
+}
+
+#[test]
+fn test_cbor_indefinite() {
+    let cases = &[
+        ("9fff", "[]"),
+        ("9f018202039f0405ffff", "[1,[2,3],[4,5]]"),
+        ("9f01820203820405ff", "[1,[2,3],[4,5]]"),
+        ("83018202039f0405ff", "[1,[2,3],[4,5]]"),
+        ("83019f0203ff820405", "[1,[2,3],[4,5]]"),
+        ("bf61610161629f0203ffff", r#"{"a":1,"b":[2,3]}"#),
+        ("826161bf61626163ff", r#"["a",{"b":"c"}]"#),
+    ];
+    for (h, j) in cases {
+        assert_eq!(decode(h), *j, "{}", h);
+    }
+    let bin = hex("bf6346756ef563416d7421ff");
+    let value: Value = cbor::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(value["Fun"], Value::Bool(true));
+    assert_eq!(value["Amt"].as_i64(), Some(-2));
+
+    // The rest of the elements are skipped, nested indefinite items included
+    let cases = &[
+        "9f01bf61619f02810380ff61624101ff04ff",
+        "8301bf61619f02810380ff61624101ff04",
+        "82019f9f9fffffff",
+    ];
+    for h in cases {
+        let First(n) = cbor::from_bin(&hex(h), &mut ()).unwrap();
+        assert_eq!(n, 1, "{}", h);
+    }
+    assert!(cbor::from_bin::<First>(&hex("82019f9fff"), &mut ()).is_err());
+}
+
+#[test]
+fn test_cbor_tags() {
+    assert_eq!(
+        decode("c074323031332d30332d32315432303a30343a30305a"),
+        r#""2013-03-21T20:04:00Z""#
+    );
+    assert_eq!(decode("c11a514b67b0"), "1363896240");
+    assert_eq!(
+        decode("d82076687474703a2f2f7777772e6578616d706c652e636f6d"),
+        r#""http://www.example.com""#
+    );
+    let b: Bytes<Vec<u8>> = cbor::from_bin(&hex("d74401020304"), &mut ()).unwrap();
+    assert_eq!(b, &[1u8, 2, 3, 4][..]);
+}
+
+#[test]
+fn test_cbor_struct() {
+    let v = V {
+        string: "Hi!".to_owned(),
+        b: false,
+        int: -5,
+        list: vec![0, 23, 24, -24, -25, 70_000, i64::min_value()],
+    };
+
+    let bin = cbor::to_bin(&v, &mut ());
+    let v1: V = cbor::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(v1, v);
+
+    // Unknown fields are skipped along with everything nested in them
+    let mut j =
+        r#"{"b":true,"extra":{"x":[1,[2,"three"],{"y":null}]},"int":1,"list":[],"string":""}"#
+            .to_string();
+    let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    let bin = cbor::to_bin(&value, &mut ());
+    let v: V = cbor::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(
+        v,
+        V {
+            string: "".to_owned(),
+            b: true,
+            int: 1,
+            list: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_cbor_errors() {
+    // Truncated input
+    assert!(cbor::from_bin::<Vec<u8>>(&hex("830102"), &mut ()).is_err());
+    // Trailing bytes
+    assert!(cbor::from_bin::<u8>(&hex("0102"), &mut ()).is_err());
+    // Non string keys
+    assert!(cbor::from_bin::<Value>(&hex("a10102"), &mut ()).is_err());
+    // Invalid utf8
+    assert!(cbor::from_bin::<String>(&hex("61ff"), &mut ()).is_err());
+    // Reserved additional information
+    assert!(cbor::from_bin::<u8>(&hex("1c"), &mut ()).is_err());
+    // Break outside of an indefinite length item
+    assert!(cbor::from_bin::<Value>(&hex("ff"), &mut ()).is_err());
+
+    // Same default depth limit of JSON and BSON
+    let mut nested = vec![0x81; 128];
+    nested.push(0x01);
+    assert!(cbor::from_bin::<Value>(&nested, &mut ()).is_ok());
+    let nested = [0x81; 200];
+    let err = cbor::from_bin::<Value>(&nested, &mut ()).unwrap_err();
+    assert!(matches!(err.kind(), toctoc::ErrorKind::DepthExceeded(128)));
+
+    // Tags count as nesting levels too
+    let mut nested = vec![0xc0; 128];
+    nested.push(0x01);
+    assert!(cbor::from_bin::<Value>(&nested, &mut ()).is_ok());
+    let nested = [0xc0; 200];
+    let err = cbor::from_bin::<Value>(&nested, &mut ()).unwrap_err();
+    assert!(matches!(err.kind(), toctoc::ErrorKind::DepthExceeded(128)));
+
+    let options = CborDeOptions { max_depth: 10 };
+    let nested = [0x81; 100];
+    let err = cbor::from_bin_with_options::<Value>(&nested, &mut (), options).unwrap_err();
+    assert!(matches!(err.kind(), toctoc::ErrorKind::DepthExceeded(10)));
+}
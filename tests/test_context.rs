@@ -0,0 +1,158 @@
+use std::any::TypeId;
+use toctoc::de::CachedContext;
+use toctoc::export::{Asset, AssetHandle, Entity, Hint};
+use toctoc::{de, json, ser, Error, ErrorKind, Result, Serialize};
+
+/// Only knows about assets
+struct Assets;
+
+impl de::Context for Assets {
+    fn asset(&mut self, a: Hint) -> Result<Asset> {
+        match a {
+            Hint::Number(id) => Ok(Asset {
+                handle: AssetHandle::Plain(id as u32),
+                id: (TypeId::of::<Assets>(), id as u32),
+            }),
+            _ => Err(Error::generic("asset not found".to_string()))?,
+        }
+    }
+}
+
+impl ser::Context for Assets {
+    fn asset(&self, _: Asset) -> &dyn Serialize {
+        &"texture.png"
+    }
+}
+
+/// Only knows about entities
+struct Entities;
+
+impl de::Context for Entities {
+    fn entity(&mut self, e: Hint) -> Result<Entity> {
+        match e {
+            Hint::Number(id) => Ok(Entity(id + 100)),
+            _ => Err(Error::not_expected("entity hint"))?,
+        }
+    }
+}
+
+impl ser::Context for Entities {
+    fn entity(&self, _: Entity) -> &dyn Serialize {
+        &42u32
+    }
+}
+
+fn asset() -> Asset {
+    Asset {
+        handle: AssetHandle::Plain(7),
+        id: (TypeId::of::<Assets>(), 7),
+    }
+}
+
+#[test]
+fn test_de_tuple() {
+    let mut ctx = (Assets, Entities);
+    assert_eq!(
+        de::Context::entity(&mut ctx, Hint::Number(1)).unwrap().0,
+        101
+    );
+    assert_eq!(
+        de::Context::asset(&mut ctx, Hint::Number(3)).unwrap().id.1,
+        3
+    );
+
+    // Only `not_expected` errors move on to the next context
+    let err = de::Context::asset(&mut ctx, Hint::Null).err().unwrap();
+    assert!(matches!(err.kind(), ErrorKind::Other));
+
+    // When no context knows about it the last error is returned
+    let err = de::Context::entity(&mut ctx, Hint::Null).err().unwrap();
+    assert!(matches!(
+        err.kind(),
+        ErrorKind::UnexpectedType { got: "entity hint" }
+    ));
+
+    let mut ctx = ((), Entities, (), Assets);
+    assert_eq!(
+        de::Context::entity(&mut ctx, Hint::Number(2)).unwrap().0,
+        102
+    );
+    assert_eq!(
+        de::Context::asset(&mut ctx, Hint::Number(4)).unwrap().id.1,
+        4
+    );
+
+    let err = de::Context::entity(&mut ((), ()), Hint::Null)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err.kind(),
+        ErrorKind::UnexpectedType { got: "entity" }
+    ));
+}
+
+#[test]
+fn test_ser_tuple() {
+    let ctx = (Entities, Assets);
+    let s = |v: &dyn Serialize| json::to_string(&v, &mut ());
+    assert_eq!(s(ser::Context::entity(&ctx, Entity(0))), "42");
+    assert_eq!(s(ser::Context::asset(&ctx, asset())), r#""texture.png""#);
+
+    let ctx = ((), (), Assets);
+    assert_eq!(s(ser::Context::entity(&ctx, Entity(0))), "null");
+    assert_eq!(s(ser::Context::asset(&ctx, asset())), r#""texture.png""#);
+}
+
+/// Counts how many times each method was called
+#[derive(Default)]
+struct Counter {
+    assets: u32,
+    entities: u32,
+}
+
+impl de::Context for Counter {
+    fn entity(&mut self, _: Hint) -> Result<Entity> {
+        self.entities += 1;
+        Ok(Entity(0))
+    }
+
+    fn asset(&mut self, a: Hint) -> Result<Asset> {
+        self.assets += 1;
+        match a {
+            Hint::Null => Err(Error::not_expected("null asset"))?,
+            _ => Ok(Asset {
+                handle: AssetHandle::Plain(self.assets),
+                id: (TypeId::of::<Counter>(), self.assets),
+            }),
+        }
+    }
+}
+
+#[test]
+fn test_cached() {
+    let mut ctx = CachedContext::new(Counter::default());
+    let a = de::Context::asset(&mut ctx, Hint::Str("texture.png")).unwrap();
+    let b = de::Context::asset(&mut ctx, Hint::Str("texture.png")).unwrap();
+    assert_eq!(a.id, b.id);
+    assert_eq!(ctx.inner().assets, 1);
+
+    // Hints of different kinds never share an entry
+    de::Context::asset(&mut ctx, Hint::Number(1)).unwrap();
+    de::Context::asset(&mut ctx, Hint::Str("1")).unwrap();
+    de::Context::asset(&mut ctx, Hint::Bytes(&[1])).unwrap();
+    de::Context::asset(&mut ctx, Hint::Bytes(&[1])).unwrap();
+    assert_eq!(ctx.inner().assets, 4);
+
+    // Errors aren't cached
+    assert!(de::Context::asset(&mut ctx, Hint::Null).is_err());
+    assert!(de::Context::asset(&mut ctx, Hint::Null).is_err());
+    assert_eq!(ctx.inner().assets, 6);
+
+    ctx.clear();
+    de::Context::asset(&mut ctx, Hint::Str("texture.png")).unwrap();
+    assert_eq!(ctx.inner().assets, 7);
+
+    de::Context::entity(&mut ctx, Hint::Number(1)).unwrap();
+    de::Context::entity(&mut ctx, Hint::Number(1)).unwrap();
+    assert_eq!(ctx.into_inner().entities, 2);
+}
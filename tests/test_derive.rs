@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use toctoc::{json, Deserialize, Serialize};
 
 #[allow(dead_code)]
@@ -65,3 +68,884 @@ fn test_ser() {
     let expected = r#"{"x":"X","t1":"A","t2":"renamedB","n":{"y":["Y","Y"],"z":null}}"#;
     assert_eq!(actual, expected);
 }
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(rename_all = "camelCase")]
+struct Renamed {
+    status_code: u32,
+    #[toctoc(rename = "msg")]
+    status_message: String,
+    kind: RenamedKind,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(rename_all = "SCREAMING_SNAKE_CASE")]
+enum RenamedKind {
+    NotFound,
+    #[toctoc(rename = "ok")]
+    Ok,
+    #[toctoc(rename_all = "kebab-case")]
+    ServerError {
+        retry_after: u32,
+    },
+}
+
+#[test]
+fn test_rename_all() {
+    let value = Renamed {
+        status_code: 404,
+        status_message: "Not Found".to_owned(),
+        kind: RenamedKind::NotFound,
+    };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(
+        j,
+        r#"{"statusCode":404,"msg":"Not Found","kind":"NOT_FOUND"}"#
+    );
+    let actual: Renamed = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let value = RenamedKind::ServerError { retry_after: 5 };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"SERVER_ERROR":{"retry-after":5}}"#);
+    let actual: RenamedKind = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let mut j = r#""ok""#.to_string();
+    let actual: RenamedKind = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, RenamedKind::Ok);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(rename_all_variants = "snake_case", rename_all_fields = "camelCase")]
+enum Command {
+    MoveTo {
+        target_x: i32,
+        target_y: i32,
+    },
+    #[toctoc(rename_all = "kebab-case")]
+    LookAt {
+        target_x: i32,
+    },
+}
+
+#[test]
+fn test_rename_all_variants_and_fields() {
+    let value = Command::MoveTo {
+        target_x: 1,
+        target_y: 2,
+    };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"move_to":{"targetX":1,"targetY":2}}"#);
+    let actual: Command = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let value = Command::LookAt { target_x: 3 };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"look_at":{"target-x":3}}"#);
+    let actual: Command = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum Format {
+    Png,
+    Jpeg,
+    #[toctoc(other)]
+    Unknown(String),
+}
+
+#[test]
+fn test_other_variant() {
+    let mut j = r#"["Png","Jpeg","Webp"]"#.to_string();
+    let actual: Vec<Format> = json::from_str(&mut j, &mut ()).unwrap();
+    let expected = vec![
+        Format::Png,
+        Format::Jpeg,
+        Format::Unknown("Webp".to_owned()),
+    ];
+    assert_eq!(actual, expected);
+
+    let j = json::to_string(&expected, &mut ());
+    assert_eq!(j, r#"["Png","Jpeg","Webp"]"#);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum BorrowedFormat<'a> {
+    Png,
+    #[toctoc(other)]
+    Unknown(std::borrow::Cow<'a, str>),
+}
+
+#[test]
+fn test_other_variant_cow() {
+    let mut j = r#"["Png","Webp"]"#.to_string();
+    let actual: Vec<BorrowedFormat> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual[1], BorrowedFormat::Unknown("Webp".into()));
+    assert_eq!(json::to_string(&actual, &mut ()), r#"["Png","Webp"]"#);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(repr)]
+#[repr(i16)]
+enum Level {
+    Trace = -1,
+    Info = 2,
+    Error = 300,
+}
+
+#[test]
+fn test_repr() {
+    let value = vec![Level::Trace, Level::Info, Level::Error];
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, "[-1,2,300]");
+    let actual: Vec<Level> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let mut j = "3".to_string();
+    assert!(json::from_str::<Level>(&mut j, &mut ()).is_err());
+    // Out of the `i16` range
+    let mut j = "65535".to_string();
+    assert!(json::from_str::<Level>(&mut j, &mut ()).is_err());
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(repr)]
+#[repr(u128)]
+enum Wide {
+    Zero = 0,
+    Max = u128::max_value(),
+}
+
+#[test]
+fn test_repr_u128() {
+    let value = vec![Wide::Zero, Wide::Max];
+    let b = toctoc::bson::to_bin(&value, &mut ());
+    let actual: Vec<Wide> = toctoc::bson::from_bin(&b, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    // `u128::max_value() as i128` is `-1`
+    let mut j = "-1".to_string();
+    assert!(json::from_str::<Wide>(&mut j, &mut ()).is_err());
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(deny_unknown_fields)]
+struct Strict {
+    a: u32,
+    b: Option<u32>,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum StrictVariant {
+    #[toctoc(deny_unknown_fields)]
+    A {
+        a: u32,
+    },
+    B {
+        b: u32,
+    },
+}
+
+#[test]
+fn test_deny_unknown_fields() {
+    let value = Strict { a: 1, b: Some(2) };
+    let mut j = json::to_string(&value, &mut ());
+    let actual: Strict = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let mut j = r#"{"a":1,"c":3}"#.to_string();
+    let err = json::from_str::<Strict>(&mut j, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("unknown field `c`"));
+
+    let bin = toctoc::bson::to_bin(&StrictVariant::B { b: 1 }, &mut ());
+    let err = toctoc::bson::from_bin::<Strict>(&bin, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("unknown field `B`"));
+
+    let value = StrictVariant::A { a: 1 };
+    let mut j = json::to_string(&value, &mut ());
+    let actual: StrictVariant = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let mut j = r#"{"A":{"a":1,"b":2}}"#.to_string();
+    let err = json::from_str::<StrictVariant>(&mut j, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("unknown field `b`"));
+
+    // Variants without the attribute still ignore unknown fields
+    let mut j = r#"{"B":{"a":1,"b":2}}"#.to_string();
+    let actual: StrictVariant = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, StrictVariant::B { b: 2 });
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Pagination {
+    page: u32,
+    per_page: u32,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Users {
+    users: Vec<String>,
+    #[toctoc(flatten)]
+    pagination: Pagination,
+}
+
+#[test]
+fn test_flatten() {
+    let value = Users {
+        users: vec!["a".to_owned(), "b".to_owned()],
+        pagination: Pagination {
+            page: 1,
+            per_page: 10,
+        },
+    };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"users":["a","b"],"page":1,"per_page":10}"#);
+    let actual: Users = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let bin = toctoc::bson::to_bin(&value, &mut ());
+    let actual: Users = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    // Any key order, unknown keys are ignored
+    let mut j = r#"{"per_page":10,"total":2,"users":["a","b"],"page":1}"#.to_string();
+    let actual: Users = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let mut j = r#"{"users":[],"page":1}"#.to_string();
+    let err = json::from_str::<Users>(&mut j, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("missing field `per_page`"));
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+struct Extra<M> {
+    id: u32,
+    #[toctoc(flatten)]
+    pagination: Option<Pagination>,
+    #[toctoc(flatten)]
+    extra: M,
+}
+
+#[test]
+fn test_flatten_maps() {
+    let mut extra = BTreeMap::new();
+    extra.insert("total".to_string(), 2);
+    let value = Extra {
+        id: 1,
+        pagination: None,
+        extra,
+    };
+    let j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"id":1,"total":2}"#);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "key `page` written twice")]
+fn test_flatten_overlap() {
+    let mut extra = BTreeMap::new();
+    extra.insert("page".to_string(), 2);
+    let value = Extra {
+        id: 1,
+        pagination: Some(Pagination {
+            page: 1,
+            per_page: 10,
+        }),
+        extra,
+    };
+    json::to_string(&value, &mut ());
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(tag = "type")]
+enum Shape {
+    Circle { radius: f64 },
+    Rect { w: u32, h: u32 },
+    Empty,
+}
+
+#[test]
+fn test_internally_tagged() {
+    let value = Shape::Rect { w: 2, h: 3 };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"type":"Rect","w":2,"h":3}"#);
+    let actual: Shape = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let mut j = json::to_string(&Shape::Empty, &mut ());
+    assert_eq!(j, r#"{"type":"Empty"}"#);
+    let actual: Shape = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, Shape::Empty);
+
+    let value = Shape::Circle { radius: 0.5 };
+    let bin = toctoc::bson::to_bin(&value, &mut ());
+    let actual: Shape = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    // Tag doesn't need to be the first field
+    let mut j = r#"{"radius":0.5,"type":"Circle"}"#.to_string();
+    let actual: Shape = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let mut j = r#"{"radius":0.5}"#.to_string();
+    let err = json::from_str::<Shape>(&mut j, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("missing field `type`"));
+
+    let mut j = r#"{"type":"Triangle"}"#.to_string();
+    let err = json::from_str::<Shape>(&mut j, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("unknown variant `Triangle`"));
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(tag = "t", content = "c")]
+enum Message {
+    Ping,
+    Text(String),
+    Move(i32, i32),
+    Login { user: String, admin: bool },
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    let value = vec![
+        Message::Ping,
+        Message::Text("hi".to_owned()),
+        Message::Move(1, -2),
+        Message::Login {
+            user: "a".to_owned(),
+            admin: false,
+        },
+    ];
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(
+        j,
+        r#"[{"t":"Ping"},{"t":"Text","c":"hi"},{"t":"Move","c":[1,-2]},{"t":"Login","c":{"user":"a","admin":false}}]"#
+    );
+    let actual: Vec<Message> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let bin = toctoc::bson::to_bin(&value, &mut ());
+    let actual: Vec<Message> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    // Content may come before the tag and unknown fields are ignored
+    let mut j = r#"{"c":{"admin":true,"user":"b"},"x":1,"t":"Login"}"#.to_string();
+    let actual: Message = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(
+        actual,
+        Message::Login {
+            user: "b".to_owned(),
+            admin: true,
+        }
+    );
+
+    let mut j = r#"{"t":"Text"}"#.to_string();
+    let err = json::from_str::<Message>(&mut j, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("missing field `c`"));
+
+    let mut j = r#"{"c":"hi"}"#.to_string();
+    let err = json::from_str::<Message>(&mut j, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("missing field `t`"));
+
+    let mut j = r#"{"t":"Quit","c":null}"#.to_string();
+    let err = json::from_str::<Message>(&mut j, &mut ()).unwrap_err();
+    assert!(err.to_string().contains("unknown variant `Quit`"));
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Typed<T> {
+    #[toctoc(skip)]
+    _t: PhantomData<T>,
+    val: u32,
+    marker: PhantomData<T>,
+}
+
+#[test]
+fn test_phantom_data() {
+    let value: Typed<String> = Typed {
+        _t: PhantomData,
+        val: 1,
+        marker: PhantomData,
+    };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"val":1,"marker":null}"#);
+    let actual: Typed<String> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    // Nothing to read, so the field may be missing
+    let mut j = r#"{"val":1}"#.to_string();
+    let actual: Typed<String> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let mut j = r#"{"val":1,"marker":"x"}"#.to_string();
+    assert!(json::from_str::<Typed<String>>(&mut j, &mut ()).is_err());
+}
+
+mod shadowed {
+    use toctoc::{Deserialize, Serialize};
+
+    /// Shadows the prelude `Default`
+    #[allow(dead_code)]
+    trait Default {}
+
+    #[derive(PartialEq, Debug, Serialize, Deserialize)]
+    pub struct Cached {
+        pub val: u32,
+        #[toctoc(skip)]
+        pub cache: Vec<u32>,
+    }
+}
+
+#[test]
+fn test_skip_shadowed_default() {
+    let mut j = r#"{"val":1}"#.to_string();
+    let actual: shadowed::Cached = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(
+        actual,
+        shadowed::Cached {
+            val: 1,
+            cache: vec![]
+        }
+    );
+}
+
+/// Implements neither `Serialize` nor `Deserialize`
+#[derive(PartialEq, Debug)]
+struct Opaque;
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Handle<T, U> {
+    id: U,
+    kind: PhantomData<T>,
+    #[toctoc(skip)]
+    cached: Option<Box<T>>,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum Node<T, U> {
+    Leaf { value: U },
+    Ref(u32, PhantomData<T>),
+}
+
+#[test]
+fn test_unused_type_params() {
+    let value: Handle<Opaque, u32> = Handle {
+        id: 7,
+        kind: PhantomData,
+        cached: None,
+    };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"id":7,"kind":null}"#);
+    let actual: Handle<Opaque, u32> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let value: Vec<Node<Opaque, String>> = vec![
+        Node::Leaf {
+            value: "a".to_owned(),
+        },
+        Node::Ref(value.id, PhantomData),
+    ];
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"[{"Leaf":{"value":"a"}},{"Ref":[7,null]}]"#);
+    let actual: Vec<Node<Opaque, String>> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+}
+
+trait Family {
+    type Item;
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Ints;
+
+impl Family for Ints {
+    type Item = i32;
+}
+
+// Only `T` gets bounded, `T::Item` needs its own bound
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(
+    ser_bound = "T::Item: toctoc::Serialize",
+    de_bound = "T::Item: toctoc::Deserialize<'de>"
+)]
+struct Items<T: Family> {
+    items: Vec<T::Item>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+#[toctoc(bound = "T::Item: toctoc::Serialize")]
+enum Total<T: Family> {
+    Sum(T::Item),
+    Empty,
+}
+
+#[test]
+fn test_custom_bound() {
+    let value: Items<Ints> = Items {
+        items: vec![1, 2, 3],
+    };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"items":[1,2,3]}"#);
+    let actual: Items<Ints> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let value: Vec<Total<Ints>> = vec![Total::Sum(6), Total::Empty];
+    let j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"[{"Sum":[6]},"Empty"]"#);
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Sparse {
+    #[toctoc(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[toctoc(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[toctoc(skip_serializing_if = "is_zero")]
+    count: u32,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+enum SparseVariant {
+    A {
+        #[toctoc(skip_serializing_if = "Option::is_none")]
+        a: Option<u32>,
+        b: u32,
+    },
+}
+
+#[test]
+fn test_skip_serializing_if() {
+    let value = Sparse {
+        name: None,
+        tags: vec![],
+        count: 0,
+    };
+    assert_eq!(json::to_string(&value, &mut ()), "{}");
+
+    let value = Sparse {
+        name: Some("a".to_owned()),
+        tags: vec!["b".to_owned()],
+        count: 2,
+    };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"name":"a","tags":["b"],"count":2}"#);
+    let actual: Sparse = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let value = SparseVariant::A { a: None, b: 1 };
+    assert_eq!(json::to_string(&value, &mut ()), r#"{"A":{"b":1}}"#);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Aliased {
+    #[toctoc(alias = "userName")]
+    #[toctoc(alias = "login")]
+    user_name: String,
+    #[toctoc(rename = "mail", alias = "email")]
+    address: String,
+}
+
+#[test]
+fn test_alias() {
+    let value = Aliased {
+        user_name: "a".to_owned(),
+        address: "b".to_owned(),
+    };
+
+    // Always serialized with the canonical name
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"user_name":"a","mail":"b"}"#);
+    let actual: Aliased = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    for j in &[
+        r#"{"userName":"a","email":"b"}"#,
+        r#"{"login":"a","mail":"b"}"#,
+    ] {
+        let mut j = j.to_string();
+        let actual: Aliased = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(actual, value);
+    }
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+struct Borrowed<'a> {
+    id: u32,
+    #[toctoc(skip)]
+    label: &'a str,
+    #[toctoc(skip)]
+    bytes: &'a [u8],
+    #[toctoc(skip)]
+    pair: (u32, &'a str),
+    #[toctoc(skip)]
+    c_str: &'a std::ffi::CStr,
+    #[toctoc(skip)]
+    os_str: &'a std::ffi::OsStr,
+}
+
+#[test]
+fn test_skip_borrowed() {
+    let mut j = r#"{"id":7,"label":"ignored"}"#.to_string();
+    let actual: Borrowed = json::from_str(&mut j, &mut ()).unwrap();
+    let expected = Borrowed {
+        id: 7,
+        label: "",
+        bytes: &[],
+        pair: (0, ""),
+        c_str: Default::default(),
+        os_str: Default::default(),
+    };
+    assert_eq!(actual, expected);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(transparent)]
+struct Meters(f64);
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(transparent)]
+struct Name {
+    value: String,
+    #[toctoc(skip)]
+    cached_len: usize,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Track {
+    name: Name,
+    lengths: Vec<Meters>,
+}
+
+#[test]
+fn test_transparent() {
+    let value = Track {
+        name: Name {
+            value: "loop".to_owned(),
+            cached_len: 0,
+        },
+        lengths: vec![Meters(1.5), Meters(2.0)],
+    };
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"{"name":"loop","lengths":[1.5,2.0]}"#);
+    let actual: Track = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let bin = toctoc::bson::to_bin(&value, &mut ());
+    let actual: Track = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    // Errors come from the inner type
+    let mut j = r#""not a number""#.to_string();
+    assert!(json::from_str::<Meters>(&mut j, &mut ()).is_err());
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[toctoc(untagged)]
+enum AnyShape {
+    Empty,
+    Id(u32),
+    Pair(u32, String),
+    Circle { r: f64 },
+}
+
+#[test]
+fn test_untagged() {
+    let value = vec![
+        AnyShape::Empty,
+        AnyShape::Id(7),
+        AnyShape::Pair(1, "a".to_owned()),
+        AnyShape::Circle { r: 1.5 },
+    ];
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#"[null,7,[1,"a"],{"r":1.5}]"#);
+    let actual: Vec<AnyShape> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    // Unknown fields are ignored like in any other struct variant
+    let mut j = r#"{"r":2.0,"extra":true}"#.to_string();
+    let actual: AnyShape = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, AnyShape::Circle { r: 2.0 });
+
+    let mut j = r#""circle""#.to_string();
+    assert!(json::from_str::<AnyShape>(&mut j, &mut ()).is_err());
+}
+
+/// Writes `u32` as hexadecimal strings
+mod hex {
+    use toctoc::{de, ser, Error, Result};
+
+    toctoc::make_place!(Place);
+
+    pub fn serialize(n: &u32, v: ser::Visitor, _: &mut dyn ser::Context) -> ser::Done {
+        v.string(&format!("{:06x}", n))
+    }
+
+    impl<'de> de::Visitor<'de> for Place<u32> {
+        fn string(&mut self, s: &'de str, _: &mut dyn de::Context) -> Result<()> {
+            let n = u32::from_str_radix(s, 16).map_err(|err| Error::generic(err.to_string()))?;
+            self.out = Some(n);
+            Ok(())
+        }
+    }
+
+    pub fn deserialize<'de>(out: &mut Option<u32>) -> &mut dyn de::Visitor<'de> {
+        Place::new(out)
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Color {
+    #[toctoc(
+        serialize_with = "hex::serialize",
+        deserialize_with = "hex::deserialize"
+    )]
+    rgb: u32,
+    alpha: u8,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum Paint {
+    Solid {
+        #[toctoc(
+            serialize_with = "hex::serialize",
+            deserialize_with = "hex::deserialize"
+        )]
+        rgb: u32,
+    },
+}
+
+#[test]
+fn test_with() {
+    let color = Color {
+        rgb: 0xff8000,
+        alpha: 255,
+    };
+    let j = json::to_string(&color, &mut ());
+    assert_eq!(j, r#"{"rgb":"ff8000","alpha":255}"#);
+    let mut j = j;
+    let actual: Color = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, color);
+
+    let mut j = r#"{"rgb":"zz","alpha":255}"#.to_string();
+    assert!(json::from_str::<Color>(&mut j, &mut ()).is_err());
+    let mut j = r#"{"alpha":255}"#.to_string();
+    assert!(json::from_str::<Color>(&mut j, &mut ()).is_err());
+
+    let paint = Paint::Solid { rgb: 0x10 };
+    let j = json::to_string(&paint, &mut ());
+    assert_eq!(j, r#"{"Solid":{"rgb":"000010"}}"#);
+    let mut j = j;
+    let actual: Paint = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, paint);
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+#[toctoc(default)]
+struct Settings {
+    width: u32,
+    height: u32,
+    #[toctoc(default = "default_title")]
+    title: String,
+    #[toctoc(skip)]
+    cached: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            width: 800,
+            height: 600,
+            title: "untitled".to_owned(),
+            cached: 7,
+        }
+    }
+}
+
+fn default_title() -> String {
+    "field default".to_owned()
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+#[toctoc(default = "Window::small")]
+struct Window {
+    w: u32,
+    h: u32,
+}
+
+impl Window {
+    fn small() -> Self {
+        Window { w: 64, h: 48 }
+    }
+}
+
+#[test]
+fn test_container_default() {
+    let mut j = r#"{"width":1024}"#.to_string();
+    let actual: Settings = json::from_str(&mut j, &mut ()).unwrap();
+    let expected = Settings {
+        width: 1024,
+        height: 600,
+        // Field defaults win over the container default
+        title: "field default".to_owned(),
+        cached: 7,
+    };
+    assert_eq!(actual, expected);
+
+    let mut j = r#"{"height":1,"title":"a"}"#.to_string();
+    let actual: Settings = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual.width, 800);
+    assert_eq!(actual.height, 1);
+    assert_eq!(actual.title, "a");
+
+    let mut j = "{}".to_string();
+    let actual: Window = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, Window::small());
+
+    let mut j = r#"{"h":100}"#.to_string();
+    let actual: Window = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, Window { w: 64, h: 100 });
+}
+
+static CONNECTION_DEFAULTS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(PartialEq, Debug, Deserialize)]
+#[toctoc(default)]
+struct Connection {
+    host: String,
+    port: u16,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        CONNECTION_DEFAULTS.fetch_add(1, Ordering::SeqCst);
+        Connection {
+            host: "localhost".to_owned(),
+            port: 80,
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {}
+}
+
+#[test]
+fn test_container_default_drop() {
+    let mut j = r#"{"port":8080}"#.to_string();
+    let actual: Connection = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual.host, "localhost");
+    assert_eq!(actual.port, 8080);
+    assert_eq!(CONNECTION_DEFAULTS.load(Ordering::SeqCst), 1);
+
+    // Not built when every field is there
+    let mut j = r#"{"host":"a","port":1}"#.to_string();
+    let actual: Connection = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual.host, "a");
+    assert_eq!(actual.port, 1);
+    assert_eq!(CONNECTION_DEFAULTS.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use toctoc::json::{self, JsonDeOptions};
+use toctoc::{bson, Error, ErrorKind};
+
+#[test]
+fn test_kind() {
+    let mut j = "300".to_string();
+    let err = json::from_str::<u8>(&mut j, &mut ()).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::OutOfRange("u8")));
+
+    let mut j = r#""true""#.to_string();
+    let err = json::from_str::<bool>(&mut j, &mut ()).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ErrorKind::UnexpectedType { got: "string" }
+    ));
+
+    let mut j = "[[[1]]]".to_string();
+    let options = JsonDeOptions { max_depth: 2 };
+    let err = json::from_str_with_options::<json::Value>(&mut j, &mut (), options).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::DepthExceeded(2)));
+
+    let bin = bson::to_bin(&vec![1u32], &mut ());
+    let err = bson::from_bin::<(u32, u32)>(&bin, &mut ()).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::MissingElement(1)));
+}
+
+#[test]
+fn test_constructors() {
+    let err = Error::missing_field("code");
+    assert!(matches!(err.kind(), ErrorKind::MissingField("code")));
+    assert_eq!(err.to_string(), "missing field `code`");
+
+    let err = Error::unknown_variant("C");
+    assert!(matches!(err.kind(), ErrorKind::UnknownVariant(v) if v == "C"));
+
+    let err = Error::invalid_map_key("x".to_string());
+    assert!(matches!(err.kind(), ErrorKind::InvalidMapKey(k) if k == "x"));
+    assert_eq!(err.to_string(), "invalid map key x");
+
+    let err = Error::generic("custom".to_string());
+    assert!(matches!(err.kind(), ErrorKind::Other));
+    assert_eq!(err.to_string(), "custom");
+}
+
+#[test]
+fn test_path() {
+    type Users = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+    let mut j = r#"{"user":{"tags":["a",1]}}"#.to_string();
+    let err = json::from_str::<Users>(&mut j, &mut ()).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ErrorKind::UnexpectedType { got: "nonnegative" }
+    ));
+    // The SIMD deserializer doesn't know the line and column
+    let expected = if cfg!(feature = "simd") {
+        "$.user.tags[1]: not expected `nonnegative`"
+    } else {
+        "$.user.tags[1]: not expected `nonnegative`, 1:22"
+    };
+    assert_eq!(err.to_string(), expected);
+
+    let mut tags = BTreeMap::new();
+    tags.insert("tags", (0u32, "a"));
+    let mut users = BTreeMap::new();
+    users.insert("user", tags);
+    let bin = bson::to_bin(&users, &mut ());
+    let err = bson::from_bin::<Users>(&bin, &mut ()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "$.user.tags[0]: not expected `nonnegative`, 0:36"
+    );
+
+    // Errors outside of any map or sequence don't have a path
+    let mut j = "true".to_string();
+    let err = json::from_str::<u32>(&mut j, &mut ()).unwrap_err();
+    let expected = if cfg!(feature = "simd") {
+        "not expected `boolean`"
+    } else {
+        "not expected `boolean`, 1:4"
+    };
+    assert_eq!(err.to_string(), expected);
+}
+
+#[cfg(not(feature = "simd"))]
+#[test]
+fn test_position() {
+    let mut j = "[1, 2 3]".to_string();
+    let err = json::from_str::<Vec<u32>>(&mut j, &mut ()).unwrap_err();
+    assert_eq!(err.position(), Some((1, 7)));
+    assert!(err.to_string().ends_with(", 1:7"));
+
+    let mut j = "{\n  \"a\": 1,\n  \"b\": tru\n}".to_string();
+    let err = json::from_str::<json::Value>(&mut j, &mut ()).unwrap_err();
+    assert_eq!(err.position(), Some((3, 11)));
+
+    // Unescaped new lines don't count
+    let mut j = "[\"a\\nb\\n\",\n\"\\nc\" x]".to_string();
+    let err = json::from_str::<Vec<String>>(&mut j, &mut ()).unwrap_err();
+    assert_eq!(err.position(), Some((2, 7)));
+
+    // Errors from the visitors point right after the value
+    let mut j = "\n\n  true".to_string();
+    let err = json::from_str::<u32>(&mut j, &mut ()).unwrap_err();
+    assert_eq!(err.position(), Some((3, 6)));
+
+    assert_eq!(Error::missing_field("code").position(), None);
+}
@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+use toctoc::json;
+use toctoc::json::{Array, Number, Object, Value};
+
+#[test]
+fn test_scalars() {
+    assert_eq!(json!(null), Value::Null);
+    assert_eq!(json!(true), Value::Bool(true));
+    assert_eq!(json!(200), Value::Number(Number::U64(200)));
+    assert_eq!(json!(-1), Value::Number(Number::I64(-1)));
+    assert_eq!(json!(0.5), Value::Number(Number::F64(0.5)));
+    assert_eq!(json!("a"), Value::String(Cow::Borrowed("a")));
+    assert_eq!(json!([]), Value::Array(Array::new()));
+    assert_eq!(json!({}), Value::Object(Object::new()));
+}
+
+#[test]
+fn test_nested() {
+    let value = json!({
+        "code": 200,
+        "msg": "ok",
+        "list": [1, -2, [null], {"a": false}],
+    });
+
+    let mut list = Array::new();
+    list.push(Value::Number(Number::U64(1)));
+    list.push(Value::Number(Number::I64(-2)));
+    list.push(Value::Array({
+        let mut array = Array::new();
+        array.push(Value::Null);
+        array
+    }));
+    list.push(Value::Object({
+        let mut object = Object::new();
+        object.insert("a".to_string(), Value::Bool(false));
+        object
+    }));
+
+    let mut expected = Object::new();
+    expected.insert("code".to_string(), Value::Number(Number::U64(200)));
+    expected.insert("msg".to_string(), Value::String(Cow::Borrowed("ok")));
+    expected.insert("list".to_string(), Value::Array(list));
+    assert_eq!(value, Value::Object(expected));
+
+    // Same as the parsed JSON
+    let mut j = r#"{"code":200,"msg":"ok","list":[1,-2,[null],{"a":false}]}"#.to_string();
+    let parsed: Value = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(value, parsed);
+}
+
+#[test]
+fn test_interpolation() {
+    let key = "dyn";
+    let name = String::from("toctoc");
+    let inner = json!([1, 2]);
+    let value = json!({
+        (key): name.len() as u32 + 1,
+        "name": name.clone(),
+        "inner": inner,
+        "sum": [1 + 2, -(3 * 2)],
+    });
+    let mut j = r#"{"dyn":7,"name":"toctoc","inner":[1,2],"sum":[3,-6]}"#.to_string();
+    let parsed: Value = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(value, parsed);
+    assert_eq!(json::to_string(&value["sum"], &mut ()), "[3,-6]");
+
+    // Trailing commas
+    assert_eq!(json::to_string(&json!([1, 2,]), &mut ()), "[1,2]");
+    assert_eq!(
+        json::to_string(&json!({"a": null,}), &mut ()),
+        r#"{"a":null}"#
+    );
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "msgpack")]
+
+use serde::{Deserialize, Serialize};
+use toctoc::bytes::Bytes;
+use toctoc::json::{self, Value};
+use toctoc::msgpack::{self, MsgpackDeOptions};
+use toctoc::{Deserialize as KDeserialize, Serialize as KSerialize};
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, KDeserialize, KSerialize)]
+struct V {
+    string: String,
+    b: bool,
+    int: i32,
+    list: Vec<i64>,
+}
+
+#[test]
+fn test_msgpack_struct() {
+    let v = V {
+        string: "Hi!".to_owned(),
+        b: false,
+        int: -5,
+        list: vec![0, 127, 128, -33, 70_000, i64::min_value()],
+    };
+
+    let bin = msgpack::to_bin(&v, &mut ());
+    assert_eq!(bin, rmp_serde::to_vec_named(&v).unwrap());
+
+    let v1: V = msgpack::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(v1, v);
+}
+
+// With `preserve-order` toctoc keeps the input order while serde_json sorts
+#[cfg(not(feature = "preserve-order"))]
+#[test]
+fn test_msgpack_twitter() {
+    let input = std::fs::read_to_string("benches/twitter.json").unwrap();
+
+    // Both objects are sorted by key
+    let mut j = input.clone();
+    let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    let actual = msgpack::to_bin(&value, &mut ());
+
+    let ground: serde_json::Value = serde_json::from_str(&input).unwrap();
+    let expected = rmp_serde::to_vec(&ground).unwrap();
+    assert_eq!(actual, expected);
+
+    let value1: Value = msgpack::from_bin(&expected, &mut ()).unwrap();
+    assert_eq!(value1, value);
+}
+
+#[test]
+fn test_msgpack_compact() {
+    // fixarray, array16 and array32 headers
+    assert_eq!(msgpack::to_bin(&vec![1u8; 15], &mut ())[0], 0x9F);
+    assert_eq!(
+        &msgpack::to_bin(&vec![1u8; 16], &mut ())[..3],
+        b"\xdc\x00\x10"
+    );
+    let bin = msgpack::to_bin(&vec![1u8; 70_000], &mut ());
+    assert_eq!(&bin[..5], b"\xdd\x00\x01\x11\x70");
+    assert_eq!(bin.len(), 70_005);
+
+    // Nested headers of every size
+    let nested = vec![vec![], vec![2u8; 20], vec![3u8; 70_000], vec![4u8]];
+    let bin = msgpack::to_bin(&nested, &mut ());
+    let mut expected = b"\x94\x90\xdc\x00\x14".to_vec();
+    expected.extend_from_slice(&[2; 20]);
+    expected.extend_from_slice(b"\xdd\x00\x01\x11\x70");
+    expected.extend_from_slice(&[3; 70_000]);
+    expected.extend_from_slice(b"\x91\x04");
+    assert_eq!(bin, expected);
+    let actual: Vec<Vec<u8>> = msgpack::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, nested);
+
+    // fixstr and str8 headers
+    assert_eq!(msgpack::to_bin(&"a", &mut ()), b"\xa1a");
+    assert_eq!(&msgpack::to_bin(&"a".repeat(32), &mut ())[..2], b"\xd9\x20");
+
+    assert_eq!(msgpack::to_bin(&-1i32, &mut ()), b"\xff");
+    assert_eq!(msgpack::to_bin(&1.5f32, &mut ()), b"\xca\x3f\xc0\x00\x00");
+    assert_eq!(msgpack::to_bin(&Option::<u8>::None, &mut ()), b"\xc0");
+}
+
+#[test]
+fn test_msgpack_bytes() {
+    let bin = msgpack::to_bin(&Bytes::new(vec![1u8, 2, 3]), &mut ());
+    assert_eq!(bin, b"\xc4\x03\x01\x02\x03");
+    let v: Bytes<Vec<u8>> = msgpack::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(v, &[1u8, 2, 3][..]);
+}
+
+#[test]
+fn test_msgpack_skip() {
+    // Unknown fields are skipped along with everything nested in them
+    let mut j =
+        r#"{"b":true,"extra":{"x":[1,[2,"three"],{"y":null}]},"int":1,"list":[],"string":""}"#
+            .to_string();
+    let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    let bin = msgpack::to_bin(&value, &mut ());
+    let v: V = msgpack::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(
+        v,
+        V {
+            string: "".to_owned(),
+            b: true,
+            int: 1,
+            list: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_msgpack_errors() {
+    // Truncated input
+    assert!(msgpack::from_bin::<Vec<u8>>(b"\x93\x01\x02", &mut ()).is_err());
+    // Trailing bytes
+    assert!(msgpack::from_bin::<u8>(b"\x01\x02", &mut ()).is_err());
+    // Non string keys
+    assert!(msgpack::from_bin::<Value>(b"\x81\x01\x02", &mut ()).is_err());
+    // Invalid utf8
+    assert!(msgpack::from_bin::<String>(b"\xa1\xff", &mut ()).is_err());
+
+    // Same default depth limit of the other formats
+    let mut nested = vec![0x91; 128];
+    nested.push(0x01);
+    assert!(msgpack::from_bin::<Value>(&nested, &mut ()).is_ok());
+    let nested = [0x91; 200];
+    let err = msgpack::from_bin::<Value>(&nested, &mut ()).unwrap_err();
+    assert!(matches!(err.kind(), toctoc::ErrorKind::DepthExceeded(128)));
+
+    let options = MsgpackDeOptions { max_depth: 10 };
+    let nested = [0x91; 100];
+    let err = msgpack::from_bin_with_options::<Value>(&nested, &mut (), options).unwrap_err();
+    assert!(matches!(err.kind(), toctoc::ErrorKind::DepthExceeded(10)));
+}
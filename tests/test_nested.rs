@@ -1,7 +1,7 @@
 #[test]
 #[cfg(feature = "deeply-nested")]
 fn test_round_trip_deeply_nested() {
-    use toctoc::json::{self, Value};
+    use toctoc::json::{self, JsonDeOptions, Value};
 
     let mut j = String::new();
     for _ in 0..100_000 {
@@ -12,7 +12,128 @@ fn test_round_trip_deeply_nested() {
     }
 
     let mut jc = j.clone();
-    let value: Value = json::from_str(&mut jc, &mut ()).unwrap();
+    let options = JsonDeOptions {
+        max_depth: usize::MAX,
+    };
+    let value: Value = json::from_str_with_options(&mut jc, &mut (), options).unwrap();
     let j2 = json::to_string(&value, &());
     assert_eq!(j, j2);
 }
+
+#[derive(toctoc::Deserialize, Debug, PartialEq)]
+struct Shallow {
+    a: u32,
+}
+
+#[cfg(not(feature = "simd"))]
+#[test]
+fn test_ignore_deeply_nested_json() {
+    use toctoc::json;
+
+    let mut j = String::from(r#"{"b":"#);
+    for _ in 0..10_000 {
+        j.push_str("[{\"x\":");
+    }
+    j.push_str("null");
+    for _ in 0..10_000 {
+        j.push_str("}]");
+    }
+    j.push_str(r#","a":1}"#);
+
+    let s: Shallow = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(s, Shallow { a: 1 });
+}
+
+#[test]
+fn test_ignore_deeply_nested_bson() {
+    use toctoc::bson;
+
+    // Builds the nested documents from the inside out, `[[[...]]]`
+    let mut doc = vec![5, 0, 0, 0, 0];
+    for _ in 0..10_000 {
+        let mut outer = vec![0; 4];
+        outer.extend_from_slice(&[0x04, b'0', 0]);
+        outer.extend_from_slice(&doc);
+        outer.push(0);
+        let size = outer.len() as u32;
+        outer[..4].copy_from_slice(&size.to_le_bytes());
+        doc = outer;
+    }
+
+    // Root document `{ "b": [[[...]]], "a": 1 }`
+    let mut bin = vec![0; 4];
+    bin.extend_from_slice(&[0x03, 0]);
+    let mut root = vec![0; 4];
+    root.extend_from_slice(&[0x04, b'b', 0]);
+    root.extend_from_slice(&doc);
+    root.extend_from_slice(&[0x83, b'a', 0, 1, 0, 0, 0, 0]);
+    let size = root.len() as u32;
+    root[..4].copy_from_slice(&size.to_le_bytes());
+    bin.extend_from_slice(&root);
+    bin.push(0);
+    let size = bin.len() as u32;
+    bin[..4].copy_from_slice(&size.to_le_bytes());
+
+    let s: Shallow = bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(s, Shallow { a: 1 });
+}
+
+#[test]
+fn test_json_depth_limit() {
+    use toctoc::json::{self, JsonDeOptions, Value};
+    use toctoc::ErrorKind;
+
+    let nested = |depth: usize| {
+        let mut j = String::new();
+        for i in 0..depth {
+            j.push_str(if i % 2 == 0 { "[" } else { "{\"x\":" });
+        }
+        j.push_str("null");
+        for i in (0..depth).rev() {
+            j.push(if i % 2 == 0 { ']' } else { '}' });
+        }
+        j
+    };
+
+    let options = JsonDeOptions::default();
+    assert_eq!(options.max_depth, 128);
+
+    let mut j = nested(128);
+    let r: toctoc::Result<Value> = json::from_str_with_options(&mut j, &mut (), options);
+    assert!(r.is_ok());
+
+    let mut j = nested(129);
+    let r: toctoc::Result<Value> = json::from_str_with_options(&mut j, &mut (), options);
+    let position = if cfg!(feature = "simd") {
+        ""
+    } else {
+        ", 1:385"
+    };
+    assert_eq!(
+        r.unwrap_err().to_string(),
+        format!(
+            "${}: maximum nesting depth of 128 exceeded{}",
+            "[0].x".repeat(64),
+            position
+        )
+    );
+
+    // Without options the default limit applies
+    let mut j = nested(128);
+    let r: toctoc::Result<Value> = json::from_str(&mut j, &mut ());
+    assert!(r.is_ok());
+
+    let mut j = nested(1_000_000);
+    let r: toctoc::Result<Value> = json::from_str(&mut j, &mut ());
+    assert!(matches!(
+        r.unwrap_err().kind(),
+        ErrorKind::DepthExceeded(128)
+    ));
+
+    let mut j = nested(1_000_000).into_bytes();
+    let r: toctoc::Result<Value> = json::from_slice(&mut j, &mut ());
+    assert!(matches!(
+        r.unwrap_err().kind(),
+        ErrorKind::DepthExceeded(128)
+    ));
+}
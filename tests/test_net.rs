@@ -0,0 +1,46 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use toctoc::{bson, json};
+
+macro_rules! round_trip {
+    ($ty:ty, $s:expr) => {{
+        let addr: $ty = $s.parse().unwrap();
+        let mut j = json::to_string(&addr, &mut ());
+        assert_eq!(j, format!("\"{}\"", $s));
+        let actual: $ty = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(actual, addr);
+
+        let bin = bson::to_bin(&addr, &mut ());
+        let actual: $ty = bson::from_bin(&bin, &mut ()).unwrap();
+        assert_eq!(actual, addr);
+    }};
+}
+
+#[test]
+fn test_round_trip() {
+    round_trip!(IpAddr, "127.0.0.1");
+    round_trip!(IpAddr, "::1");
+    round_trip!(Ipv4Addr, "192.168.0.1");
+    round_trip!(Ipv6Addr, "2001:db8::ff00:42:8329");
+    round_trip!(SocketAddr, "127.0.0.1:8080");
+    round_trip!(SocketAddr, "[::1]:443");
+    round_trip!(SocketAddrV4, "10.0.0.1:22");
+    round_trip!(SocketAddrV6, "[fe80::1]:9000");
+}
+
+#[test]
+fn test_invalid() {
+    let mut j = r#""::1""#.to_string();
+    let err = json::from_str::<Ipv4Addr>(&mut j, &mut ()).unwrap_err();
+    let expected = if cfg!(feature = "simd") {
+        "invalid `Ipv4Addr` string `::1`"
+    } else {
+        "invalid `Ipv4Addr` string `::1`, 1:5"
+    };
+    assert_eq!(err.to_string(), expected);
+
+    let mut j = r#""127.0.0.1""#.to_string();
+    assert!(json::from_str::<SocketAddr>(&mut j, &mut ()).is_err());
+
+    let mut j = "1".to_string();
+    assert!(json::from_str::<IpAddr>(&mut j, &mut ()).is_err());
+}
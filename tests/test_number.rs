@@ -15,3 +15,270 @@ fn test_ser() {
         assert_eq!(actual, *expected);
     }
 }
+
+#[test]
+fn test_de() {
+    let cases = &[
+        ("0", 0.0),
+        ("1", 1.0),
+        ("-1", -1.0),
+        ("1.5", 1.5),
+        ("-1.5", -1.5),
+        ("0.25", 0.25),
+        ("1e3", 1000.0),
+        ("1E3", 1000.0),
+        ("1e+3", 1000.0),
+        ("1e-3", 0.001),
+        ("1.23e+10", 1.23e10),
+        ("-1.23E-10", -1.23e-10),
+        ("1e308", 1e308),
+        ("18446744073709551616", 18446744073709551616.0),
+        ("-9223372036854775809", -9223372036854775809.0),
+    ];
+
+    for (json, expected) in cases {
+        let mut json = json.to_string();
+        let actual: f64 = json::from_str(&mut json, &mut ()).unwrap();
+        assert_eq!(actual, *expected, "{}", json);
+    }
+}
+
+#[test]
+fn test_de_zero() {
+    let mut j = "-0.0".to_string();
+    let n: f64 = json::from_str(&mut j, &mut ()).unwrap();
+    assert!(n == 0.0 && n.is_sign_negative());
+
+    // Denormals and smaller numbers underflow to zero
+    let mut j = "1.0e-400".to_string();
+    let n: f64 = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(n, 0.0);
+
+    let mut j = "-1.0e-400".to_string();
+    let n: f64 = json::from_str(&mut j, &mut ()).unwrap();
+    assert!(n == 0.0 && n.is_sign_negative());
+}
+
+// ? NOTE: simd-json reports `-0` as an integer, so the sign is lost
+#[cfg(not(feature = "simd"))]
+#[test]
+fn test_de_negative_zero() {
+    let mut j = "-0".to_string();
+    let n: f64 = json::from_str(&mut j, &mut ()).unwrap();
+    assert!(n == 0.0 && n.is_sign_negative());
+}
+
+#[test]
+fn test_de_out_of_range() {
+    for json in &["1e309", "-1e309", "1.8e308", "1e999999999999"] {
+        let mut json = json.to_string();
+        let actual: toctoc::Result<f64> = json::from_str(&mut json, &mut ());
+        assert!(actual.is_err(), "{}", json);
+    }
+}
+
+#[test]
+fn test_de_invalid() {
+    for json in &["01", "1.", ".5", "1e", "1e+", "-", "+1", "1.e3"] {
+        let mut json = json.to_string();
+        let actual: toctoc::Result<f64> = json::from_str(&mut json, &mut ());
+        assert!(actual.is_err(), "{}", json);
+    }
+}
+
+#[test]
+fn test_short_round_trip() {
+    for n in &[i16::MIN, -1, 0, 1, i16::MAX] {
+        let mut j = json::to_string(n, &mut ());
+        assert_eq!(j, n.to_string());
+        let actual: i16 = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(actual, *n);
+    }
+
+    for n in &[0, 1, u16::MAX] {
+        let mut j = json::to_string(n, &mut ());
+        assert_eq!(j, n.to_string());
+        let actual: u16 = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(actual, *n);
+    }
+}
+
+#[test]
+fn test_nonzero() {
+    use std::num::{NonZeroI32, NonZeroI8, NonZeroU64};
+
+    let mut j = json::to_string(&NonZeroI32::new(-7).unwrap(), &mut ());
+    assert_eq!(j, "-7");
+    let actual: NonZeroI32 = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual.get(), -7);
+
+    let mut j = json::to_string(&NonZeroU64::new(u64::MAX).unwrap(), &mut ());
+    let actual: NonZeroU64 = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual.get(), u64::MAX);
+
+    // Zero is rejected
+    let mut j = "0".to_string();
+    let err = json::from_str::<NonZeroI32>(&mut j, &mut ()).unwrap_err();
+    let expected = if cfg!(feature = "simd") {
+        "out of range of `NonZeroI32`"
+    } else {
+        "out of range of `NonZeroI32`, 1:1"
+    };
+    assert_eq!(format!("{:?}", err), expected);
+
+    // The range is checked by the inner primitive
+    let mut j = "-129".to_string();
+    assert!(json::from_str::<NonZeroI8>(&mut j, &mut ()).is_err());
+}
+
+#[test]
+fn test_range() {
+    let mut j = json::to_string(&(2..5u32), &mut ());
+    assert_eq!(j, "[2,5]");
+    let actual: std::ops::Range<u32> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, 2..5);
+
+    let mut j = json::to_string(&(-1.5..=1.5f64), &mut ());
+    assert_eq!(j, "[-1.5,1.5]");
+    let actual: std::ops::RangeInclusive<f64> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, -1.5..=1.5);
+
+    // Exactly two elements are required
+    for j in &["[]", "[1]", "[1,2,3]", "{}"] {
+        let mut j = j.to_string();
+        assert!(
+            json::from_str::<std::ops::Range<u32>>(&mut j, &mut ()).is_err(),
+            "{}",
+            j
+        );
+        let mut j = j.to_string();
+        assert!(json::from_str::<std::ops::RangeInclusive<u32>>(&mut j, &mut ()).is_err());
+    }
+}
+
+#[test]
+fn test_128() {
+    // Values in the 64 bits range are written as numbers
+    let mut j = json::to_string(&-5i128, &mut ());
+    assert_eq!(j, "-5");
+    let actual: i128 = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, -5);
+
+    // Otherwise as strings to not lose any precision
+    for n in &[
+        i128::min_value(),
+        i64::min_value() as i128 - 1,
+        i128::max_value(),
+    ] {
+        let mut j = json::to_string(n, &mut ());
+        assert_eq!(j, format!("\"{}\"", n));
+        let actual: i128 = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(actual, *n);
+    }
+
+    let mut j = json::to_string(&u128::max_value(), &mut ());
+    assert_eq!(j, format!("\"{}\"", u128::max_value()));
+    let actual: u128 = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, u128::max_value());
+
+    let mut j = r#""12a""#.to_string();
+    let err = json::from_str::<u128>(&mut j, &mut ()).unwrap_err();
+    let expected = if cfg!(feature = "simd") {
+        "invalid `u128` string `12a`"
+    } else {
+        "invalid `u128` string `12a`, 1:5"
+    };
+    assert_eq!(format!("{:?}", err), expected);
+
+    let mut j = "-1".to_string();
+    assert!(json::from_str::<u128>(&mut j, &mut ()).is_err());
+}
+
+#[test]
+fn test_arithmetic() {
+    use json::Number::{self, *};
+
+    let cases: &[(Number, Number, Number, Number, Number, Number)] = &[
+        // a, b, a + b, a - b, a * b, a / b
+        (U64(7), U64(2), U64(9), U64(5), U64(14), F64(3.5)),
+        (U64(2), U64(7), U64(9), I64(-5), U64(14), F64(2.0 / 7.0)),
+        (U64(8), I64(-2), U64(6), U64(10), I64(-16), I64(-4)),
+        (I64(-8), U64(2), I64(-6), I64(-10), I64(-16), I64(-4)),
+        (I64(-3), I64(-3), I64(-6), U64(0), U64(9), U64(1)),
+        (U64(1), F32(0.5), F32(1.5), F32(0.5), F32(0.5), F32(2.0)),
+        (F32(0.5), I64(-1), F32(-0.5), F32(1.5), F32(-0.5), F32(-0.5)),
+        (
+            F32(0.5),
+            F64(0.25),
+            F64(0.75),
+            F64(0.25),
+            F64(0.125),
+            F64(2.0),
+        ),
+        (I64(1), F64(0.5), F64(1.5), F64(0.5), F64(0.5), F64(2.0)),
+    ];
+    for (a, b, add, sub, mul, div) in cases {
+        assert_eq!(a.clone() + b.clone(), *add, "{:?} + {:?}", a, b);
+        assert_eq!(a.clone() - b.clone(), *sub, "{:?} - {:?}", a, b);
+        assert_eq!(a.clone() * b.clone(), *mul, "{:?} * {:?}", a, b);
+        assert_eq!(a.clone() / b.clone(), *div, "{:?} / {:?}", a, b);
+    }
+
+    // Overflowing both integer representations promotes to `F64`
+    assert_eq!(U64(u64::MAX) + U64(1), F64(u64::MAX as f64 + 1.0));
+    assert_eq!(I64(i64::MIN) - U64(1), F64(i64::MIN as f64 - 1.0));
+    assert_eq!(
+        U64(u64::MAX) * U64(u64::MAX),
+        F64(u64::MAX as f64 * u64::MAX as f64)
+    );
+
+    // Results fitting in the other representation don't
+    assert_eq!(I64(i64::MIN) * I64(-1), U64(i64::MAX as u64 + 1));
+    assert_eq!(I64(i64::MIN) / I64(-1), U64(i64::MAX as u64 + 1));
+    assert_eq!(U64(0) - U64(u64::MAX), F64(-(u64::MAX as f64)));
+    assert_eq!(U64(0) - U64(i64::MAX as u64 + 1), I64(i64::MIN));
+
+    // Division by zero
+    assert_eq!(U64(1) / U64(0), F64(f64::INFINITY));
+    assert_eq!(I64(-1) / U64(0), F64(f64::NEG_INFINITY));
+    assert!(matches!(U64(0) / U64(0), F64(n) if n.is_nan()));
+}
+
+#[test]
+fn test_ordering() {
+    use json::Number::*;
+    use std::cmp::Ordering::*;
+
+    assert!(U64(1) < U64(2));
+    assert!(I64(-1) < U64(0));
+    assert!(I64(i64::MIN) < I64(i64::MIN + 1));
+    assert!(F32(0.5) < U64(1));
+    assert!(I64(-2) < F64(-1.5));
+    assert!(F64(2.5) > F32(2.25));
+
+    // Integers are compared exactly, even when `f64` can't tell them apart
+    assert!(U64(u64::MAX - 1) < U64(u64::MAX));
+    assert!(I64(i64::MIN) < I64(i64::MIN + 1));
+
+    // Ties between representations agree with `==`
+    assert_eq!(U64(1).partial_cmp(&U64(1)), Some(Equal));
+    assert_eq!(U64(1).partial_cmp(&I64(1)), Some(Less));
+    assert_eq!(F64(1.0).partial_cmp(&U64(1)), Some(Greater));
+    assert_eq!(F64(f64::NAN).partial_cmp(&F64(f64::NAN)), None);
+    assert_eq!(U64(1).partial_cmp(&F32(f32::NAN)), None);
+}
+
+#[test]
+fn test_getters() {
+    use json::Number::*;
+
+    assert_eq!(U64(5).as_f64(), 5.0);
+    assert_eq!(I64(-5).as_f64(), -5.0);
+    assert_eq!(F32(0.5).as_f64(), 0.5);
+    assert_eq!(F64(0.1).as_f64(), 0.1);
+
+    assert_eq!(U64(i64::MAX as u64).as_i64(), Some(i64::MAX));
+    assert_eq!(U64(i64::MAX as u64 + 1).as_i64(), None);
+    assert_eq!(I64(i64::MIN).as_i64(), Some(i64::MIN));
+    assert_eq!(F64(1.0).as_i64(), None);
+}
@@ -0,0 +1,124 @@
+use std::collections::{BTreeMap, HashMap};
+use toctoc::json::{self, JsonSerOptions, NonFiniteMode, Value};
+
+#[test]
+fn test_default_options() {
+    let mut map = BTreeMap::new();
+    map.insert("a", vec![1.5, f64::NAN]);
+    map.insert("ñ", vec![]);
+
+    let j = json::to_string_with_options(&map, &mut (), JsonSerOptions::default()).unwrap();
+    assert_eq!(j, json::to_string(&map, &mut ()));
+    assert_eq!(j, r#"{"a":[1.5,null],"ñ":[]}"#);
+}
+
+#[test]
+fn test_sort_keys() {
+    let mut j = r#"{"zeta":[{"y":1,"x":2}],"alpha":{"b":{},"a":[]},"mid":null}"#.to_string();
+    let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+
+    let mut map = HashMap::new();
+    for i in 0..100u32 {
+        map.insert(i.to_string(), i);
+    }
+
+    let options = JsonSerOptions {
+        sort_keys: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        json::to_string_with_options(&value, &mut (), options.clone()).unwrap(),
+        r#"{"alpha":{"a":[],"b":{}},"mid":null,"zeta":[{"x":2,"y":1}]}"#
+    );
+
+    let sorted: BTreeMap<_, _> = map.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    assert_eq!(
+        json::to_string_with_options(&map, &mut (), options).unwrap(),
+        json::to_string(&sorted, &mut ()),
+    );
+
+    let options = JsonSerOptions {
+        indent: Some("  ".to_string()),
+        sort_keys: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        json::to_string_with_options(&value, &mut (), options).unwrap(),
+        r#"{
+  "alpha": {
+    "a": [],
+    "b": {}
+  },
+  "mid": null,
+  "zeta": [
+    {
+      "x": 2,
+      "y": 1
+    }
+  ]
+}"#
+    );
+}
+
+#[test]
+fn test_escape_non_ascii() {
+    let mut map = BTreeMap::new();
+    map.insert("clé", "ünicode ✓ 😀\n");
+
+    let options = JsonSerOptions {
+        escape_non_ascii: true,
+        ..Default::default()
+    };
+    let mut j = json::to_string_with_options(&map, &mut (), options).unwrap();
+    assert_eq!(j, r#"{"cl\u00e9":"\u00fcnicode \u2713 \ud83d\ude00\n"}"#);
+
+    let out: BTreeMap<String, String> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(out["clé"], "ünicode ✓ 😀\n");
+}
+
+#[test]
+fn test_non_finite() {
+    let v = vec![1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+    let s = vec![f32::NAN, f32::NEG_INFINITY];
+
+    let options = |non_finite| JsonSerOptions {
+        non_finite,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        json::to_string_with_options(&v, &mut (), options(NonFiniteMode::Null)).unwrap(),
+        "[1.0,null,null,null]"
+    );
+    assert_eq!(
+        json::to_string_with_options(&v, &mut (), options(NonFiniteMode::String)).unwrap(),
+        r#"[1.0,"NaN","Infinity","-Infinity"]"#
+    );
+    assert_eq!(
+        json::to_string_with_options(&s, &mut (), options(NonFiniteMode::String)).unwrap(),
+        r#"["NaN","-Infinity"]"#
+    );
+    assert!(json::to_string_with_options(&v, &mut (), options(NonFiniteMode::Error)).is_err());
+    assert!(json::to_string_with_options(&s, &mut (), options(NonFiniteMode::Error)).is_err());
+    assert!(
+        json::to_string_with_options(&vec![1.0], &mut (), options(NonFiniteMode::Error)).is_ok()
+    );
+}
+
+#[test]
+fn test_all_options() {
+    let mut map = HashMap::new();
+    map.insert("ö", f64::INFINITY);
+    map.insert("a", 0.5);
+
+    let options = JsonSerOptions {
+        indent: Some("\t".to_string()),
+        sort_keys: true,
+        escape_non_ascii: true,
+        non_finite: NonFiniteMode::String,
+    };
+    assert_eq!(
+        json::to_string_with_options(&map, &mut (), options).unwrap(),
+        "{\n\t\"a\": 0.5,\n\t\"\\u00f6\": \"Infinity\"\n}"
+    );
+}
@@ -0,0 +1,39 @@
+use std::path::{Path, PathBuf};
+use toctoc::{bson, json};
+
+#[test]
+fn test_round_trip() {
+    let sep = std::path::MAIN_SEPARATOR;
+    let cases = [
+        "file.txt".to_string(),
+        "with spaces/and more spaces.txt".to_string(),
+        "ünicode/日本語/😀.json".to_string(),
+        format!("{0}root{0}dir{0}file", sep),
+    ];
+
+    for case in &cases {
+        let path = PathBuf::from(case);
+        let mut j = json::to_string(&path, &mut ());
+        assert_eq!(j, json::to_string(case, &mut ()));
+        let actual: PathBuf = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(actual, path);
+
+        let bin = bson::to_bin(&path, &mut ());
+        let actual: PathBuf = bson::from_bin(&bin, &mut ()).unwrap();
+        assert_eq!(actual, path);
+    }
+
+    // Borrowed paths
+    let path = Path::new("a b/c");
+    assert_eq!(json::to_string(&path, &mut ()), r#""a b/c""#);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = Path::new(OsStr::from_bytes(b"a\xffb"));
+    assert_eq!(json::to_string(&path, &mut ()), "\"a\u{fffd}b\"");
+}
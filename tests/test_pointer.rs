@@ -0,0 +1,48 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use toctoc::bytes::Bytes;
+use toctoc::{bson, json};
+
+#[test]
+fn test_json() {
+    let value = Arc::new("shared".to_string());
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, r#""shared""#);
+    let actual: Arc<String> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let value = Rc::new(vec![1u32, 2, 3]);
+    let mut j = json::to_string(&value, &mut ());
+    assert_eq!(j, "[1,2,3]");
+    let actual: Rc<Vec<u32>> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, value);
+}
+
+#[test]
+fn test_bson() {
+    let value = Arc::new("shared".to_string());
+    let bin = bson::to_bin(&value, &mut ());
+    assert_eq!(bin, bson::to_bin(&*value, &mut ()));
+    let actual: Arc<String> = bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, value);
+
+    let value = Rc::new(vec![1u32, 2, 3]);
+    let bin = bson::to_bin(&value, &mut ());
+    assert_eq!(bin, bson::to_bin(&*value, &mut ()));
+    let actual: Rc<Vec<u32>> = bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual, value);
+}
+
+#[test]
+fn test_forwarded_visits() {
+    // BSON visits `uint128`
+    let bin = bson::to_bin(&u128::max_value(), &mut ());
+    let actual: Rc<u128> = bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(*actual, u128::max_value());
+
+    // BSON visits `bytes`
+    let value = Bytes::new(vec![1u32, 2, 3]);
+    let bin = bson::to_bin(&value, &mut ());
+    let actual: Arc<Bytes<Vec<u32>>> = bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(*actual, &[1, 2, 3][..]);
+}
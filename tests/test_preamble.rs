@@ -0,0 +1,95 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use toctoc::de::{self, Context, Visitor};
+use toctoc::export::{Asset, AssetHandle, Hint};
+use toctoc::json::{self, PreambleContext, Value};
+use toctoc::{Deserialize, Result};
+
+toctoc::make_place!(Place);
+
+/// Asset registry filled from the preamble
+#[derive(Default)]
+struct Registry {
+    ids: HashMap<String, u32>,
+}
+
+impl Context for Registry {
+    fn asset(&mut self, a: Hint) -> Result<Asset> {
+        match a {
+            Hint::Str(name) => match self.ids.get(name) {
+                Some(&id) => Ok(Asset {
+                    handle: AssetHandle::Plain(id),
+                    id: (TypeId::of::<Texture>(), id),
+                }),
+                None => Err(toctoc::Error::generic(format!("unknown asset `{}`", name)))?,
+            },
+            _ => Err(toctoc::Error::not_expected("asset hint"))?,
+        }
+    }
+}
+
+impl PreambleContext for Registry {
+    fn load_preamble(&mut self, v: &Value) -> Result<()> {
+        if let Value::Object(object) = v {
+            for (name, id) in object {
+                if let Value::Number(json::Number::U64(id)) = id {
+                    self.ids.insert(name.clone(), *id as u32);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Handle to a texture asset referenced by name
+#[derive(Debug, PartialEq)]
+struct Texture(u32);
+
+impl<'de> Deserialize<'de> for Texture {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor<'de> {
+        impl<'de> Visitor<'de> for Place<Texture> {
+            fn string(&mut self, s: &'de str, c: &mut dyn de::Context) -> Result<()> {
+                match c.asset(Hint::Str(s))?.handle {
+                    AssetHandle::Plain(id) => self.out = Some(Texture(id)),
+                    _ => unreachable!(),
+                }
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Material {
+    albedo: Texture,
+    normal: Texture,
+}
+
+#[test]
+fn test_preamble() {
+    let mut j = r#"{
+        "albedo": "wood",
+        "normal": "wood_normal",
+        "assets": {"wood": 3, "wood_normal": 7}
+    }"#
+    .to_string();
+
+    let mut registry = Registry::default();
+    let material: Material = json::from_str_with_preamble(&mut j, &mut registry, "assets").unwrap();
+    assert_eq!(
+        material,
+        Material {
+            albedo: Texture(3),
+            normal: Texture(7),
+        }
+    );
+}
+
+#[test]
+fn test_preamble_missing() {
+    let mut j = r#"{"albedo":"wood","normal":"wood"}"#.to_string();
+    let mut registry = Registry::default();
+    let r: Result<Material> = json::from_str_with_preamble(&mut j, &mut registry, "assets");
+    assert!(r.is_err());
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use toctoc::json::{self, Value};
+
+#[test]
+fn test_pretty() {
+    let mut map = BTreeMap::new();
+    map.insert("a", vec![1, 2]);
+    map.insert("b", vec![]);
+    let j = json::to_string_pretty(&(map, "c", ()), &mut (), "  ");
+    assert_eq!(
+        j,
+        r#"[
+  {
+    "a": [
+      1,
+      2
+    ],
+    "b": []
+  },
+  "c",
+  null
+]"#
+    );
+
+    let j = json::to_string_pretty(&BTreeMap::<String, u32>::new(), &mut (), "\t");
+    assert_eq!(j, "{}");
+    let j = json::to_string_pretty(&vec![vec![1]], &mut (), "\t");
+    assert_eq!(j, "[\n\t[\n\t\t1\n\t]\n]");
+}
+
+#[test]
+fn test_pretty_twitter() {
+    let input = std::fs::read_to_string("benches/twitter.json").unwrap();
+
+    let mut j = input.clone();
+    let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    let actual = json::to_string_pretty(&value, &mut (), "  ");
+
+    let value: serde_json::Value = serde_json::from_str(&input).unwrap();
+    let expected = serde_json::to_string_pretty(&value).unwrap();
+
+    // Same document, regardless of the order of the keys
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&actual).unwrap(),
+        value
+    );
+
+    // Same layout, with `preserve-order` toctoc keeps the input order while
+    // serde_json sorts, so lines are compared without their trailing commas
+    let lines = |s: &str| {
+        let mut lines: Vec<String> = s
+            .lines()
+            .map(|l| l.trim_end_matches(',').to_owned())
+            .collect();
+        lines.sort_unstable();
+        lines
+    };
+    assert_eq!(lines(&actual), lines(&expected));
+}
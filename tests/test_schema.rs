@@ -0,0 +1,66 @@
+use toctoc::json::{self, Value};
+use toctoc::Serialize;
+
+#[derive(Serialize)]
+struct Example {
+    code: u32,
+    message: String,
+    tags: Vec<String>,
+}
+
+const SCHEMA: &str = r#"{
+    "type": "object",
+    "required": ["code", "message"],
+    "properties": {
+        "code": { "type": "integer", "minimum": 100, "maximum": 599 },
+        "message": { "type": "string", "minLength": 1, "maxLength": 16 },
+        "tags": { "type": "array", "items": { "type": "string" } }
+    }
+}"#;
+
+fn validate(json: &str) -> toctoc::Result<()> {
+    let mut s = SCHEMA.to_string();
+    let schema: Value = json::from_str(&mut s, &mut ()).unwrap();
+    let mut j = json.to_string();
+    let value: Value = json::from_str(&mut j, &mut ()).unwrap();
+    value.schema_validate(&schema)
+}
+
+#[test]
+fn test_schema_valid() {
+    let example = Example {
+        code: 200,
+        message: "reminiscent".to_string(),
+        tags: vec!["a".to_string()],
+    };
+    validate(&json::to_string(&example, &mut ())).unwrap();
+
+    // Properties not present aren't validated
+    validate(r#"{"code":404,"message":"not found"}"#).unwrap();
+}
+
+#[test]
+fn test_schema_invalid() {
+    let cases = &[
+        r#"[]"#,
+        r#"{"code":200}"#,
+        r#"{"code":"200","message":"ok"}"#,
+        r#"{"code":200.5,"message":"ok"}"#,
+        r#"{"code":99,"message":"ok"}"#,
+        r#"{"code":600,"message":"ok"}"#,
+        r#"{"code":200,"message":""}"#,
+        r#"{"code":200,"message":"reminiscent of serde"}"#,
+        r#"{"code":200,"message":"ok","tags":["a",1]}"#,
+        r#"{"code":200,"message":"ok","tags":"a"}"#,
+    ];
+
+    for json in cases {
+        assert!(validate(json).is_err(), "{}", json);
+    }
+}
+
+#[test]
+fn test_schema_error_path() {
+    let err = validate(r#"{"code":200,"message":"ok","tags":["a",1]}"#).unwrap_err();
+    assert!(err.to_string().starts_with("#/tags/1:"), "{}", err);
+}
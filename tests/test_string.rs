@@ -0,0 +1,359 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, LinkedList, VecDeque};
+use toctoc::json::{self, JsonSer, Value};
+
+#[test]
+fn test_escaped() {
+    let mut j = r#""hello\nworld""#.to_string();
+    let actual: String = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, "hello\nworld");
+
+    let mut j = r#""\"\\\/\b\f\n\r\t""#.to_string();
+    let actual: String = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, "\"\\/\x08\x0c\n\r\t");
+}
+
+#[test]
+fn test_escaped_unicode() {
+    let mut j = r#""café ☺ 😀""#.to_string();
+    let actual: String = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, "café ☺ 😀");
+
+    let mut j = r#""caf\u00e9 \u263A""#.to_string();
+    let actual: String = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, "café ☺");
+
+    // Surrogate pair
+    let mut j = r#""\ud83d\ude00""#.to_string();
+    let actual: String = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, "😀");
+}
+
+#[test]
+fn test_escaped_in_place() {
+    let mut j = r#"["a\tb \u00e9 \ud83d\ude00", "plain"]"#.to_string();
+    let range = j.as_bytes().as_ptr_range();
+    let actual: Vec<&str> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, vec!["a\tb é 😀", "plain"]);
+
+    // Unescaped into the input itself, no copies
+    for s in &actual {
+        assert!(range.contains(&s.as_ptr()));
+    }
+}
+
+#[test]
+fn test_escaped_leaves_valid_utf8() {
+    // Unescaping shifts the bytes, `€` would be left cut in half
+    for input in &[
+        r#"["\n€"]"#,
+        r#"["\u00e9\\😀"]"#,
+        r#"["\n€", tru]"#,
+        r#"["\n€\x"]"#,
+    ] {
+        let mut j = input.to_string();
+        let _ = json::from_str::<Vec<&str>>(&mut j, &mut ());
+        assert!(std::str::from_utf8(j.as_bytes()).is_ok(), "{}", input);
+    }
+}
+
+#[test]
+fn test_escaped_zerocopy() {
+    let mut j = r#"["hello\nworld", "plain"]"#.to_string();
+    let actual: Vec<&str> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, vec!["hello\nworld", "plain"]);
+}
+
+#[test]
+fn test_escaped_key() {
+    let mut j = r#"{"a\tb": "cd"}"#.to_string();
+    let actual: Value = json::from_str(&mut j, &mut ()).unwrap();
+    match actual {
+        Value::Object(object) => {
+            assert_eq!(object.get("a\tb"), Some(&Value::String("cd".into())));
+        }
+        _ => panic!("expecting object"),
+    }
+}
+
+#[test]
+fn test_invalid_escape() {
+    for j in &[r#""\x""#, r#""\u12""#, r#""\udc00""#, r#""unterminated"#] {
+        let mut j = j.to_string();
+        let actual: toctoc::Result<String> = json::from_str(&mut j, &mut ());
+        assert!(actual.is_err());
+    }
+}
+
+#[test]
+fn test_control_character() {
+    // Every position around the 8 bytes chunks of the scan
+    for i in 0..20 {
+        let mut text: Vec<char> = "aé".repeat(10).chars().collect();
+        text.insert(i, '\t');
+        let text: String = text.into_iter().collect();
+        let mut j = format!("\"{}\"", text);
+        let actual: toctoc::Result<String> = json::from_str(&mut j, &mut ());
+        assert!(actual.is_err(), "{}", i);
+
+        let mut j = format!("\"{}\"", text.replace('\t', "~"));
+        let actual: String = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(actual, text.replace('\t', "~"));
+    }
+}
+
+#[test]
+fn test_ser_escaped_key() {
+    let mut map = BTreeMap::new();
+    map.insert("new\nline".to_string(), 1);
+    map.insert("\"quoted\"".to_string(), 2);
+    map.insert("back\\slash".to_string(), 3);
+
+    let mut j = json::to_string(&map, &mut ());
+    assert_eq!(j, r#"{"\"quoted\"":2,"back\\slash":3,"new\nline":1}"#);
+
+    let actual: BTreeMap<String, i32> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, map);
+}
+
+#[test]
+fn test_ser_raw_key() {
+    use toctoc::ser::{Context, Done, Visitor};
+    use toctoc::Serialize;
+
+    struct Raw;
+
+    impl Serialize for Raw {
+        fn begin(&self, v: Visitor, c: &mut dyn Context) -> Done {
+            // Safety: keys are plain identifiers
+            unsafe { v.map().field_raw_key("code", &200u32, c) }
+                .field("message", &"ok", c)
+                .done()
+        }
+    }
+
+    assert_eq!(
+        json::to_string(&Raw, &mut ()),
+        r#"{"code":200,"message":"ok"}"#
+    );
+
+    // Formats without a specialized implementation fallback to `field`
+    let bin = toctoc::bson::to_bin(&Raw, &mut ());
+    let out: BTreeMap<String, Value> = toctoc::bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(out.len(), 2);
+}
+
+#[test]
+fn test_to_string_into() {
+    let value: BTreeMap<String, Vec<u32>> =
+        (0..4).map(|i| (i.to_string(), (0..i).collect())).collect();
+
+    let mut buf = String::new();
+    json::to_string_into(&value, &mut (), &mut buf);
+    assert_eq!(buf, json::to_string(&value, &mut ()));
+
+    // No reallocation after the first call
+    let capacity = buf.capacity();
+    let ptr = buf.as_ptr();
+    for _ in 0..1000 {
+        json::to_string_into(&value, &mut (), &mut buf);
+        assert_eq!(buf.capacity(), capacity);
+        assert_eq!(buf.as_ptr(), ptr);
+    }
+    assert_eq!(buf, json::to_string(&value, &mut ()));
+}
+
+#[test]
+fn test_reused_ser() {
+    let value: BTreeMap<String, Vec<u32>> =
+        (0..4).map(|i| (i.to_string(), (0..i).collect())).collect();
+    let expected = json::to_string(&value, &mut ());
+
+    let mut ser = JsonSer::new();
+    assert_eq!(ser.serialize(&value, &mut ()), expected);
+
+    // No reallocation after the first call
+    let ptr = ser.serialize(&value, &mut ()).as_ptr();
+    for _ in 0..1000 {
+        let j = ser.serialize(&value, &mut ());
+        assert_eq!(j, expected);
+        assert_eq!(j.as_ptr(), ptr);
+    }
+
+    // Smaller outputs reuse the same buffer too
+    assert_eq!(ser.serialize(&1u8, &mut ()), "1");
+    assert_eq!(ser.serialize(&1u8, &mut ()).as_ptr(), ptr);
+    ser.clear();
+    assert_eq!(ser.serialize(&value, &mut ()).as_ptr(), ptr);
+
+    // Pretty printing starts from the first indentation level every time
+    let mut ser = JsonSer::pretty("  ");
+    let expected = json::to_string_pretty(&value, &mut (), "  ");
+    for _ in 0..3 {
+        assert_eq!(ser.serialize(&value, &mut ()), expected);
+    }
+}
+
+#[test]
+fn test_to_writer() {
+    // Big enough to be written in many chunks
+    let value: Vec<String> = (0..10_000).map(|i| format!("item,{}", i)).collect();
+    let mut out = vec![];
+    json::to_writer(&value, &mut (), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        json::to_string(&value, &mut ())
+    );
+
+    // Values serialized one after the other are all written
+    let mut out = vec![];
+    let mut ser = JsonSer::with_writer(&mut out);
+    ser.serialize(&value, &mut ());
+    ser.serialize(&vec![1, 2], &mut ());
+    assert!(!ser.serialize(&value, &mut ()).is_empty());
+    ser.finish().unwrap();
+    drop(ser);
+    let expected = json::to_string(&value, &mut ());
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        format!("{}[1,2]{}", expected, expected)
+    );
+
+    // Writer errors are returned
+    struct Full(usize);
+    impl std::io::Write for Full {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.0 < buf.len() {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            self.0 -= buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    assert!(json::to_writer(&value, &mut (), Full(10_000)).is_err());
+    assert!(json::to_writer(&value, &mut (), Full(1_000_000)).is_ok());
+}
+
+#[test]
+fn test_sets() {
+    let mut j = r#"["b","a","c","a"]"#.to_string();
+    let set: BTreeSet<String> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(set.len(), 3);
+    let mut j = json::to_string(&set, &mut ());
+    assert_eq!(j, r#"["a","b","c"]"#);
+    let actual: BTreeSet<String> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, set);
+
+    let mut j = r#"[3,1,2,3]"#.to_string();
+    let set: HashSet<u32> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(set, [1, 2, 3].iter().cloned().collect());
+    let mut j = json::to_string(&set, &mut ());
+    let actual: HashSet<u32> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, set);
+}
+
+#[test]
+fn test_char() {
+    for ch in &['a', 'ß', '€', '😀'] {
+        let mut j = json::to_string(ch, &mut ());
+        assert_eq!(j, format!("\"{}\"", ch));
+        let actual: char = json::from_str(&mut j, &mut ()).unwrap();
+        assert_eq!(actual, *ch);
+    }
+
+    for j in &[r#""""#, r#""ab""#, r#""a😀""#] {
+        let mut j = j.to_string();
+        assert!(json::from_str::<char>(&mut j, &mut ()).is_err());
+    }
+}
+
+#[test]
+fn test_deque_and_list() {
+    let mut j = "[1,2,3]".to_string();
+    let deque: VecDeque<u32> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(deque, VecDeque::from(vec![1, 2, 3]));
+    assert_eq!(json::to_string(&deque, &mut ()), "[1,2,3]");
+
+    let mut j = r#"["a","b"]"#.to_string();
+    let list: LinkedList<String> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(list.iter().collect::<Vec<_>>(), vec!["a", "b"]);
+    assert_eq!(json::to_string(&list, &mut ()), r#"["a","b"]"#);
+}
+
+#[test]
+#[cfg(feature = "slow-tests")]
+fn test_large_deque() {
+    let deque: VecDeque<u64> = (0..1_000_000).collect();
+    let mut j = json::to_string(&deque, &mut ());
+    let actual: VecDeque<u64> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, deque);
+}
+
+#[test]
+fn test_from_slice() {
+    let mut j = br#"{"a":"b\nc"}"#.to_vec();
+    let actual: BTreeMap<String, &str> = json::from_slice(&mut j, &mut ()).unwrap();
+    assert_eq!(actual["a"], "b\nc");
+
+    let mut j = b"[\"\xff\"]".to_vec();
+    let err = json::from_slice::<Vec<String>>(&mut j, &mut ()).unwrap_err();
+    assert!(matches!(err.kind(), toctoc::ErrorKind::InvalidUtf8));
+}
+
+#[test]
+fn test_from_reader() {
+    let reader = std::io::Cursor::new(r#"["a","b"]"#);
+    let actual: Vec<String> = json::from_reader(reader, &mut ()).unwrap();
+    assert_eq!(actual, vec!["a", "b"]);
+
+    // Large file
+    let value: Vec<(u64, String)> = (0..200_000).map(|i| (i, i.to_string())).collect();
+    let path = std::env::temp_dir().join("toctoc_test_from_reader.json");
+    let file = std::fs::File::create(&path).unwrap();
+    json::to_writer(&value, &mut (), std::io::BufWriter::new(file)).unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let actual: Vec<(u64, String)> = json::from_reader(file, &mut ()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(actual, value);
+
+    let reader = std::io::Cursor::new(&b"[\"\xff\"]"[..]);
+    assert!(json::from_reader::<Vec<String>, _>(reader, &mut ()).is_err());
+}
+
+#[test]
+fn test_ndjson() {
+    let value = vec![vec![1, 2], vec![], vec![3]];
+    let mut j = json::to_ndjson(&value, &mut ());
+    assert_eq!(j, "[1,2]\n[]\n[3]\n");
+    let actual: Vec<Vec<u32>> = json::from_ndjson(&mut j, &mut ())
+        .collect::<toctoc::Result<_>>()
+        .unwrap();
+    assert_eq!(actual, value);
+
+    // Invalid lines don't stop the iteration
+    let mut j = "{\"a\":1}\n  \n{\"a\":\n\r\n{\"b\":2}\r\n[]".to_string();
+    let lines: Vec<toctoc::Result<BTreeMap<String, u32>>> =
+        json::from_ndjson(&mut j, &mut ()).collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0].as_ref().unwrap()["a"], 1);
+    assert!(lines[1].is_err());
+    assert_eq!(lines[2].as_ref().unwrap()["b"], 2);
+    assert!(lines[3].is_err());
+}
+
+#[test]
+fn test_to_vec() {
+    let value = vec!["plain", "esc\"aped\n", "ünicode ✓ 😀"];
+    let expected = json::to_string(&value, &mut ());
+
+    let v = json::to_vec(&value, &mut ());
+    assert_eq!(std::str::from_utf8(&v).unwrap(), expected);
+
+    let mut buf = b"old contents".to_vec();
+    json::to_vec_into(&value, &mut (), &mut buf);
+    assert_eq!(buf, v);
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "simd")]
+
+use simd_json::Node;
+use toctoc::json::JsonDe;
+
+#[test]
+fn test_next_back() {
+    let mut j = r#"[{"status":"a"},"b",1,{"status":"c"},2]"#.to_string();
+    let mut de = JsonDe::new(&mut j).unwrap();
+
+    // Last string token
+    let last = de.by_ref().rev().find_map(|node| match node {
+        Node::String(s) => Some(s),
+        _ => None,
+    });
+    assert_eq!(last, Some("c"));
+
+    // Front and back never cross each other
+    let mut j = r#"["x","y"]"#.to_string();
+    let mut de = JsonDe::new(&mut j).unwrap();
+    let count = de.by_ref().count();
+    assert_eq!(de.next_back().map(|_| ()), None);
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn test_size_hint() {
+    use toctoc::de::{Context, Deserializer, Map, Seq, Visitor};
+    use toctoc::json;
+
+    #[derive(Default)]
+    struct Hints(Vec<(usize, Option<usize>)>);
+
+    impl<'de> Visitor<'de> for Hints {
+        fn nonnegative(&mut self, _: u64, _: &mut dyn Context) -> toctoc::Result<()> {
+            Ok(())
+        }
+
+        fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> toctoc::Result<()> {
+            self.0.push(s.size_hint());
+            while s.visit(self, c)? {
+                self.0.push(s.size_hint());
+            }
+            Ok(())
+        }
+
+        fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> toctoc::Result<()> {
+            self.0.push(m.size_hint());
+            while m.next()?.is_some() {
+                m.visit(self, c)?;
+                self.0.push(m.size_hint());
+            }
+            Ok(())
+        }
+    }
+
+    let mut j = r#"[1,{"a":1,"b":[]}]"#.to_string();
+    let mut hints = Hints::default();
+    Deserializer::from(&mut JsonDe::new(&mut j).unwrap())
+        .deserialize(&mut hints, &mut ())
+        .unwrap();
+    let exact = |n| (n, Some(n));
+    assert_eq!(
+        hints.0,
+        vec![
+            exact(2), // [1,{..}]
+            exact(1),
+            exact(2), // {"a":1,"b":[]}
+            exact(1),
+            exact(0), // []
+            exact(0),
+            exact(0),
+        ]
+    );
+
+    // Collections are allocated upfront
+    let mut j = "[1,2,3,4,5]".to_string();
+    let v: Vec<u32> = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(v.capacity(), 5);
+}
+
+#[test]
+fn test_size_hint_after_ignore() {
+    use toctoc::de::{Context, Deserializer, Map, Seq, Visitor};
+
+    #[derive(Default)]
+    struct Hints(Vec<(usize, Option<usize>)>);
+
+    impl<'de> Visitor<'de> for Hints {
+        fn nonnegative(&mut self, _: u64, _: &mut dyn Context) -> toctoc::Result<()> {
+            Ok(())
+        }
+
+        fn seq(&mut self, s: &mut dyn Seq<'de>, c: &mut dyn Context) -> toctoc::Result<()> {
+            s.visit(self, c)?;
+            s.ignore_remaining(c)?;
+            self.0.push(s.size_hint());
+            Ok(())
+        }
+
+        fn map(&mut self, m: &mut dyn Map<'de>, c: &mut dyn Context) -> toctoc::Result<()> {
+            m.next()?;
+            m.visit(self, c)?;
+            m.ignore_remaining(c)?;
+            self.0.push(m.size_hint());
+            Ok(())
+        }
+    }
+
+    let mut j = r#"[{"a":1,"b":2,"c":3},4,5]"#.to_string();
+    let mut hints = Hints::default();
+    Deserializer::from(&mut JsonDe::new(&mut j).unwrap())
+        .deserialize(&mut hints, &mut ())
+        .unwrap();
+    assert_eq!(hints.0, vec![(0, Some(0)), (0, Some(0))]);
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use toctoc::ser::SystemTimeUnix;
+use toctoc::{bson, json};
+
+#[test]
+fn test_unix() {
+    let t = SystemTimeUnix(UNIX_EPOCH + Duration::from_secs(1_705_314_600));
+    let mut j = json::to_string(&t, &mut ());
+    assert_eq!(j, "1705314600");
+    let actual: SystemTimeUnix = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, t);
+
+    // Times before the epoch are negative
+    let t = SystemTimeUnix(UNIX_EPOCH - Duration::from_secs(86_400));
+    let mut j = json::to_string(&t, &mut ());
+    assert_eq!(j, "-86400");
+    let actual: SystemTimeUnix = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, t);
+
+    let mut j = r#""1705314600""#.to_string();
+    assert!(json::from_str::<SystemTimeUnix>(&mut j, &mut ()).is_err());
+}
+
+#[test]
+fn test_unix_round_trip() {
+    // Only whole seconds are kept
+    let now = SystemTime::now();
+    let secs = now.duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    let mut j = json::to_string(&SystemTimeUnix(now), &mut ());
+    let actual: SystemTimeUnix = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual.0, UNIX_EPOCH + Duration::from_secs(secs));
+
+    let bin = bson::to_bin(&vec![SystemTimeUnix(now)], &mut ());
+    let actual: Vec<SystemTimeUnix> = bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual[0].0, UNIX_EPOCH + Duration::from_secs(secs));
+
+    // Rounds down before the epoch too
+    let t = SystemTimeUnix(UNIX_EPOCH - Duration::from_millis(1_500));
+    assert_eq!(json::to_string(&t, &mut ()), "-2");
+}
+
+#[cfg(feature = "rfc3339")]
+#[test]
+fn test_rfc3339() {
+    use toctoc::ser::SystemTimeRfc3339;
+
+    let t = SystemTimeRfc3339(UNIX_EPOCH + Duration::from_secs(1_705_314_600));
+    let mut j = json::to_string(&t, &mut ());
+    assert_eq!(j, r#""2024-01-15T10:30:00Z""#);
+    let actual: SystemTimeRfc3339 = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, t);
+
+    // Offsets are converted to UTC
+    let mut j = r#""2024-01-15T12:30:00+02:00""#.to_string();
+    let actual: SystemTimeRfc3339 = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, t);
+
+    let t = SystemTimeRfc3339(UNIX_EPOCH - Duration::from_secs(1));
+    let mut j = json::to_string(&t, &mut ());
+    assert_eq!(j, r#""1969-12-31T23:59:59Z""#);
+    let actual: SystemTimeRfc3339 = json::from_str(&mut j, &mut ()).unwrap();
+    assert_eq!(actual, t);
+
+    let now = SystemTimeRfc3339(SystemTime::now());
+    let bin = bson::to_bin(&vec![now], &mut ());
+    let actual: Vec<SystemTimeRfc3339> = bson::from_bin(&bin, &mut ()).unwrap();
+    assert_eq!(actual[0], now);
+
+    let mut j = r#""2024-01-15 10:30""#.to_string();
+    assert!(json::from_str::<SystemTimeRfc3339>(&mut j, &mut ()).is_err());
+}
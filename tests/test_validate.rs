@@ -0,0 +1,127 @@
+use toctoc::{bson, json, ErrorKind};
+
+#[test]
+fn test_json_valid() {
+    assert!(json::validate("null").is_ok());
+    assert!(json::validate(r#" {"a": [1, -2.5e3, "é\n"], "b": {}} "#).is_ok());
+
+    // Nesting isn't limited and doesn't recurse
+    let deep = format!("{}{}", "[".repeat(1_000), "]".repeat(1_000));
+    assert!(json::validate(&deep).is_ok());
+}
+
+#[test]
+fn test_json_malformed() {
+    let inputs = [
+        "",
+        "nul",
+        "[1,]",
+        r#"{"a":1,}"#,
+        r#"{"a" 1}"#,
+        r#"{1:1}"#,
+        r#"[{"a":[1,2}]"#,
+        r#"[[[["unterminated]]]]"#,
+        r#"["\q"]"#,
+        "[01]",
+        "[1.e3]",
+        "[1e999]",
+        "[1] 2",
+    ];
+    for input in &inputs {
+        assert!(json::validate(input).is_err(), "`{}` passed", input);
+    }
+
+    // The input isn't modified
+    let j = r#"["\n"]"#;
+    json::validate(j).unwrap();
+    assert_eq!(j, r#"["\n"]"#);
+}
+
+/// Wraps `elements` into a document
+fn doc(elements: &[u8]) -> Vec<u8> {
+    let size = (elements.len() + 5) as u32;
+    let mut bin = size.to_le_bytes().to_vec();
+    bin.extend_from_slice(elements);
+    bin.push(0);
+    bin
+}
+
+/// Root document holding a single embedded document with `elements`
+fn nested(elements: &[u8]) -> Vec<u8> {
+    let mut root = vec![0x03, 0];
+    root.extend(doc(elements));
+    doc(&root)
+}
+
+#[test]
+fn test_bson_valid() {
+    let bin = bson::to_bin(&vec![vec!["a", "b"], vec![]], &mut ());
+    assert!(bson::validate(&bin).is_ok());
+
+    assert!(bson::validate(&nested(&[0x08, b'a', 0, 1])).is_ok());
+}
+
+#[test]
+fn test_bson_malformed() {
+    let bin = bson::to_bin(&vec![1u32, 2, 3], &mut ());
+    assert!(bson::validate(&bin[..bin.len() - 1]).is_err());
+    assert!(bson::validate(&bin[..3]).is_err());
+
+    let mut extra = bin.clone();
+    extra.push(0);
+    assert!(bson::validate(&extra).is_err());
+
+    // Unknown type code, inside a document that would be skipped when ignored
+    assert!(bson::validate(&nested(&[0x7F, b'a', 0])).is_err());
+
+    // Embedded document size past the end of the root document
+    let mut bin = nested(&[0x08, b'a', 0, 1]);
+    bin[6] = 0xFF;
+    assert!(bson::validate(&bin).is_err());
+
+    // Elements overflow the embedded document size
+    let mut bin = nested(&[0x08, b'a', 0, 1]);
+    bin[6] -= 2;
+    assert!(bson::validate(&bin).is_err());
+
+    // String sizes
+    let string = |size: u32, s: &[u8]| {
+        let mut e = vec![0x02, b'a', 0];
+        e.extend_from_slice(&size.to_le_bytes());
+        e.extend_from_slice(s);
+        nested(&e)
+    };
+    assert!(bson::validate(&string(2, b"a\0")).is_ok());
+    assert!(bson::validate(&string(0, b"")).is_err());
+    assert!(bson::validate(&string(200, b"a\0")).is_err());
+
+    let err = bson::validate(&string(2, b"\xFF\0")).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::InvalidUtf8));
+
+    // Field names must also be utf8
+    let bin = nested(&[0x08, b'a', 0xFF, 0, 1]);
+    let err = bson::validate(&bin).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::InvalidUtf8));
+    assert!(bson::from_bin::<bson::Value>(&bin, &mut ()).is_err());
+    let mut out = String::new();
+    assert!(bson::stream_to_json(&bin, &mut (), &mut out).is_err());
+
+    // Field name without its '\0'
+    let mut bin = doc(&[0x08, b'a']);
+    bin.pop();
+    bin[0] -= 1;
+    assert!(bson::validate(&bin).is_err());
+
+    // Deeply nested documents
+    let mut bin = doc(&[0x0A, 0]);
+    for _ in 0..200 {
+        let mut e = vec![0x03, 0];
+        e.extend(bin);
+        bin = doc(&e);
+    }
+    // The limit is reached within the 2 MiB stack of a spawned thread, debug builds included
+    let err = std::thread::spawn(move || bson::validate(&bin).unwrap_err())
+        .join()
+        .unwrap();
+    assert!(matches!(err.kind(), ErrorKind::DepthExceeded(128)));
+}
@@ -45,3 +45,55 @@ fn test_zerocopy_to_owned() {
 
     assert_eq!(mo, expected);
 }
+
+#[test]
+fn test_zerocopy_owned_mut() {
+    let b = toctoc::bson::to_bin(&vec!["a", "bc"], &mut ());
+    let mut v = toctoc::bson::from_bin_owned::<Vec<&str>>(b, &mut ()).unwrap();
+    unsafe {
+        assert_eq!(*v.as_ref(), vec!["a", "bc"]);
+
+        let v = v.as_mut();
+        v.reverse();
+        v.pop();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0], "bc");
+    }
+}
+
+#[test]
+fn test_zerocopy_iter() {
+    let b = toctoc::bson::to_bin(&vec!["a", "bc", "def"], &mut ());
+    let ptr = b.as_ptr() as usize;
+    let len = b.len();
+
+    // The items don't outlive the iterator
+    let mut iter = unsafe { toctoc::bson::from_bin_iter::<&str>(b, &mut ()).unwrap() };
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+
+    for expected in &["a", "bc", "def"] {
+        let s = iter.next().unwrap();
+        assert_eq!(s, *expected);
+        // Still borrows from the original data
+        assert!((s.as_ptr() as usize) >= ptr && (s.as_ptr() as usize) < ptr + len);
+    }
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_zerocopy_map() {
+    let j = r#"{ "sender": "you", "text": "hi!" }"#.to_string();
+    let m = json::from_str_owned::<Message>(j, &mut ()).unwrap();
+    let sender: json::Owned<String> = unsafe { m.map(|m| m.sender.to_string()) };
+    assert_eq!(unsafe { sender.as_ref() }, "you");
+
+    let j = r#"{ "sender": "you", "text": "hi!" }"#.to_string();
+    let m = json::from_str_owned::<Message>(j, &mut ()).unwrap();
+    let len = unsafe { m.try_map(|m| m.text.parse::<u32>()) };
+    assert!(len.is_err());
+
+    let j = r#"{ "sender": "you", "text": "42" }"#.to_string();
+    let m = json::from_str_owned::<Message>(j, &mut ()).unwrap();
+    let n = unsafe { m.try_map(|m| m.text.parse::<u32>()) }.unwrap();
+    assert_eq!(unsafe { *n.as_ref() }, 42);
+}